@@ -1,7 +1,10 @@
 //! This contains the [`BenchContext`], which is information that all replay-based benchmarks need.
 //! The initialization code is also the same, so this can be shared across benchmark commands.
 
-use crate::{authenticated_transport::AuthenticatedTransportConnect, bench_mode::BenchMode};
+use crate::{
+    authenticated_transport::AuthenticatedTransportConnect, bench::output::TxGasBreakdown,
+    bench_mode::BenchMode, valid_payload::WaitConfig,
+};
 use alloy_eips::BlockNumberOrTag;
 use alloy_provider::{
     network::{AnyNetwork, Ethereum},
@@ -13,13 +16,14 @@ use alloy_transport::BoxTransport;
 use alloy_transport_http::Http;
 use reqwest::{Client, Url};
 use reth_node_core::args::BenchmarkArgs;
+use reth_primitives::SealedBlock;
 use tracing::info;
 
 /// This is intended to be used by benchmarks that replay blocks from an RPC.
 ///
 /// It contains an authenticated provider for engine API queries, a block provider for block
 /// queries, a [`BenchMode`] to determine whether the benchmark should run for a closed or open
-/// range of blocks, and the next block to fetch.
+/// range of blocks, the next block to fetch, and the [`WaitConfig`] of the engine API calls.
 pub(crate) struct BenchContext {
     /// The auth provider used for engine API queries.
     pub(crate) auth_provider: RootProvider<BoxTransport, AnyNetwork>,
@@ -30,6 +34,8 @@ pub(crate) struct BenchContext {
     pub(crate) benchmark_mode: BenchMode,
     /// The next block to fetch.
     pub(crate) next_block: u64,
+    /// The config of the engine API calls, see [`wait_config`].
+    pub(crate) wait_config: WaitConfig,
 }
 
 impl BenchContext {
@@ -53,24 +59,8 @@ impl BenchContext {
         let mut benchmark_mode = BenchMode::new(bench_args.from, bench_args.to)?;
 
         // construct the authenticated provider
-        let auth_jwt = bench_args.auth_jwtsecret.clone().ok_or_else(|| {
-            eyre::eyre!("--auth-jwtsecret must be provided for authenticated RPC")
-        })?;
-
-        // fetch jwt from file
-        //
-        // the jwt is hex encoded so we will decode it after
-        let jwt = std::fs::read_to_string(auth_jwt)?;
-        let jwt = JwtSecret::from_hex(jwt)?;
-
-        // get engine url
-        let auth_url = Url::parse(&bench_args.engine_rpc_url)?;
-
-        // construct the authed transport
-        info!("Connecting to Engine RPC at {} for replay", auth_url);
-        let auth_transport = AuthenticatedTransportConnect::new(auth_url, jwt);
-        let client = ClientBuilder::default().connect_boxed(auth_transport).await?;
-        let auth_provider = RootProvider::<_, AnyNetwork>::new(client);
+        let jwt = read_jwt_secret(bench_args)?;
+        let auth_provider = connect_engine(&bench_args.engine_rpc_url, jwt).await?;
 
         let first_block = match benchmark_mode {
             BenchMode::Continuous => {
@@ -106,6 +96,268 @@ impl BenchContext {
             }
         };
 
-        Ok(Self { auth_provider, block_provider, benchmark_mode, next_block })
+        let wait_config = wait_config(bench_args);
+        Ok(Self { auth_provider, block_provider, benchmark_mode, next_block, wait_config })
+    }
+}
+
+/// Reads the JWT secret of the engine API from the `--jwtsecret` file of the given
+/// [`BenchmarkArgs`].
+pub(crate) fn read_jwt_secret(bench_args: &BenchmarkArgs) -> eyre::Result<JwtSecret> {
+    let auth_jwt = bench_args
+        .auth_jwtsecret
+        .clone()
+        .ok_or_else(|| eyre::eyre!("--auth-jwtsecret must be provided for authenticated RPC"))?;
+
+    // fetch jwt from file
+    //
+    // the jwt is hex encoded so we will decode it after
+    let jwt = std::fs::read_to_string(auth_jwt)?;
+    Ok(JwtSecret::from_hex(jwt)?)
+}
+
+/// Connects an authenticated provider for engine API queries to the engine at the given url.
+pub(crate) async fn connect_engine(
+    engine_rpc_url: &str,
+    jwt: JwtSecret,
+) -> eyre::Result<RootProvider<BoxTransport, AnyNetwork>> {
+    // get engine url
+    let auth_url = Url::parse(engine_rpc_url)?;
+
+    // construct the authed transport
+    info!("Connecting to Engine RPC at {} for replay", auth_url);
+    let auth_transport = AuthenticatedTransportConnect::new(auth_url, jwt);
+    let client = ClientBuilder::default().connect_boxed(auth_transport).await?;
+    Ok(RootProvider::<_, AnyNetwork>::new(client))
+}
+
+/// Returns the [`WaitConfig`] of the engine API calls of a benchmark with the given
+/// [`BenchmarkArgs`].
+///
+/// If `--chain` is set, the engine API version of every block is selected from the chain spec,
+/// see [`WaitConfig::engine_version`], and with `--validate-payloads` the structure of every block
+/// is checked against it, see [`WaitConfig::validate_payloads`]. The hardforks of
+/// `--fork-override` take precedence over the chain spec, see [`WaitConfig::fork_overrides`].
+pub(crate) fn wait_config(bench_args: &BenchmarkArgs) -> WaitConfig {
+    let mut wait_config = WaitConfig::default();
+    match &bench_args.chain {
+        Some(chain_spec) if bench_args.validate_payloads => {
+            wait_config = wait_config.with_payload_validation(chain_spec.clone());
+        }
+        Some(chain_spec) => wait_config = wait_config.with_chain_spec(chain_spec.clone()),
+        None => {}
+    }
+    for (fork, timestamp) in &bench_args.fork_overrides {
+        wait_config = wait_config.with_fork_override(*fork, *timestamp);
+    }
+    if bench_args.verify_hashes {
+        wait_config = wait_config.with_verify_hashes();
+    }
+    if bench_args.engine_version_fallback {
+        wait_config = wait_config.with_version_fallback();
+    }
+    wait_config
+}
+
+/// Fetches the receipts of the given block from the block provider, and breaks down the gas used
+/// by its transactions by type, see [`TxGasBreakdown::from_block`].
+pub(crate) async fn fetch_tx_gas_breakdown(
+    block_provider: &RootProvider<Http<Client>, Ethereum>,
+    block: &SealedBlock,
+) -> eyre::Result<TxGasBreakdown> {
+    let receipts = block_provider
+        .get_block_receipts(block.number.into())
+        .await?
+        .ok_or_else(|| eyre::eyre!("receipts of block {} not found", block.number))?;
+    let gas_used = receipts.iter().map(|receipt| receipt.gas_used as u64).collect::<Vec<_>>();
+    TxGasBreakdown::from_block(block, &gas_used).ok_or_else(|| {
+        eyre::eyre!(
+            "block {} has {} transactions, but {} receipts",
+            block.number,
+            block.body.len(),
+            gas_used.len()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valid_payload::{call_new_payload, tests::MockEngineApi, EngineWaitError};
+    use alloy_rpc_types_engine::{PayloadStatus, PayloadStatusEnum};
+    use clap::Parser;
+    use reth_node_api::EngineApiMessageVersion;
+    use reth_primitives::{constants::EMPTY_ROOT_HASH, Block, Header, B256};
+    use reth_rpc_types_compat::engine::payload::block_to_payload;
+
+    /// A chain that merges at block 10, activates Shanghai at genesis, Cancun at timestamp 10 and
+    /// Prague at timestamp 20.
+    const CHAIN: &str = r#"{
+        "config": {
+            "chainId": 1337,
+            "homesteadBlock": 0,
+            "eip150Block": 0,
+            "eip155Block": 0,
+            "eip158Block": 0,
+            "byzantiumBlock": 0,
+            "constantinopleBlock": 0,
+            "petersburgBlock": 0,
+            "istanbulBlock": 0,
+            "berlinBlock": 0,
+            "londonBlock": 0,
+            "mergeNetsplitBlock": 10,
+            "terminalTotalDifficulty": 0,
+            "shanghaiTime": 0,
+            "cancunTime": 10,
+            "pragueTime": 20
+        },
+        "difficulty": "0x0",
+        "gasLimit": "0x1c9c380",
+        "alloc": {}
+    }"#;
+
+    /// A helper type to parse the [`BenchmarkArgs`] of a benchmark command.
+    #[derive(Parser)]
+    struct CommandParser {
+        #[command(flatten)]
+        args: BenchmarkArgs,
+    }
+
+    /// Returns the [`WaitConfig`] of a benchmark command with the given arguments.
+    fn command_wait_config(args: &[&str]) -> WaitConfig {
+        let args = std::iter::once("reth-bench").chain(args.iter().copied());
+        wait_config(&CommandParser::parse_from(args).args)
+    }
+
+    /// Returns a block with the given number and timestamp, with the fields of Cancun.
+    fn cancun_block(number: u64, timestamp: u64) -> Block {
+        Block {
+            header: Header {
+                number,
+                timestamp,
+                base_fee_per_gas: Some(7),
+                withdrawals_root: Some(EMPTY_ROOT_HASH),
+                parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                blob_gas_used: Some(0),
+                excess_blob_gas: Some(0),
+                ..Default::default()
+            },
+            withdrawals: Some(Default::default()),
+            ..Default::default()
+        }
+    }
+
+    /// Sends the given block to the engine like the benchmark commands do, and returns the
+    /// engine API version of the call together with the called methods.
+    async fn send_block(
+        block: Block,
+        config: &WaitConfig,
+    ) -> (Result<EngineApiMessageVersion, EngineWaitError>, Vec<String>) {
+        let block = block.seal_slow();
+        let mock = MockEngineApi::default();
+        mock.push_success(PayloadStatus::new(PayloadStatusEnum::Valid, Some(block.hash())));
+        let versioned_hashes = block.blob_versioned_hashes().into_iter().copied().collect();
+        let (payload, parent_beacon_block_root) = block_to_payload(block);
+        let res = call_new_payload(
+            mock.provider(),
+            payload,
+            parent_beacon_block_root,
+            versioned_hashes,
+            config,
+        )
+        .await
+        .map(|(version, _)| version);
+        (res, mock.calls())
+    }
+
+    #[tokio::test]
+    async fn chain_selects_engine_version() {
+        // without a chain, the version is derived from the fields of the block
+        let (version, calls) = send_block(cancun_block(30, 20), &command_wait_config(&[])).await;
+        assert_eq!(version.unwrap(), EngineApiMessageVersion::V3);
+        assert_eq!(calls, ["engine_newPayloadV3"]);
+
+        // the block is at the activation of Prague
+        let config = command_wait_config(&["--chain", CHAIN]);
+        let (version, calls) = send_block(cancun_block(30, 20), &config).await;
+        assert_eq!(version.unwrap(), EngineApiMessageVersion::V4);
+        assert_eq!(calls, ["engine_newPayloadV4"]);
+
+        let (version, calls) = send_block(cancun_block(30, 19), &config).await;
+        assert_eq!(version.unwrap(), EngineApiMessageVersion::V3);
+        assert_eq!(calls, ["engine_newPayloadV3"]);
+    }
+
+    #[tokio::test]
+    async fn fork_overrides_select_engine_version() {
+        let config = command_wait_config(&["--chain", CHAIN, "--fork-override", "prague=30"]);
+        let (version, calls) = send_block(cancun_block(30, 20), &config).await;
+        assert_eq!(version.unwrap(), EngineApiMessageVersion::V3);
+        assert_eq!(calls, ["engine_newPayloadV3"]);
+
+        let (version, calls) = send_block(cancun_block(30, 30), &config).await;
+        assert_eq!(version.unwrap(), EngineApiMessageVersion::V4);
+        assert_eq!(calls, ["engine_newPayloadV4"]);
+    }
+
+    #[tokio::test]
+    async fn chain_rejects_pre_merge_blocks() {
+        let config = command_wait_config(&["--chain", CHAIN]);
+        let (res, calls) = send_block(cancun_block(9, 10), &config).await;
+        assert!(matches!(res, Err(EngineWaitError::PreMergeBlock { block_number: 9 })), "{res:?}");
+        assert!(calls.is_empty());
+
+        let (res, calls) = send_block(cancun_block(10, 10), &config).await;
+        res.unwrap();
+        assert_eq!(calls, ["engine_newPayloadV3"]);
+    }
+
+    #[tokio::test]
+    async fn validate_payloads_before_sending() {
+        let config = command_wait_config(&["--chain", CHAIN, "--validate-payloads"]);
+        let (res, calls) = send_block(cancun_block(10, 10), &config).await;
+        res.unwrap();
+        assert_eq!(calls, ["engine_newPayloadV3"]);
+
+        // the block has the fields of Cancun before Cancun, so it is sent as a V2 payload without
+        // them, whose block hash does not match the block anymore
+        let (res, calls) = send_block(cancun_block(10, 9), &config).await;
+        assert!(
+            matches!(
+                res,
+                Err(EngineWaitError::MalformedPayload { method: "engine_newPayloadV2", .. })
+            ),
+            "{res:?}"
+        );
+        assert!(calls.is_empty());
+
+        // without the flag, the engine is left to reject it
+        let config = command_wait_config(&["--chain", CHAIN]);
+        let (_, calls) = send_block(cancun_block(10, 9), &config).await;
+        assert_eq!(calls, ["engine_newPayloadV2"]);
+    }
+
+    #[tokio::test]
+    async fn chain_rejects_zero_parent_beacon_block_root() {
+        let mut block = cancun_block(10, 10);
+        block.header.parent_beacon_block_root = Some(B256::ZERO);
+
+        let config = command_wait_config(&["--chain", CHAIN]);
+        let (res, calls) = send_block(block.clone(), &config).await;
+        assert!(
+            matches!(res, Err(EngineWaitError::ZeroParentBeaconBlockRoot { timestamp: 10, .. })),
+            "{res:?}"
+        );
+        assert!(calls.is_empty());
+
+        // without a chain, it is not known whether Cancun is active
+        let (_, calls) = send_block(block, &command_wait_config(&[])).await;
+        assert_eq!(calls, ["engine_newPayloadV3"]);
+    }
+
+    #[test]
+    fn validate_payloads_requires_chain() {
+        let args = ["reth-bench", "--validate-payloads"];
+        assert!(CommandParser::try_parse_from(args).is_err());
     }
 }
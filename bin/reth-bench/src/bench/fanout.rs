@@ -0,0 +1,135 @@
+//! Runs the `reth bench fanout` command, sending every block to several engines concurrently and
+//! comparing their results.
+
+use crate::{
+    bench::{
+        context::{connect_engine, read_jwt_secret, BenchContext},
+        output::LatencySummary,
+    },
+    fanout::{fan_out_new_payload_v3, FanOutReport},
+    rpc_source::RpcBlockSource,
+    valid_payload::EngineApiValidWaitExt,
+};
+use clap::Parser;
+use reth_cli_runner::CliContext;
+use reth_node_core::args::BenchmarkArgs;
+use tracing::{debug, info, warn};
+
+/// `reth benchmark fanout` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The RPC url to use for getting data.
+    #[arg(long, value_name = "RPC_URL", verbatim_doc_comment)]
+    rpc_url: String,
+
+    /// The url of another engine to send every block to, next to `--engine-rpc-url`.
+    ///
+    /// Can be given multiple times. All engines are authenticated with the same `--jwtsecret`.
+    #[arg(
+        long = "fanout-engine-rpc-url",
+        value_name = "ENGINE_RPC_URL",
+        required = true,
+        verbatim_doc_comment
+    )]
+    fanout_engine_rpc_urls: Vec<String>,
+
+    #[command(flatten)]
+    benchmark: BenchmarkArgs,
+}
+
+impl Command {
+    /// Execute `benchmark fanout` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        let BenchContext {
+            benchmark_mode,
+            block_provider,
+            auth_provider,
+            mut next_block,
+            wait_config,
+        } = BenchContext::new(&self.benchmark, self.rpc_url).await?;
+
+        // endpoint 0 is `--engine-rpc-url`, the others follow in the order they were given
+        let jwt = read_jwt_secret(&self.benchmark)?;
+        let mut engines = vec![auth_provider];
+        for engine_rpc_url in &self.fanout_engine_rpc_urls {
+            engines.push(connect_engine(engine_rpc_url, jwt).await?);
+        }
+        for (endpoint, engine) in engines.iter().enumerate() {
+            // warm up the engine connection, so the first timed call does not pay for the
+            // handshake
+            engine.prepare().await?;
+            let engine_rpc_url = match endpoint {
+                0 => &self.benchmark.engine_rpc_url,
+                _ => &self.fanout_engine_rpc_urls[endpoint - 1],
+            };
+            info!(endpoint, %engine_rpc_url, "Fanning out to engine");
+        }
+
+        let source = RpcBlockSource::new(block_provider);
+        let mut report = FanOutReport::default();
+        // the warmup blocks are still compared, but left out of the latencies
+        let mut warmup_report = FanOutReport::default();
+        let mut warmup_blocks = self.benchmark.warmup;
+        while benchmark_mode.contains(next_block) {
+            let block = source.fetch_replay_block(next_block).await?;
+            next_block += 1;
+
+            let block_number = block.block_number();
+            debug!(?block_number, "Sending payload to engines");
+            let result = fan_out_new_payload_v3(&engines, &block, &wait_config).await;
+            for (endpoint, res) in result.endpoints.iter().enumerate() {
+                if let Err(err) = res {
+                    warn!(%err, endpoint, ?block_number, "Engine failed to process block");
+                }
+            }
+
+            if warmup_blocks > 0 {
+                warmup_blocks -= 1;
+                debug!(?block_number, "Discarding warmup block");
+                warmup_report.record(result);
+                continue
+            }
+            report.record(result);
+        }
+
+        let latency_summary = (0..engines.len()).fold(LatencySummary::default(), |summary, i| {
+            summary.with_row(format!("endpoint {i}"), report.new_payload_latencies(i))
+        });
+        info!("Latency summary:\n{latency_summary}");
+
+        let divergent_blocks =
+            warmup_report.divergent_blocks().count() + report.divergent_blocks().count();
+        if divergent_blocks > 0 {
+            return Err(eyre::eyre!("Endpoints disagreed on {divergent_blocks} blocks"))
+        }
+        info!(blocks = report.blocks.len(), "All endpoints agreed on every block");
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fanout_engine_rpc_urls() {
+        let command = Command::parse_from([
+            "fanout",
+            "--rpc-url",
+            "http://localhost:8545",
+            "--fanout-engine-rpc-url",
+            "http://localhost:9551",
+            "--fanout-engine-rpc-url",
+            "http://localhost:10551",
+        ]);
+        assert_eq!(
+            command.fanout_engine_rpc_urls,
+            ["http://localhost:9551", "http://localhost:10551"]
+        );
+
+        // fanning out to `--engine-rpc-url` alone is not a comparison
+        let res = Command::try_parse_from(["fanout", "--rpc-url", "http://localhost:8545"]);
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Runs the `reth bench` command, sending only forkchoiceUpdated to advance the head one block at a
+//! time, without a newPayload call.
+
+use crate::{
+    bench::{
+        context::BenchContext,
+        output::{FcuResult, LatencyHistogram, LatencySummary, FCU_OUTPUT_SUFFIX},
+    },
+    valid_payload::{EngineApiValidWaitExt, EngineWaitError, WaitConfig, WaitMetrics},
+};
+use alloy_provider::Provider;
+use alloy_rpc_types_engine::{ForkchoiceState, ForkchoiceUpdated};
+use clap::Parser;
+use csv::Writer;
+use reth_cli_runner::CliContext;
+use reth_node_core::args::BenchmarkArgs;
+use std::time::Instant;
+use tracing::{debug, info, warn};
+
+/// `reth benchmark fcu-only` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The RPC url to use for getting data.
+    #[arg(long, value_name = "RPC_URL", verbatim_doc_comment)]
+    rpc_url: String,
+
+    #[command(flatten)]
+    benchmark: BenchmarkArgs,
+}
+
+impl Command {
+    /// Execute `benchmark fcu-only` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        let cloned_args = self.benchmark.clone();
+        let BenchContext {
+            benchmark_mode,
+            block_provider,
+            auth_provider,
+            mut next_block,
+            wait_config,
+        } = BenchContext::new(&cloned_args, self.rpc_url).await?;
+
+        // only the hashes are needed, so the blocks are fetched without their transactions
+        let (sender, mut receiver) = tokio::sync::mpsc::channel(1000);
+        tokio::task::spawn(async move {
+            while benchmark_mode.contains(next_block) {
+                let block_hash = |number: u64| {
+                    let block_provider = &block_provider;
+                    async move {
+                        block_provider
+                            .get_block_by_number(number.into(), false)
+                            .await
+                            .unwrap()
+                            .expect("block exists")
+                            .header
+                            .hash
+                            .expect("block has hash")
+                    }
+                };
+                let (head, safe, finalized) = tokio::join!(
+                    block_hash(next_block),
+                    block_hash(next_block.saturating_sub(32)),
+                    block_hash(next_block.saturating_sub(64)),
+                );
+                let forkchoice_state = ForkchoiceState {
+                    head_block_hash: head,
+                    safe_block_hash: safe,
+                    finalized_block_hash: finalized,
+                };
+
+                sender.send((next_block, forkchoice_state)).await.unwrap();
+                next_block += 1;
+            }
+        });
+
+        // fail early if the engine does not support the call, which also warms up the engine
+        // connection, so the first timed call does not pay for the handshake
+        auth_provider.negotiate_capabilities(&["engine_forkchoiceUpdatedV3"]).await?;
+
+        let mut results = Vec::new();
+        let mut fcu_latencies = LatencyHistogram::default();
+        let mut warmup_blocks = self.benchmark.warmup;
+        let mut blocks_skipped = 0;
+
+        while let Some((block_number, forkchoice_state)) = receiver.recv().await {
+            debug!(?block_number, head = %forkchoice_state.head_block_hash, "Updating forkchoice");
+
+            // the blocks are expected to be known to the node, so the call only canonicalizes
+            let start = Instant::now();
+            let (_, metrics) = match update_forkchoice(
+                &auth_provider,
+                block_number,
+                forkchoice_state,
+                &wait_config,
+            )
+            .await
+            {
+                Err(err @ EngineWaitError::Invalid { .. }) => {
+                    warn!(%err, ?block_number, "Skipping block with invalid forkchoice update");
+                    // a skipped warmup block counts towards the warmup, not the skipped blocks
+                    if warmup_blocks > 0 {
+                        warmup_blocks -= 1;
+                    } else {
+                        blocks_skipped += 1;
+                    }
+                    continue
+                }
+                res => res?,
+            };
+            let fcu_result = FcuResult {
+                block_number,
+                latency: start.elapsed(),
+                poll_count: metrics.poll_count,
+            };
+            info!(%fcu_result);
+
+            // the warmup blocks advance the head of the node, but are left out of the results
+            if warmup_blocks > 0 {
+                warmup_blocks -= 1;
+                debug!(?block_number, "Discarding warmup block");
+                continue
+            }
+
+            fcu_latencies.record(fcu_result.latency);
+            results.push(fcu_result);
+        }
+
+        // write the csv output to a file
+        if let Some(path) = self.benchmark.output {
+            let output_path = path.join(FCU_OUTPUT_SUFFIX);
+            info!("Writing forkchoiceUpdated latency output to file: {:?}", output_path);
+            let mut writer = Writer::from_path(output_path)?;
+            for result in &results {
+                writer.serialize(result)?;
+            }
+            writer.flush()?;
+
+            info!("Finished writing benchmark output files to {:?}.", path);
+        }
+
+        info!(
+            blocks_processed = results.len(),
+            blocks_skipped, "Finished forkchoiceUpdated benchmark"
+        );
+        let latency_summary = LatencySummary::default().with_row("fcu", fcu_latencies);
+        info!("Latency summary:\n{latency_summary}");
+
+        Ok(())
+    }
+}
+
+/// Sends `engine_forkchoiceUpdatedV3` with the given state, and waits until the response is VALID.
+///
+/// If [`WaitConfig::verify_hashes`] is set, the latest valid hash of the response must be the new
+/// head, otherwise the call fails with [`EngineWaitError::HashMismatch`].
+async fn update_forkchoice<N, T, P: EngineApiValidWaitExt<N, T>>(
+    provider: &P,
+    block_number: u64,
+    forkchoice_state: ForkchoiceState,
+    config: &WaitConfig,
+) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError> {
+    let (updated, metrics) =
+        provider.fork_choice_updated_v3_wait_with(forkchoice_state, None, config).await?;
+    config.verify_hash(
+        "engine_forkchoiceUpdatedV3",
+        block_number,
+        forkchoice_state.head_block_hash,
+        &updated.payload_status,
+    )?;
+    Ok((updated, metrics))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valid_payload::tests::MockEngineApi;
+    use alloy_rpc_types_engine::{PayloadStatus, PayloadStatusEnum};
+    use reth_primitives::B256;
+
+    #[tokio::test]
+    async fn update_forkchoice_verify_hashes() {
+        let head = B256::with_last_byte(1);
+        let forkchoice_state = ForkchoiceState {
+            head_block_hash: head,
+            safe_block_hash: B256::ZERO,
+            finalized_block_hash: B256::ZERO,
+        };
+        let mock = MockEngineApi::default();
+        for hash in [head, B256::with_last_byte(2), B256::with_last_byte(2)] {
+            let status = PayloadStatus::new(PayloadStatusEnum::Valid, Some(hash));
+            mock.push_success(ForkchoiceUpdated::new(status));
+        }
+        let provider = mock.provider();
+        let config = WaitConfig::default().with_verify_hashes();
+
+        update_forkchoice(&provider, 7, forkchoice_state, &config).await.unwrap();
+        let err = update_forkchoice(&provider, 7, forkchoice_state, &config).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                EngineWaitError::HashMismatch {
+                    method: "engine_forkchoiceUpdatedV3",
+                    block_number: 7,
+                    actual: Some(actual),
+                    ..
+                } if actual == B256::with_last_byte(2)
+            ),
+            "{err:?}"
+        );
+
+        // the head is only checked with --verify-hashes
+        update_forkchoice(&provider, 7, forkchoice_state, &WaitConfig::default()).await.unwrap();
+    }
+}
@@ -6,9 +6,13 @@ use reth_node_core::args::LogArgs;
 use reth_tracing::FileWorkerGuard;
 
 mod context;
+mod fanout;
+mod fcu_only;
 mod new_payload_fcu;
 mod new_payload_only;
-mod output;
+pub(crate) mod output;
+mod reorg_script;
+mod replay_rpc;
 
 /// `reth bench` command
 #[derive(Debug, Parser)]
@@ -27,7 +31,39 @@ pub enum Subcommands {
     NewPayloadFcu(new_payload_fcu::Command),
 
     /// Benchmark which only calls subsequent `newPayload` calls.
+    ///
+    /// This measures the execution-only latency of each block, without the forkchoice update
+    /// round trip. It is not a full import: the blocks are never made canonical, so the head of
+    /// the node does not advance.
     NewPayloadOnly(new_payload_only::Command),
+
+    /// Benchmark which only calls `forkchoiceUpdated`, advancing the head one block at a time.
+    ///
+    /// This measures the latency of making already known blocks canonical, without executing
+    /// them, so the blocks must have been sent to the node before, e.g. with `new-payload-only`.
+    FcuOnly(fcu_only::Command),
+
+    /// Benchmark which sends every block with `newPayload` to several engines concurrently.
+    ///
+    /// This compares the latencies of the engines under identical load, e.g. of two node builds,
+    /// and reports every block on which they disagree as a consensus divergence. The forkchoice is
+    /// not updated.
+    Fanout(fanout::Command),
+
+    /// Benchmark which replays a range of blocks from a JSON-RPC node with `newPayload`.
+    ///
+    /// The next block is fetched while the engine executes the current one, so the latency of the
+    /// source node does not add to the duration of the replay. The forkchoice is not updated, and
+    /// the replay stops at the first block that fails.
+    ReplayRpc(replay_rpc::Command),
+
+    /// Benchmark which replays a script of competing branches, to measure how fast the engine
+    /// handles reorgs.
+    ///
+    /// The blocks and the forkchoice updates are given by the script, so `--from`, `--to` and
+    /// `--warmup` do not apply. The latencies of the forkchoice updates that reorg the canonical
+    /// chain are reported separately.
+    ReorgScript(reorg_script::Command),
 }
 
 impl BenchmarkCommand {
@@ -39,6 +75,10 @@ impl BenchmarkCommand {
         match self.command {
             Subcommands::NewPayloadFcu(command) => command.execute(ctx).await,
             Subcommands::NewPayloadOnly(command) => command.execute(ctx).await,
+            Subcommands::FcuOnly(command) => command.execute(ctx).await,
+            Subcommands::Fanout(command) => command.execute(ctx).await,
+            Subcommands::ReplayRpc(command) => command.execute(ctx).await,
+            Subcommands::ReorgScript(command) => command.execute(ctx).await,
         }
     }
 
@@ -2,13 +2,16 @@
 
 use crate::{
     bench::{
-        context::BenchContext,
+        context::{fetch_tx_gas_breakdown, BenchContext},
         output::{
-            NewPayloadResult, TotalGasOutput, TotalGasRow, GAS_OUTPUT_SUFFIX,
-            NEW_PAYLOAD_OUTPUT_SUFFIX,
+            write_results, write_tx_gas_breakdown, BenchmarkSummary, BlockRecord, LatencyHistogram,
+            LatencySummary, NewPayloadResult, OutputFormat, TotalGasOutput, TotalGasRow,
+            TxGasBreakdown, GAS_OUTPUT_SUFFIX, NEW_PAYLOAD_OUTPUT_SUFFIX,
         },
     },
-    valid_payload::call_new_payload,
+    valid_payload::{
+        call_new_payload, payload_blob_gas_used, EngineApiValidWaitExt, EngineWaitError,
+    },
 };
 use alloy_provider::Provider;
 use clap::Parser;
@@ -18,7 +21,7 @@ use reth_node_core::args::BenchmarkArgs;
 use reth_primitives::{Block, B256};
 use reth_rpc_types_compat::engine::payload::block_to_payload;
 use std::time::Instant;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// `reth benchmark new-payload-only` command
 #[derive(Debug, Parser)]
@@ -27,6 +30,10 @@ pub struct Command {
     #[arg(long, value_name = "RPC_URL", verbatim_doc_comment)]
     rpc_url: String,
 
+    /// The format of the per-block results and the summary written to the output directory.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Csv)]
+    output_format: OutputFormat,
+
     #[command(flatten)]
     benchmark: BenchmarkArgs,
 }
@@ -37,9 +44,15 @@ impl Command {
         let cloned_args = self.benchmark.clone();
         // TODO: this could be just a function I guess, but destructuring makes the code slightly
         // more readable than a 4 element tuple.
-        let BenchContext { benchmark_mode, block_provider, auth_provider, mut next_block } =
-            BenchContext::new(&cloned_args, self.rpc_url).await?;
-
+        let BenchContext {
+            benchmark_mode,
+            block_provider,
+            auth_provider,
+            mut next_block,
+            wait_config,
+        } = BenchContext::new(&cloned_args, self.rpc_url).await?;
+
+        let tx_gas_breakdown = self.benchmark.tx_gas_breakdown;
         let (sender, mut receiver) = tokio::sync::mpsc::channel(1000);
         tokio::task::spawn(async move {
             while benchmark_mode.contains(next_block) {
@@ -56,22 +69,43 @@ impl Command {
                     }
                 };
 
+                // the receipts are only fetched if the gas is broken down by transaction type
+                let tx_gas = if tx_gas_breakdown {
+                    fetch_tx_gas_breakdown(&block_provider, &block)
+                        .await
+                        .inspect_err(|err| {
+                            warn!(%err, block_number = block.number, "Failed to break down gas")
+                        })
+                        .ok()
+                } else {
+                    None
+                };
+
                 next_block += 1;
-                sender.send(block).await.unwrap();
+                sender.send((block, tx_gas)).await.unwrap();
             }
         });
 
+        // warm up the engine connection, so the first timed call does not pay for the handshake
+        auth_provider.prepare().await?;
+
         // put results in a summary vec so they can be printed at the end
         let mut results = Vec::new();
-        let total_benchmark_duration = Instant::now();
-
-        while let Some(block) = receiver.recv().await {
+        let mut records = Vec::new();
+        let mut new_payload_latencies = LatencyHistogram::default();
+        let mut tx_gas = TxGasBreakdown::default();
+        let mut warmup_blocks = self.benchmark.warmup;
+        let mut blocks_skipped = 0;
+        let mut total_benchmark_duration = Instant::now();
+
+        while let Some((block, block_tx_gas)) = receiver.recv().await {
             // just put gas used here
             let gas_used = block.header.gas_used;
 
             let versioned_hashes: Vec<B256> =
                 block.blob_versioned_hashes().into_iter().copied().collect();
             let (payload, parent_beacon_block_root) = block_to_payload(block);
+            let blob_gas_used = payload_blob_gas_used(&payload);
 
             let block_number = payload.block_number();
 
@@ -81,21 +115,62 @@ impl Command {
             );
 
             let start = Instant::now();
-            call_new_payload(&auth_provider, payload, parent_beacon_block_root, versioned_hashes)
-                .await?;
+            let (_, new_payload_metrics) = match call_new_payload(
+                &auth_provider,
+                payload,
+                parent_beacon_block_root,
+                versioned_hashes,
+                &wait_config,
+            )
+            .await
+            {
+                Err(err @ EngineWaitError::Invalid { .. }) => {
+                    warn!(%err, ?block_number, "Skipping invalid block");
+                    // a skipped warmup block counts towards the warmup, not the skipped blocks
+                    if warmup_blocks > 0 {
+                        warmup_blocks -= 1;
+                        total_benchmark_duration = Instant::now();
+                    } else {
+                        blocks_skipped += 1;
+                    }
+                    continue
+                }
+                res => res?,
+            };
 
             let new_payload_result = NewPayloadResult { gas_used, latency: start.elapsed() };
             info!(%new_payload_result);
 
+            // the warmup blocks advance the node state, but are left out of the results
+            if warmup_blocks > 0 {
+                warmup_blocks -= 1;
+                debug!(?block_number, "Discarding warmup block");
+                total_benchmark_duration = Instant::now();
+                continue
+            }
+
+            if let Some(block_tx_gas) = &block_tx_gas {
+                tx_gas.add(block_tx_gas);
+            }
+
             // current duration since the start of the benchmark
             let current_duration = total_benchmark_duration.elapsed();
 
             // record the current result
+            new_payload_latencies.record(new_payload_result.latency);
+            records.push(BlockRecord {
+                block_number,
+                gas_used,
+                blob_gas_used,
+                new_payload_latency: new_payload_result.latency,
+                fcu_latency: None,
+                poll_count: new_payload_metrics.poll_count,
+            });
             let row = TotalGasRow { block_number, gas_used, time: current_duration };
             results.push((row, new_payload_result));
         }
 
-        let (gas_output_results, new_payload_results): (_, Vec<NewPayloadResult>) =
+        let (gas_output_results, new_payload_results): (Vec<TotalGasRow>, Vec<NewPayloadResult>) =
             results.into_iter().unzip();
 
         // write the csv output to files
@@ -118,6 +193,14 @@ impl Command {
             }
             writer.flush()?;
 
+            // and the per-block results and the summary in the requested format
+            let total_duration = gas_output_results.last().map(|row| row.time).unwrap_or_default();
+            let summary = BenchmarkSummary::new(&records, total_duration, blocks_skipped);
+            write_results(&path, self.output_format, &records, &summary)?;
+            if self.benchmark.tx_gas_breakdown {
+                write_tx_gas_breakdown(&path, &tx_gas)?;
+            }
+
             info!("Finished writing benchmark output files to {:?}.", path);
         }
 
@@ -127,9 +210,25 @@ impl Command {
             total_duration=?gas_output.total_duration,
             total_gas_used=?gas_output.total_gas_used,
             blocks_processed=?gas_output.blocks_processed,
+            blocks_skipped,
             "Total Ggas/s: {:.4}",
             gas_output.total_gigagas_per_second()
         );
+        // blocks before the blob gas fields were added count as zero blob gas
+        let summary = BenchmarkSummary::new(&records, gas_output.total_duration, blocks_skipped);
+        info!(
+            total_blob_gas_used=?summary.total_blob_gas_used,
+            "Total blob Mgas/s: {:.4}",
+            summary.blob_megagas_per_second()
+        );
+
+        let latency_summary =
+            LatencySummary::default().with_row("newPayload", new_payload_latencies);
+        info!("Latency summary:\n{latency_summary}");
+
+        if self.benchmark.tx_gas_breakdown {
+            info!("Gas used by transaction type:\n{tx_gas}");
+        }
 
         Ok(())
     }
@@ -1,9 +1,15 @@
 //! Contains various benchmark output formats, either for logging or for
 //! serialization to / from files.
 
-use reth_primitives::constants::gas_units::GIGAGAS;
+use clap::ValueEnum;
+use csv::Writer;
+use reth_primitives::{
+    constants::gas_units::{GIGAGAS, MEGAGAS},
+    SealedBlock, TransactionSigned, EIP4844_TX_TYPE_ID,
+};
 use serde::{ser::SerializeStruct, Serialize};
-use std::time::Duration;
+use std::{fs::File, io::BufWriter, path::Path, time::Duration};
+use tracing::info;
 
 /// This is the suffix for gas output csv files.
 pub(crate) const GAS_OUTPUT_SUFFIX: &str = "total_gas.csv";
@@ -14,6 +20,35 @@ pub(crate) const COMBINED_OUTPUT_SUFFIX: &str = "combined_latency.csv";
 /// This is the suffix for new payload output csv files.
 pub(crate) const NEW_PAYLOAD_OUTPUT_SUFFIX: &str = "new_payload_latency.csv";
 
+/// This is the suffix for forkchoiceUpdated-only output csv files.
+pub(crate) const FCU_OUTPUT_SUFFIX: &str = "fcu_latency.csv";
+
+/// This is the suffix for per-block result csv files.
+pub(crate) const BLOCKS_OUTPUT_SUFFIX: &str = "blocks.csv";
+
+/// This is the suffix for summary csv files.
+pub(crate) const SUMMARY_OUTPUT_SUFFIX: &str = "summary.csv";
+
+/// This is the suffix for json files, containing both the per-block results and the summary.
+pub(crate) const JSON_OUTPUT_SUFFIX: &str = "results.json";
+
+/// This is the suffix for csv files with the gas used per transaction type.
+pub(crate) const TX_GAS_OUTPUT_SUFFIX: &str = "tx_type_gas.csv";
+
+/// The type id of OP stack deposit transactions, which are only decoded with the `optimism`
+/// feature.
+const DEPOSIT_TX_TYPE_ID: u8 = 0x7e;
+
+/// The format of the per-block results and the summary written to the output directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum OutputFormat {
+    /// One csv file with a row per block, and one with the summary.
+    #[default]
+    Csv,
+    /// One json file with the per-block results and the summary.
+    Json,
+}
+
 /// This represents the results of a single `newPayload` call in the benchmark, containing the gas
 /// used and the `newPayload` latency.
 #[derive(Debug)]
@@ -115,6 +150,42 @@ impl Serialize for CombinedResult {
     }
 }
 
+/// This represents the result of a single `forkchoiceUpdated` call that advanced the head by one
+/// block, without a preceding `newPayload` call.
+#[derive(Debug)]
+pub(crate) struct FcuResult {
+    /// The number of the new head block.
+    pub(crate) block_number: u64,
+    /// The latency of the `forkchoiceUpdated` call.
+    pub(crate) latency: Duration,
+    /// The number of `forkchoiceUpdated` calls sent until the response was VALID.
+    pub(crate) poll_count: usize,
+}
+
+impl std::fmt::Display for FcuResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Head advanced to block {} after {} forkchoice updates. Latency: {:?}",
+            self.block_number, self.poll_count, self.latency
+        )
+    }
+}
+
+/// This serializes the latency of the [`FcuResult`] as microseconds, like the other csv outputs.
+impl Serialize for FcuResult {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        let mut state = serializer.serialize_struct("FcuResult", 3)?;
+        state.serialize_field("block_number", &self.block_number)?;
+        state.serialize_field("latency", &self.latency.as_micros())?;
+        state.serialize_field("poll_count", &self.poll_count)?;
+        state.end()
+    }
+}
+
 /// This represents a row of total gas data in the benchmark.
 #[derive(Debug)]
 pub(crate) struct TotalGasRow {
@@ -177,10 +248,400 @@ impl Serialize for TotalGasRow {
     }
 }
 
+/// The results of a single block in the benchmark, meant for machine-readable output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct BlockRecord {
+    /// The number of the block.
+    pub(crate) block_number: u64,
+    /// The gas used by the block.
+    pub(crate) gas_used: u64,
+    /// The blob gas used by the block, zero for blocks before the blob gas fields were added.
+    pub(crate) blob_gas_used: u64,
+    /// The latency of the `newPayload` call.
+    pub(crate) new_payload_latency: Duration,
+    /// The latency of the `forkchoiceUpdated` call, if one was sent.
+    pub(crate) fcu_latency: Option<Duration>,
+    /// The number of `newPayload` calls sent until the payload was VALID.
+    pub(crate) poll_count: usize,
+}
+
+/// This serializes the latencies of the [`BlockRecord`] as fractional milliseconds.
+impl Serialize for BlockRecord {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        let mut state = serializer.serialize_struct("BlockRecord", 6)?;
+        state.serialize_field("block_number", &self.block_number)?;
+        state.serialize_field("new_payload_ms", &millis(self.new_payload_latency))?;
+        state.serialize_field("fcu_ms", &self.fcu_latency.map(millis))?;
+        state.serialize_field("poll_count", &self.poll_count)?;
+        state.serialize_field("gas_used", &self.gas_used)?;
+        state.serialize_field("blob_gas_used", &self.blob_gas_used)?;
+        state.end()
+    }
+}
+
+/// The aggregated results of a benchmark run, meant for machine-readable output.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct BenchmarkSummary {
+    /// The number of blocks processed.
+    pub(crate) blocks_processed: u64,
+    /// The number of blocks after the warmup that were skipped because the engine rejected them
+    /// as INVALID.
+    pub(crate) blocks_skipped: u64,
+    /// The total gas used by all blocks.
+    pub(crate) total_gas_used: u64,
+    /// The total duration of the benchmark.
+    pub(crate) total_duration: Duration,
+    /// The total gas used per second of the benchmark.
+    pub(crate) gas_per_second: f64,
+    /// The total blob gas used by all blocks.
+    pub(crate) total_blob_gas_used: u64,
+    /// The total blob gas used per second of the benchmark.
+    pub(crate) blob_gas_per_second: f64,
+    /// The median latency of the `newPayload` calls.
+    pub(crate) new_payload_p50: Option<Duration>,
+    /// The 99th percentile latency of the `newPayload` calls.
+    pub(crate) new_payload_p99: Option<Duration>,
+    /// The median latency of the `forkchoiceUpdated` calls.
+    pub(crate) fcu_p50: Option<Duration>,
+    /// The 99th percentile latency of the `forkchoiceUpdated` calls.
+    pub(crate) fcu_p99: Option<Duration>,
+}
+
+impl BenchmarkSummary {
+    /// Creates the summary of the given block records, processed in the given total duration, and
+    /// the number of blocks that were skipped as INVALID.
+    ///
+    /// The throughput is zero if the total duration is zero.
+    pub(crate) fn new(
+        records: &[BlockRecord],
+        total_duration: Duration,
+        blocks_skipped: u64,
+    ) -> Self {
+        let total_gas_used = records.iter().map(|record| record.gas_used).sum::<u64>();
+        let total_blob_gas_used = records.iter().map(|record| record.blob_gas_used).sum::<u64>();
+        let new_payload =
+            records.iter().map(|record| record.new_payload_latency).collect::<LatencyHistogram>();
+        let fcu =
+            records.iter().filter_map(|record| record.fcu_latency).collect::<LatencyHistogram>();
+        Self {
+            blocks_processed: records.len() as u64,
+            blocks_skipped,
+            total_gas_used,
+            total_duration,
+            gas_per_second: per_second(total_gas_used, total_duration),
+            total_blob_gas_used,
+            blob_gas_per_second: per_second(total_blob_gas_used, total_duration),
+            new_payload_p50: new_payload.percentile(0.5),
+            new_payload_p99: new_payload.percentile(0.99),
+            fcu_p50: fcu.percentile(0.5),
+            fcu_p99: fcu.percentile(0.99),
+        }
+    }
+
+    /// Return the blob gas used per second of the benchmark, in Mgas/s.
+    pub(crate) fn blob_megagas_per_second(&self) -> f64 {
+        self.blob_gas_per_second / MEGAGAS as f64
+    }
+}
+
+/// This serializes the durations of the [`BenchmarkSummary`] as fractional milliseconds.
+impl Serialize for BenchmarkSummary {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::ser::Serializer,
+    {
+        let mut state = serializer.serialize_struct("BenchmarkSummary", 11)?;
+        state.serialize_field("blocks_processed", &self.blocks_processed)?;
+        state.serialize_field("blocks_skipped", &self.blocks_skipped)?;
+        state.serialize_field("total_gas_used", &self.total_gas_used)?;
+        state.serialize_field("total_duration_ms", &millis(self.total_duration))?;
+        state.serialize_field("gas_per_second", &self.gas_per_second)?;
+        state.serialize_field("total_blob_gas_used", &self.total_blob_gas_used)?;
+        state.serialize_field("blob_gas_per_second", &self.blob_gas_per_second)?;
+        state.serialize_field("new_payload_p50_ms", &self.new_payload_p50.map(millis))?;
+        state.serialize_field("new_payload_p99_ms", &self.new_payload_p99.map(millis))?;
+        state.serialize_field("fcu_p50_ms", &self.fcu_p50.map(millis))?;
+        state.serialize_field("fcu_p99_ms", &self.fcu_p99.map(millis))?;
+        state.end()
+    }
+}
+
+/// The contents of the json output file.
+#[derive(Debug, Serialize)]
+struct JsonOutput<'a> {
+    summary: &'a BenchmarkSummary,
+    blocks: &'a [BlockRecord],
+}
+
+/// Writes the block records and the summary of a benchmark run to the given output directory, in
+/// the given format.
+pub(crate) fn write_results(
+    dir: &Path,
+    format: OutputFormat,
+    records: &[BlockRecord],
+    summary: &BenchmarkSummary,
+) -> eyre::Result<()> {
+    match format {
+        OutputFormat::Csv => {
+            let output_path = dir.join(BLOCKS_OUTPUT_SUFFIX);
+            info!("Writing per-block output to file: {:?}", output_path);
+            let mut writer = Writer::from_path(output_path)?;
+            for record in records {
+                writer.serialize(record)?;
+            }
+            writer.flush()?;
+
+            let output_path = dir.join(SUMMARY_OUTPUT_SUFFIX);
+            info!("Writing summary output to file: {:?}", output_path);
+            let mut writer = Writer::from_path(output_path)?;
+            writer.serialize(summary)?;
+            writer.flush()?;
+        }
+        OutputFormat::Json => {
+            let output_path = dir.join(JSON_OUTPUT_SUFFIX);
+            info!("Writing json output to file: {:?}", output_path);
+            let writer = BufWriter::new(File::create(output_path)?);
+            serde_json::to_writer_pretty(writer, &JsonOutput { summary, blocks: records })?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the gas used per transaction type of a benchmark run to a csv file in the given output
+/// directory.
+pub(crate) fn write_tx_gas_breakdown(dir: &Path, breakdown: &TxGasBreakdown) -> eyre::Result<()> {
+    let output_path = dir.join(TX_GAS_OUTPUT_SUFFIX);
+    info!("Writing transaction type gas output to file: {:?}", output_path);
+    let mut writer = Writer::from_path(output_path)?;
+    for row in breakdown.rows() {
+        writer.serialize(row)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Returns the given amount over the given duration, or zero if the duration is zero.
+fn per_second(amount: u64, duration: Duration) -> f64 {
+    if duration.is_zero() {
+        return 0.0
+    }
+    amount as f64 / duration.as_secs_f64()
+}
+
+/// Converts the given duration to fractional milliseconds.
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Accumulates the latencies of an engine API call over a benchmark run, to report their
+/// distribution at the end of the run.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LatencyHistogram {
+    latencies: Vec<Duration>,
+}
+
+impl LatencyHistogram {
+    /// Records a latency.
+    pub(crate) fn record(&mut self, latency: Duration) {
+        self.latencies.push(latency);
+    }
+
+    /// Returns the number of recorded latencies.
+    pub(crate) fn len(&self) -> usize {
+        self.latencies.len()
+    }
+
+    /// Returns the mean of the recorded latencies, or `None` if there are none.
+    pub(crate) fn mean(&self) -> Option<Duration> {
+        let total: Duration = self.latencies.iter().sum();
+        Some(total / u32::try_from(self.len()).ok().filter(|len| *len > 0)?)
+    }
+
+    /// Returns the nearest-rank percentile (between `0.0` and `1.0`) of the recorded latencies, or
+    /// `None` if there are none.
+    pub(crate) fn percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.latencies.is_empty() {
+            return None
+        }
+        let mut latencies = self.latencies.clone();
+        latencies.sort_unstable();
+        let rank = (percentile.clamp(0.0, 1.0) * latencies.len() as f64).ceil() as usize;
+        Some(latencies[rank.saturating_sub(1)])
+    }
+}
+
+impl FromIterator<Duration> for LatencyHistogram {
+    fn from_iter<T: IntoIterator<Item = Duration>>(iter: T) -> Self {
+        Self { latencies: iter.into_iter().collect() }
+    }
+}
+
+/// A table of the latency distributions of the engine API calls of a benchmark run, with one row
+/// per call.
+#[derive(Debug, Default)]
+pub(crate) struct LatencySummary {
+    rows: Vec<(String, LatencyHistogram)>,
+}
+
+impl LatencySummary {
+    /// Adds a row for the given call.
+    pub(crate) fn with_row(mut self, call: impl Into<String>, histogram: LatencyHistogram) -> Self {
+        self.rows.push((call.into(), histogram));
+        self
+    }
+}
+
+impl std::fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let format = |latency: Option<Duration>| {
+            latency.map(|latency| format!("{latency:.2?}")).unwrap_or_else(|| "-".to_string())
+        };
+
+        write!(
+            f,
+            "{:<12} {:>8} {:>12} {:>12} {:>12} {:>12}",
+            "call", "count", "mean", "p50", "p90", "p99"
+        )?;
+        for (call, histogram) in &self.rows {
+            write!(
+                f,
+                "\n{:<12} {:>8} {:>12} {:>12} {:>12} {:>12}",
+                call,
+                histogram.len(),
+                format(histogram.mean()),
+                format(histogram.percentile(0.5)),
+                format(histogram.percentile(0.9)),
+                format(histogram.percentile(0.99)),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The kinds of transactions the gas of a benchmark run is broken down by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TxGasKind {
+    /// A transaction that calls an account, including plain transfers.
+    Call,
+    /// A transaction that creates a contract.
+    Create,
+    /// An EIP-4844 blob transaction.
+    Blob,
+    /// An OP stack deposit transaction.
+    Deposit,
+}
+
+impl TxGasKind {
+    /// All kinds, in the order of the [`TxGasBreakdown`] rows.
+    const ALL: [Self; 4] = [Self::Call, Self::Create, Self::Blob, Self::Deposit];
+
+    /// Classifies the given transaction, blob and deposit transactions by their type and all other
+    /// transactions by whether they create a contract.
+    pub(crate) fn of(tx: &TransactionSigned) -> Self {
+        match u8::from(tx.tx_type()) {
+            EIP4844_TX_TYPE_ID => Self::Blob,
+            DEPOSIT_TX_TYPE_ID => Self::Deposit,
+            _ if tx.kind().is_create() => Self::Create,
+            _ => Self::Call,
+        }
+    }
+
+    /// Returns the name of the kind in the output.
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Call => "call",
+            Self::Create => "create",
+            Self::Blob => "blob",
+            Self::Deposit => "deposit",
+        }
+    }
+}
+
+/// A row of the [`TxGasBreakdown`], the transactions of one [`TxGasKind`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct TxGasRow {
+    /// The name of the kind of the transactions.
+    pub(crate) tx_type: &'static str,
+    /// The number of transactions.
+    pub(crate) count: u64,
+    /// The gas used by the transactions.
+    pub(crate) gas_used: u64,
+}
+
+/// The gas used by the transactions of a benchmark run, broken down by [`TxGasKind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TxGasBreakdown {
+    rows: [TxGasRow; 4],
+}
+
+impl Default for TxGasBreakdown {
+    fn default() -> Self {
+        Self {
+            rows: TxGasKind::ALL
+                .map(|kind| TxGasRow { tx_type: kind.name(), ..Default::default() }),
+        }
+    }
+}
+
+impl TxGasBreakdown {
+    /// Classifies the transactions of the given block, given the gas used by each transaction,
+    /// e.g. from the receipts of the block.
+    ///
+    /// Returns `None` if the gas used is not given for every transaction.
+    pub(crate) fn from_block(block: &SealedBlock, gas_used: &[u64]) -> Option<Self> {
+        if block.body.len() != gas_used.len() {
+            return None
+        }
+        let mut breakdown = Self::default();
+        for (tx, gas_used) in block.body.iter().zip(gas_used) {
+            let row = &mut breakdown.rows[TxGasKind::of(tx) as usize];
+            row.count += 1;
+            row.gas_used += gas_used;
+        }
+        Some(breakdown)
+    }
+
+    /// Adds the transactions of the given breakdown to this one.
+    pub(crate) fn add(&mut self, other: &Self) {
+        for (row, other) in self.rows.iter_mut().zip(&other.rows) {
+            row.count += other.count;
+            row.gas_used += other.gas_used;
+        }
+    }
+
+    /// Returns the rows of the breakdown, one per [`TxGasKind`].
+    pub(crate) const fn rows(&self) -> &[TxGasRow] {
+        &self.rows
+    }
+
+    /// Returns the total gas used by all transactions.
+    pub(crate) fn total_gas_used(&self) -> u64 {
+        self.rows.iter().map(|row| row.gas_used).sum()
+    }
+}
+
+impl std::fmt::Display for TxGasBreakdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let total = self.total_gas_used();
+        write!(f, "{:<12} {:>8} {:>16} {:>8}", "type", "count", "gas_used", "share")?;
+        for row in &self.rows {
+            let share = if total == 0 { 0.0 } else { row.gas_used as f64 / total as f64 * 100.0 };
+            write!(
+                f,
+                "\n{:<12} {:>8} {:>16} {:>7.2}%",
+                row.tx_type, row.count, row.gas_used, share
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use csv::Writer;
     use std::io::BufRead;
 
     #[test]
@@ -203,4 +664,138 @@ mod tests {
         let second_line = result.next().unwrap().unwrap();
         assert_eq!(second_line, expected_second_line);
     }
+
+    #[test]
+    fn test_write_fcu_result_csv() {
+        let result =
+            FcuResult { block_number: 7, latency: Duration::from_micros(1_500), poll_count: 2 };
+
+        let mut writer = Writer::from_writer(vec![]);
+        writer.serialize(result).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(output, "block_number,latency,poll_count\n7,1500,2\n");
+    }
+
+    #[test]
+    fn latency_summary() {
+        let histogram = (1..=100).map(Duration::from_millis).collect::<LatencyHistogram>();
+        assert_eq!(histogram.mean(), Some(Duration::from_micros(50_500)));
+        assert_eq!(histogram.percentile(0.9), Some(Duration::from_millis(90)));
+        assert_eq!(LatencyHistogram::default().mean(), None);
+
+        let summary = LatencySummary::default()
+            .with_row("newPayload", histogram)
+            .with_row("fcu", LatencyHistogram::default());
+        let expected = "\
+call            count         mean          p50          p90          p99
+newPayload        100      50.50ms      50.00ms      90.00ms      99.00ms
+fcu                 0            -            -            -            -";
+        assert_eq!(summary.to_string(), expected);
+    }
+
+    #[test]
+    fn block_records_output() {
+        let records = [
+            BlockRecord {
+                block_number: 1,
+                gas_used: 1_000_000,
+                blob_gas_used: 0,
+                new_payload_latency: Duration::from_micros(1_500),
+                fcu_latency: None,
+                poll_count: 2,
+            },
+            BlockRecord {
+                block_number: 2,
+                gas_used: 3_000_000,
+                blob_gas_used: 393_216,
+                new_payload_latency: Duration::from_millis(3),
+                fcu_latency: Some(Duration::from_micros(250)),
+                poll_count: 1,
+            },
+        ];
+        let summary = BenchmarkSummary::new(&records, Duration::from_millis(8), 1);
+        assert_eq!(summary.gas_per_second, 500_000_000.0);
+        assert_eq!(summary.blob_megagas_per_second(), 49.152);
+        assert_eq!(summary.fcu_p50, Some(Duration::from_micros(250)));
+        let instant = BenchmarkSummary::new(&records, Duration::ZERO, 0);
+        assert_eq!(instant.gas_per_second, 0.0);
+        assert_eq!(instant.blob_gas_per_second, 0.0);
+
+        let mut writer = Writer::from_writer(vec![]);
+        for record in &records {
+            writer.serialize(record).unwrap();
+        }
+        let expected = "\
+block_number,new_payload_ms,fcu_ms,poll_count,gas_used,blob_gas_used
+1,1.5,,2,1000000,0
+2,3.0,0.25,1,3000000,393216
+";
+        assert_eq!(String::from_utf8(writer.into_inner().unwrap()).unwrap(), expected);
+
+        let json =
+            serde_json::to_value(JsonOutput { summary: &summary, blocks: &records }).unwrap();
+        assert_eq!(
+            json["summary"],
+            serde_json::json!({
+                "blocks_processed": 2,
+                "blocks_skipped": 1,
+                "total_gas_used": 4_000_000,
+                "total_duration_ms": 8.0,
+                "gas_per_second": 500_000_000.0,
+                "total_blob_gas_used": 393_216,
+                "blob_gas_per_second": 49_152_000.0,
+                "new_payload_p50_ms": 1.5,
+                "new_payload_p99_ms": 3.0,
+                "fcu_p50_ms": 0.25,
+                "fcu_p99_ms": 0.25,
+            })
+        );
+        assert_eq!(json["blocks"][0]["fcu_ms"], serde_json::Value::Null);
+        assert_eq!(json["blocks"][0]["blob_gas_used"], 0);
+        assert_eq!(json["blocks"][1]["new_payload_ms"], 3.0);
+    }
+
+    #[test]
+    fn tx_gas_breakdown() {
+        use reth_primitives::{
+            Block, Signature, Transaction, TxEip1559, TxEip4844, TxKind, TxLegacy,
+        };
+
+        let tx = |transaction| {
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+        };
+        let block = Block {
+            body: vec![
+                tx(Transaction::Legacy(TxLegacy { to: TxKind::Create, ..Default::default() })),
+                tx(Transaction::Eip1559(TxEip1559 {
+                    to: TxKind::Call(Default::default()),
+                    ..Default::default()
+                })),
+                tx(Transaction::Eip4844(TxEip4844::default())),
+                tx(Transaction::Legacy(TxLegacy {
+                    to: TxKind::Call(Default::default()),
+                    ..Default::default()
+                })),
+            ],
+            ..Default::default()
+        }
+        .seal_slow();
+        assert_eq!(TxGasBreakdown::from_block(&block, &[1, 2]), None);
+
+        let mut breakdown = TxGasBreakdown::default();
+        let block_breakdown =
+            TxGasBreakdown::from_block(&block, &[50_000, 21_000, 30_000, 49_000]).unwrap();
+        breakdown.add(&block_breakdown);
+        breakdown.add(&block_breakdown);
+        assert_eq!(breakdown.total_gas_used(), 300_000);
+        assert_eq!(breakdown.rows()[0], TxGasRow { tx_type: "call", count: 4, gas_used: 140_000 });
+
+        let expected = "\
+type            count         gas_used    share
+call                4           140000   46.67%
+create              2           100000   33.33%
+blob                2            60000   20.00%
+deposit             0                0    0.00%";
+        assert_eq!(breakdown.to_string(), expected);
+    }
 }
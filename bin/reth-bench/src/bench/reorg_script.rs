@@ -0,0 +1,73 @@
+//! Runs the `reth bench reorg-script` command, replaying a [`ReplayScript`] of competing branches
+//! against the engine to benchmark how fast it handles reorgs.
+
+use crate::{
+    bench::{
+        context::{connect_engine, read_jwt_secret, wait_config},
+        output::{LatencyHistogram, LatencySummary},
+    },
+    reorg::{replay_script, ReplayScript, ScriptFcuResult, ScriptReport},
+    valid_payload::EngineApiValidWaitExt,
+};
+use clap::Parser;
+use reth_cli_runner::CliContext;
+use reth_node_core::args::BenchmarkArgs;
+use std::path::PathBuf;
+use tracing::info;
+
+/// `reth benchmark reorg-script` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The path of the JSON replay script, with the branches to deliver and the forkchoice
+    /// updates to send.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    script: PathBuf,
+
+    #[command(flatten)]
+    benchmark: BenchmarkArgs,
+}
+
+impl Command {
+    /// Execute `benchmark reorg-script` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        info!("Running replay script: {:?}", self.script);
+        let script = ReplayScript::load(&self.script)?;
+
+        let jwt = read_jwt_secret(&self.benchmark)?;
+        let engine = connect_engine(&self.benchmark.engine_rpc_url, jwt).await?;
+        let wait_config = wait_config(&self.benchmark);
+
+        // warm up the engine connection, so the first timed call does not pay for the handshake
+        engine.prepare().await?;
+
+        let report = replay_script(&engine, &script, &wait_config).await;
+        log_report(&report);
+        if let Some(failure) = report.failure {
+            return Err(eyre::eyre!("Step {} of the script failed: {}", failure.step, failure.error))
+        }
+
+        Ok(())
+    }
+}
+
+/// Logs the number of reorgs and the latency summary of the given script run.
+fn log_report(report: &ScriptReport) {
+    info!(
+        total_duration=?report.total_duration,
+        payloads_delivered=report.payloads.len(),
+        forkchoice_updates=report.forkchoice_updates.len(),
+        reorgs=report.reorgs().count(),
+        "Finished replay script"
+    );
+
+    let (reorg_fcu_latencies, extension_fcu_latencies): (Vec<_>, Vec<_>) =
+        report.forkchoice_updates.iter().partition(|fcu| fcu.is_reorg());
+    let latencies = |fcus: Vec<&ScriptFcuResult>| {
+        fcus.into_iter().map(|fcu| fcu.latency).collect::<LatencyHistogram>()
+    };
+    let latency_summary = LatencySummary::default()
+        .with_row("newPayload", report.payloads.iter().map(|payload| payload.latency).collect())
+        .with_row("extend fcu", latencies(extension_fcu_latencies))
+        .with_row("reorg fcu", latencies(reorg_fcu_latencies));
+    info!("Latency summary:\n{latency_summary}");
+}
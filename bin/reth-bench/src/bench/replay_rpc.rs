@@ -0,0 +1,126 @@
+//! Runs the `reth bench replay-rpc` command, replaying a range of blocks fetched from a JSON-RPC
+//! node into the engine with newPayload, while the next block is fetched.
+
+use crate::{
+    bench::{
+        context::{connect_engine, read_jwt_secret, wait_config},
+        output::{LatencyHistogram, LatencySummary},
+    },
+    bench_mode::BenchMode,
+    replay::ReplayReport,
+    rpc_source::{replay_from_rpc, RpcBlockSource},
+    valid_payload::EngineApiValidWaitExt,
+};
+use alloy_provider::ProviderBuilder;
+use clap::Parser;
+use reth_cli_runner::CliContext;
+use reth_node_core::args::BenchmarkArgs;
+use std::ops::RangeInclusive;
+use tracing::info;
+
+/// `reth benchmark replay-rpc` command
+#[derive(Debug, Parser)]
+pub struct Command {
+    /// The RPC url of the node to fetch the blocks from, e.g. an archive node.
+    #[arg(long, value_name = "RPC_URL", verbatim_doc_comment)]
+    rpc_url: String,
+
+    #[command(flatten)]
+    benchmark: BenchmarkArgs,
+}
+
+impl Command {
+    /// Execute `benchmark replay-rpc` command
+    pub async fn execute(self, _ctx: CliContext) -> eyre::Result<()> {
+        info!("Running benchmark using data from RPC URL: {}", self.rpc_url);
+        let BenchMode::Range(blocks) = BenchMode::new(self.benchmark.from, self.benchmark.to)?
+        else {
+            return Err(eyre::eyre!("--from and --to must be provided to replay blocks from RPC"))
+        };
+
+        let source = RpcBlockSource::new(ProviderBuilder::new().on_http(self.rpc_url.parse()?));
+        let jwt = read_jwt_secret(&self.benchmark)?;
+        let engine = connect_engine(&self.benchmark.engine_rpc_url, jwt).await?;
+        let wait_config = wait_config(&self.benchmark);
+
+        // warm up the engine connection, so the first timed call does not pay for the handshake
+        engine.prepare().await?;
+
+        // the warmup blocks advance the node state, but are left out of the results
+        let (warmup_blocks, blocks) = split_warmup(blocks, self.benchmark.warmup);
+        if let Some(warmup_blocks) = warmup_blocks {
+            info!(?warmup_blocks, "Replaying warmup blocks");
+            let report = replay_from_rpc(&source, &engine, warmup_blocks, &wait_config).await?;
+            if let Some(failure) = report.failure {
+                return Err(eyre::eyre!(
+                    "Warmup block {} ({}) failed: {}",
+                    failure.block_number,
+                    failure.block_hash,
+                    failure.error
+                ))
+            }
+        }
+
+        let report = replay_from_rpc(&source, &engine, blocks, &wait_config).await?;
+        log_report(&report);
+        if let Some(failure) = report.failure {
+            return Err(eyre::eyre!(
+                "Block {} ({}) failed: {}",
+                failure.block_number,
+                failure.block_hash,
+                failure.error
+            ))
+        }
+
+        Ok(())
+    }
+}
+
+/// Splits the first `warmup` blocks off the given range, and returns them, if any, together with
+/// the remaining blocks.
+fn split_warmup(
+    blocks: RangeInclusive<u64>,
+    warmup: u64,
+) -> (Option<RangeInclusive<u64>>, RangeInclusive<u64>) {
+    let (from, to) = blocks.into_inner();
+    let first = from.saturating_add(warmup);
+    let warmup_blocks = (warmup > 0).then(|| from..=(first - 1).min(to));
+    (warmup_blocks, first..=to)
+}
+
+/// Logs the throughput and the latency summary of the given replay.
+fn log_report(report: &ReplayReport) {
+    info!(
+        total_duration=?report.total_duration,
+        total_gas_used=report.total_gas_used(),
+        blocks_processed=report.blocks.len(),
+        "Total Mgas/s: {:.4}",
+        report.mgas_per_second()
+    );
+    info!(
+        total_blob_gas_used = report.total_blob_gas_used(),
+        "Total blob Mgas/s: {:.4}",
+        report.blob_mgas_per_second()
+    );
+
+    let new_payload_latencies =
+        report.blocks.iter().map(|block| block.new_payload_latency).collect::<LatencyHistogram>();
+    let latency_summary = LatencySummary::default().with_row("newPayload", new_payload_latencies);
+    info!("Latency summary:\n{latency_summary}");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_warmup_blocks() {
+        assert_eq!(split_warmup(0..=9, 0), (None, 0..=9));
+        assert_eq!(split_warmup(0..=9, 3), (Some(0..=2), 3..=9));
+
+        // all blocks are warmup blocks, so no results are reported
+        let (warmup_blocks, blocks) = split_warmup(5..=9, 10);
+        assert_eq!(warmup_blocks, Some(5..=9));
+        assert!(blocks.is_empty());
+    }
+}
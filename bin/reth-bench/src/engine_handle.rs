@@ -0,0 +1,286 @@
+//! An in-memory engine API transport, that answers engine API requests by forwarding them to a
+//! local [`BeaconConsensusEngineHandle`] instead of sending them to a server.
+//!
+//! This allows using [`EngineApiValidWaitExt`](crate::valid_payload::EngineApiValidWaitExt)
+//! against an embedded engine, without an HTTP or IPC socket in between.
+
+use alloy_json_rpc::{ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload};
+use alloy_provider::RootProvider;
+use alloy_rpc_client::RpcClient;
+use alloy_rpc_types_engine::{
+    CancunPayloadFields, ExecutionPayloadInputV2, ExecutionPayloadV4, ForkchoiceState,
+};
+use alloy_transport::{TransportError, TransportFut};
+use futures::future::join_all;
+use reth_beacon_consensus::BeaconConsensusEngineHandle;
+use reth_node_api::EngineTypes;
+use reth_primitives::B256;
+use reth_rpc_types::{ExecutionPayload, ExecutionPayloadV1, ExecutionPayloadV3};
+use reth_rpc_types_compat::engine::payload::convert_payload_input_v2_to_payload;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
+use std::{
+    fmt::Display,
+    task::{Context, Poll},
+};
+
+/// The JSON-RPC error code for an unknown method.
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+
+/// The JSON-RPC error code for params that could not be parsed.
+const INVALID_PARAMS_CODE: i64 = -32602;
+
+/// The JSON-RPC error code for a call that the engine failed to process.
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+/// A [`tower::Service`] transport that forwards the `engine_newPayloadV1`-`V4` and
+/// `engine_forkchoiceUpdatedV1`-`V3` calls to a [`BeaconConsensusEngineHandle`].
+///
+/// The calls are passed to the engine as is, without the version specific validation of the
+/// engine RPC API. All other methods fail with a method not found error.
+#[derive(Debug, Clone)]
+pub struct EngineHandleTransport<Engine: EngineTypes> {
+    handle: BeaconConsensusEngineHandle<Engine>,
+}
+
+impl<Engine: EngineTypes + 'static> EngineHandleTransport<Engine> {
+    /// Creates a new transport forwarding all engine calls to the given handle.
+    pub const fn new(handle: BeaconConsensusEngineHandle<Engine>) -> Self {
+        Self { handle }
+    }
+
+    /// Returns a provider sending all requests to the engine.
+    pub fn provider(&self) -> RootProvider<Self> {
+        RootProvider::new(RpcClient::new(self.clone(), true))
+    }
+}
+
+impl<Engine: EngineTypes + 'static> tower::Service<RequestPacket>
+    for EngineHandleTransport<Engine>
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let handle = self.handle.clone();
+        Box::pin(async move {
+            let response = match req {
+                RequestPacket::Single(req) => {
+                    ResponsePacket::Single(respond(&handle, req.id().clone(), &req).await)
+                }
+                RequestPacket::Batch(reqs) => ResponsePacket::Batch(
+                    join_all(reqs.iter().map(|req| respond(&handle, req.id().clone(), req))).await,
+                ),
+            };
+            Ok(response)
+        })
+    }
+}
+
+/// Forwards a single request to the engine and wraps the result in a response.
+async fn respond<Engine: EngineTypes>(
+    handle: &BeaconConsensusEngineHandle<Engine>,
+    id: alloy_json_rpc::Id,
+    req: &alloy_json_rpc::SerializedRequest,
+) -> Response {
+    let payload = match dispatch(handle, req.method(), req.params()).await {
+        Ok(result) => ResponsePayload::Success(result),
+        Err(error) => ResponsePayload::Failure(error),
+    };
+    Response { id, payload }
+}
+
+async fn dispatch<Engine: EngineTypes>(
+    handle: &BeaconConsensusEngineHandle<Engine>,
+    method: &str,
+    params: Option<&RawValue>,
+) -> Result<Box<RawValue>, ErrorPayload> {
+    match method {
+        "engine_newPayloadV1" => {
+            let (payload,): (ExecutionPayloadV1,) = parse_params(params)?;
+            to_result(handle.new_payload(payload.into(), None).await)
+        }
+        "engine_newPayloadV2" => {
+            let (payload,): (ExecutionPayloadInputV2,) = parse_params(params)?;
+            to_result(handle.new_payload(convert_payload_input_v2_to_payload(payload), None).await)
+        }
+        "engine_newPayloadV3" => {
+            let (payload, versioned_hashes, parent_beacon_block_root): (
+                ExecutionPayloadV3,
+                Vec<B256>,
+                B256,
+            ) = parse_params(params)?;
+            let cancun_fields = CancunPayloadFields { parent_beacon_block_root, versioned_hashes };
+            to_result(handle.new_payload(payload.into(), Some(cancun_fields)).await)
+        }
+        "engine_newPayloadV4" => {
+            let (payload, versioned_hashes, parent_beacon_block_root): (
+                ExecutionPayloadV4,
+                Vec<B256>,
+                B256,
+            ) = parse_params(params)?;
+            let cancun_fields = CancunPayloadFields { parent_beacon_block_root, versioned_hashes };
+            let payload = ExecutionPayload::from(payload);
+            to_result(handle.new_payload(payload, Some(cancun_fields)).await)
+        }
+        "engine_forkchoiceUpdatedV1" |
+        "engine_forkchoiceUpdatedV2" |
+        "engine_forkchoiceUpdatedV3" => {
+            let (state, payload_attrs): (ForkchoiceState, Option<Engine::PayloadAttributes>) =
+                parse_params(params)?;
+            to_result(handle.fork_choice_updated(state, payload_attrs).await)
+        }
+        _ => Err(error_payload(METHOD_NOT_FOUND_CODE, format!("method not found: {method}"))),
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Option<&RawValue>) -> Result<T, ErrorPayload> {
+    let params = params.map_or("null", RawValue::get);
+    serde_json::from_str(params).map_err(|err| error_payload(INVALID_PARAMS_CODE, err))
+}
+
+fn to_result<T: Serialize, E: Display>(res: Result<T, E>) -> Result<Box<RawValue>, ErrorPayload> {
+    let result = res.map_err(|err| error_payload(INTERNAL_ERROR_CODE, err))?;
+    serde_json::value::to_raw_value(&result).map_err(|err| error_payload(INTERNAL_ERROR_CODE, err))
+}
+
+fn error_payload(code: i64, message: impl Display) -> ErrorPayload {
+    ErrorPayload { code, message: message.to_string(), data: None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valid_payload::{EngineApiValidWaitExt, EngineWaitError, WaitConfig};
+    use alloy_provider::ext::EngineApi;
+    use alloy_rpc_types_engine::{PayloadStatus, PayloadStatusEnum};
+    use reth_beacon_consensus::{BeaconEngineMessage, OnForkChoiceUpdated};
+    use reth_ethereum_engine_primitives::EthEngineTypes;
+    use reth_tokio_util::EventSender;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    /// The payloads received by the engine spawned with [`spawn_engine`].
+    type Received = Arc<Mutex<Vec<(ExecutionPayload, Option<CancunPayloadFields>)>>>;
+
+    /// Spawns an engine that answers `newPayload` with the given statuses in order and every
+    /// forkchoice update with `SYNCING`, and returns a transport to it and the received payloads.
+    fn spawn_engine(
+        statuses: Vec<PayloadStatusEnum>,
+    ) -> (EngineHandleTransport<EthEngineTypes>, Received) {
+        let (to_engine, mut from_handle) = unbounded_channel();
+        let handle = BeaconConsensusEngineHandle::new(to_engine, EventSender::default());
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        let engine_received = received.clone();
+        tokio::spawn(async move {
+            let mut statuses = statuses.into_iter();
+            while let Some(message) = from_handle.recv().await {
+                match message {
+                    BeaconEngineMessage::NewPayload { payload, cancun_fields, tx } => {
+                        engine_received.lock().unwrap().push((payload, cancun_fields));
+                        let status = statuses.next().expect("no status left");
+                        let _ = tx.send(Ok(PayloadStatus::from_status(status)));
+                    }
+                    BeaconEngineMessage::ForkchoiceUpdated { tx, .. } => {
+                        let _ = tx.send(Ok(OnForkChoiceUpdated::syncing()));
+                    }
+                    BeaconEngineMessage::TransitionConfigurationExchanged => {}
+                }
+            }
+        });
+
+        (EngineHandleTransport::new(handle), received)
+    }
+
+    fn payload_v3() -> ExecutionPayloadV3 {
+        let block = reth_primitives::Block {
+            header: reth_primitives::Header {
+                number: 1,
+                parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                blob_gas_used: Some(0),
+                excess_blob_gas: Some(0),
+                ..Default::default()
+            },
+            withdrawals: Some(Default::default()),
+            ..Default::default()
+        };
+        crate::replay::block_to_execution_payload_v3(&block.seal_slow()).0
+    }
+
+    #[tokio::test]
+    async fn new_payload_wait_forwards_to_engine() {
+        let (transport, received) =
+            spawn_engine(vec![PayloadStatusEnum::Syncing, PayloadStatusEnum::Valid]);
+        let provider = transport.provider();
+
+        let payload = payload_v3();
+        let versioned_hashes = vec![B256::with_last_byte(2)];
+        provider
+            .new_payload_v3_wait(payload.clone(), versioned_hashes.clone(), B256::with_last_byte(1))
+            .await
+            .unwrap();
+
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[1].0, ExecutionPayload::from(payload));
+        assert_eq!(
+            received[1].1,
+            Some(CancunPayloadFields {
+                parent_beacon_block_root: B256::with_last_byte(1),
+                versioned_hashes
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn new_payload_wait_invalid() {
+        let (transport, _) = spawn_engine(vec![PayloadStatusEnum::Invalid {
+            validation_error: "bad block".to_string(),
+        }]);
+        let err = transport
+            .provider()
+            .new_payload_v3_wait(payload_v3(), Vec::new(), B256::with_last_byte(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineWaitError::Invalid { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn fork_choice_updated_forwards_to_engine() {
+        let (transport, _) = spawn_engine(Vec::new());
+        let provider = transport.provider();
+
+        let updated = provider.fork_choice_updated_v3(ForkchoiceState::default(), None).await;
+        assert_eq!(updated.unwrap().payload_status.status, PayloadStatusEnum::Syncing);
+
+        let config = WaitConfig::default().with_max_attempts(2);
+        let err = provider
+            .fork_choice_updated_v3_wait_with(ForkchoiceState::default(), None, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineWaitError::Timeout { .. }), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn engine_unavailable() {
+        let (to_engine, from_handle) = unbounded_channel();
+        drop(from_handle);
+        let handle =
+            BeaconConsensusEngineHandle::<EthEngineTypes>::new(to_engine, EventSender::default());
+
+        let err = EngineHandleTransport::new(handle)
+            .provider()
+            .new_payload_v3(payload_v3(), Vec::new(), B256::with_last_byte(1))
+            .await
+            .unwrap_err();
+        let payload = err.as_error_resp().unwrap();
+        assert_eq!(payload.code, INTERNAL_ERROR_CODE);
+        assert_eq!(payload.message, "beacon consensus engine task stopped");
+    }
+}
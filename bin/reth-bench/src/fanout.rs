@@ -0,0 +1,282 @@
+//! Sends the same payloads to several engines concurrently, e.g. to compare two node builds
+//! side-by-side under identical load, and collects the results of every engine.
+
+use crate::{
+    bench::output::LatencyHistogram,
+    replay::ReplayBlock,
+    valid_payload::{EngineApiValidWaitExt, EngineWaitError, WaitConfig, WaitMetrics},
+};
+use alloy_rpc_types_engine::PayloadStatus;
+use futures::future::join_all;
+use reth_primitives::B256;
+use std::{fmt, time::Duration};
+use tracing::error;
+
+/// The result of a `newPayload` call on a single endpoint.
+pub type EndpointResult = Result<(PayloadStatus, WaitMetrics), EngineWaitError>;
+
+/// The results of a single block that was sent to every endpoint.
+#[derive(Debug)]
+pub struct FanOutBlockResult {
+    /// The number of the block.
+    pub block_number: u64,
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The result of every endpoint, in the order of the endpoints.
+    pub endpoints: Vec<EndpointResult>,
+}
+
+impl FanOutBlockResult {
+    /// Returns whether the endpoints disagree on the payload, see [`Self::divergence`].
+    pub fn is_divergent(&self) -> bool {
+        self.divergence().is_some()
+    }
+
+    /// Compares the statuses returned by the endpoints, and returns a [`Divergence`] if they
+    /// disagree.
+    ///
+    /// The endpoints disagree if some endpoint considered the payload VALID while another
+    /// considered it INVALID, or if they returned different latest valid hashes. The validation
+    /// errors are not compared, because they are not part of consensus. Endpoints whose call
+    /// failed for another reason, e.g. a timeout, are not taken into account.
+    pub fn divergence(&self) -> Option<Divergence> {
+        let statuses = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .filter_map(|(endpoint, result)| Some((endpoint, settled_status(result)?.clone())))
+            .collect::<Vec<_>>();
+        let (_, first) = statuses.first()?;
+        if statuses.iter().all(|(_, status)| agree(first, status)) {
+            return None
+        }
+        Some(Divergence { block_number: self.block_number, block_hash: self.block_hash, statuses })
+    }
+
+    /// Returns the [`WaitMetrics`] of the given endpoint, or `None` if its call failed.
+    pub fn metrics(&self, endpoint: usize) -> Option<&WaitMetrics> {
+        self.endpoints.get(endpoint)?.as_ref().ok().map(|(_, metrics)| metrics)
+    }
+}
+
+/// Returns the VALID or INVALID status returned by the endpoint, or `None` if its call failed for
+/// another reason.
+const fn settled_status(result: &EndpointResult) -> Option<&PayloadStatus> {
+    match result {
+        Ok((status, _)) | Err(EngineWaitError::Invalid { status, .. }) => Some(status),
+        Err(_) => None,
+    }
+}
+
+/// Returns whether the given statuses agree on the validity and the latest valid hash.
+fn agree(a: &PayloadStatus, b: &PayloadStatus) -> bool {
+    let is_invalid = |status: &PayloadStatus| status.status.is_invalid();
+    is_invalid(a) == is_invalid(b) && a.latest_valid_hash == b.latest_valid_hash
+}
+
+/// The endpoints disagreed on the status of a payload, which indicates a consensus bug in one of
+/// them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The number of the block.
+    pub block_number: u64,
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The status returned by every endpoint that settled, with the index of the endpoint.
+    pub statuses: Vec<(usize, PayloadStatus)>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "endpoints disagree on block {} ({})", self.block_number, self.block_hash)?;
+        for (endpoint, status) in &self.statuses {
+            write!(f, "\n  endpoint {endpoint}: {status}")?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of [`fan_out_payloads`].
+#[derive(Debug, Default)]
+pub struct FanOutReport {
+    /// The results of all blocks, in order.
+    pub blocks: Vec<FanOutBlockResult>,
+}
+
+impl FanOutReport {
+    /// Returns the blocks on which the endpoints disagreed, see
+    /// [`FanOutBlockResult::is_divergent`].
+    pub fn divergent_blocks(&self) -> impl Iterator<Item = &FanOutBlockResult> {
+        self.blocks.iter().filter(|block| block.is_divergent())
+    }
+
+    /// Returns the [`Divergence`] of every block on which the endpoints disagreed, in order.
+    pub fn divergences(&self) -> Vec<Divergence> {
+        self.blocks.iter().filter_map(FanOutBlockResult::divergence).collect()
+    }
+
+    /// Records the result of a block, and logs its [`Divergence`] as an error if the endpoints
+    /// disagree on it.
+    pub fn record(&mut self, result: FanOutBlockResult) {
+        if let Some(divergence) = result.divergence() {
+            error!(
+                block_number = divergence.block_number,
+                block_hash = %divergence.block_hash,
+                "Consensus divergence: {divergence}",
+            );
+        }
+        self.blocks.push(result);
+    }
+
+    /// Returns the given percentile (between `0.0` and `1.0`) of the `newPayload` latencies of
+    /// the given endpoint, or `None` if no call to the endpoint succeeded.
+    pub fn new_payload_latency_percentile(
+        &self,
+        endpoint: usize,
+        percentile: f64,
+    ) -> Option<Duration> {
+        self.new_payload_latencies(endpoint).percentile(percentile)
+    }
+
+    /// Returns the `newPayload` latencies of all successful calls to the given endpoint.
+    pub(crate) fn new_payload_latencies(&self, endpoint: usize) -> LatencyHistogram {
+        self.blocks
+            .iter()
+            .filter_map(|block| block.metrics(endpoint).map(|metrics| metrics.valid_after))
+            .collect()
+    }
+}
+
+/// Sends the given block with `engine_newPayloadV3` to all given endpoints concurrently, and waits
+/// until every endpoint settled according to the given [`WaitConfig`].
+///
+/// A failing endpoint does not affect the calls to the other endpoints.
+pub async fn fan_out_new_payload_v3<N, T, P>(
+    providers: &[P],
+    block: &ReplayBlock,
+    config: &WaitConfig,
+) -> FanOutBlockResult
+where
+    P: EngineApiValidWaitExt<N, T>,
+{
+    let endpoints = join_all(providers.iter().map(|provider| {
+        provider.new_payload_v3_wait_with(
+            block.payload.clone(),
+            block.versioned_hashes.clone(),
+            block.parent_beacon_block_root,
+            config,
+        )
+    }))
+    .await;
+
+    let payload = &block.payload.payload_inner.payload_inner;
+    FanOutBlockResult {
+        block_number: payload.block_number,
+        block_hash: payload.block_hash,
+        endpoints,
+    }
+}
+
+/// Sends every block in order to all given endpoints with [`fan_out_new_payload_v3`].
+///
+/// Only `newPayload` is sent, the forkchoice states of the blocks are ignored. A block on which
+/// the endpoints disagree is logged as an error with its [`Divergence`] and recorded, but does not
+/// stop the fan-out, see [`FanOutReport::record`].
+pub async fn fan_out_payloads<N, T, P>(
+    providers: &[P],
+    blocks: impl IntoIterator<Item = ReplayBlock>,
+    config: &WaitConfig,
+) -> FanOutReport
+where
+    P: EngineApiValidWaitExt<N, T>,
+{
+    let mut report = FanOutReport::default();
+    for block in blocks {
+        report.record(fan_out_new_payload_v3(providers, &block, config).await);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valid_payload::tests::MockEngineApi;
+    use alloy_rpc_types_engine::PayloadStatusEnum;
+    use reth_primitives::{Block, Header};
+
+    fn block(number: u64) -> ReplayBlock {
+        let block = Block {
+            header: Header {
+                number,
+                parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        ReplayBlock::new_payload_only(&block.seal_slow())
+    }
+
+    #[tokio::test]
+    async fn fan_out_flags_divergence() {
+        let invalid = || PayloadStatusEnum::Invalid { validation_error: "bad block".to_string() };
+        let provider =
+            |statuses: Vec<PayloadStatusEnum>| MockEngineApi::with_statuses(statuses).provider();
+
+        let providers = [
+            provider(vec![PayloadStatusEnum::Valid, PayloadStatusEnum::Valid]),
+            provider(vec![PayloadStatusEnum::Syncing, PayloadStatusEnum::Valid, invalid()]),
+        ];
+        let report =
+            fan_out_payloads(&providers, [block(1), block(2)], &WaitConfig::default()).await;
+        assert_eq!(report.blocks.len(), 2);
+        assert!(!report.blocks[0].is_divergent());
+        assert_eq!(report.blocks[0].metrics(1).unwrap().poll_count, 2);
+        let divergent = report.divergent_blocks().map(|block| block.block_number);
+        assert_eq!(divergent.collect::<Vec<_>>(), [2]);
+        assert!(matches!(report.blocks[1].endpoints[1], Err(EngineWaitError::Invalid { .. })));
+        assert!(report.blocks[1].metrics(1).is_none());
+        assert!(report.new_payload_latency_percentile(1, 1.0).is_some());
+
+        // accepted INVALID responses are compared as well
+        let providers = [provider(vec![PayloadStatusEnum::Valid]), provider(vec![invalid()])];
+        let config = WaitConfig::default().with_accept_invalid();
+        assert!(fan_out_new_payload_v3(&providers, &block(3), &config).await.is_divergent());
+
+        // failures other than INVALID are not a divergence
+        let providers = [provider(Vec::new()), provider(vec![invalid()])];
+        let result = fan_out_new_payload_v3(&providers, &block(3), &WaitConfig::default()).await;
+        assert!(matches!(result.endpoints[0], Err(EngineWaitError::Transport(_))));
+        assert!(!result.is_divergent());
+    }
+
+    #[tokio::test]
+    async fn divergent_latest_valid_hash() {
+        let status = |hash| PayloadStatus::new(PayloadStatusEnum::Valid, Some(hash));
+        let mocks = [MockEngineApi::default(), MockEngineApi::default(), MockEngineApi::default()];
+        mocks[0].push_success(status(B256::with_last_byte(1)));
+        mocks[1].push_success(status(B256::with_last_byte(1)));
+        mocks[2].push_success(status(B256::with_last_byte(2)));
+        let providers = mocks.iter().map(MockEngineApi::provider).collect::<Vec<_>>();
+
+        let report = fan_out_payloads(&providers, [block(1)], &WaitConfig::default()).await;
+        let divergences = report.divergences();
+        assert_eq!(divergences.len(), 1);
+        let divergence = &divergences[0];
+        assert_eq!(divergence.statuses.len(), 3);
+        assert_eq!(divergence.statuses[2], (2, status(B256::with_last_byte(2))));
+        assert_eq!(
+            divergence.to_string().lines().next().unwrap(),
+            format!("endpoints disagree on block 1 ({})", report.blocks[0].block_hash)
+        );
+        assert_eq!(divergence.to_string().lines().count(), 4);
+
+        // different validation errors are not a divergence
+        let invalid = |error: &str| PayloadStatusEnum::Invalid { validation_error: error.into() };
+        let providers = [
+            MockEngineApi::with_statuses([invalid("bad state root")]).provider(),
+            MockEngineApi::with_statuses([invalid("state root mismatch")]).provider(),
+        ];
+        let result = fan_out_new_payload_v3(&providers, &block(1), &WaitConfig::default()).await;
+        assert_eq!(result.divergence(), None);
+    }
+}
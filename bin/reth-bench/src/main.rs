@@ -11,6 +11,12 @@ static ALLOC: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 pub mod authenticated_transport;
 pub mod bench;
 pub mod bench_mode;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod engine_handle;
+pub mod fanout;
+pub mod reorg;
+pub mod replay;
+pub mod rpc_source;
 pub mod valid_payload;
 
 use bench::BenchmarkCommand;
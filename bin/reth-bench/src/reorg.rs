@@ -0,0 +1,452 @@
+//! Replays a script of competing branches against an engine, to benchmark how fast it handles
+//! reorgs.
+//!
+//! A [`ReplayScript`] is a list of steps that are run in order: a [`ScriptStep::Branch`] delivers
+//! the payloads of a branch with `engine_newPayloadV3`, and a [`ScriptStep::ForkchoiceUpdated`]
+//! makes a block of any delivered branch the head. A reorg is benchmarked by importing the blocks
+//! `A -> B` of one branch, then delivering a competing branch `B' -> C'` on top of `A`, and
+//! updating the forkchoice to `C'`. Scripts are stored as JSON:
+//!
+//! ```json
+//! {
+//!   "steps": [
+//!     {
+//!       "branch": {
+//!         "name": "canonical",
+//!         "payloads": [
+//!           { "executionPayload": {}, "versionedHashes": [], "parentBeaconBlockRoot": "0x.." }
+//!         ]
+//!       }
+//!     },
+//!     {
+//!       "forkchoice_updated": {
+//!         "headBlockHash": "0x..",
+//!         "safeBlockHash": "0x..",
+//!         "finalizedBlockHash": "0x.."
+//!       }
+//!     }
+//!   ]
+//! }
+//! ```
+
+use crate::{
+    bench::output::LatencyHistogram,
+    replay::block_to_execution_payload_v3,
+    valid_payload::{EngineApiImportExt, EngineApiValidWaitExt, EngineWaitError, WaitConfig},
+};
+use alloy_rpc_types_engine::{CancunPayloadFields, ForkchoiceState, PayloadStatusEnum};
+use reth_primitives::{SealedBlock, B256};
+use reth_rpc_types::ExecutionPayloadV3;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
+    time::{Duration, Instant},
+};
+use tracing::{debug, info};
+
+/// A list of steps that [`replay_script`] runs in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayScript {
+    /// The steps of the script.
+    pub steps: Vec<ScriptStep>,
+}
+
+impl ReplayScript {
+    /// Reads the JSON script at the given path.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Writes the script to the given path as JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, serde_json::to_vec(self)?)
+    }
+}
+
+/// A step of a [`ReplayScript`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScriptStep {
+    /// Delivers the payloads of a branch with `engine_newPayloadV3`, in order, without making
+    /// them canonical.
+    ///
+    /// The first payload may build on any block the engine knows, e.g. a block of an earlier
+    /// branch that is no longer the head.
+    Branch {
+        /// The name of the branch, which is only used for logging.
+        name: String,
+        /// The payloads of the branch, each building on the one before it.
+        payloads: Vec<ScriptPayload>,
+    },
+    /// Sends `engine_forkchoiceUpdatedV3` with the given state, and waits until it is VALID.
+    ForkchoiceUpdated(ForkchoiceState),
+}
+
+/// The arguments of a single `engine_newPayloadV3` call of a [`ScriptStep::Branch`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptPayload {
+    /// The payload.
+    pub execution_payload: ExecutionPayloadV3,
+    /// The versioned hashes of the blob transactions in the payload.
+    pub versioned_hashes: Vec<B256>,
+    /// The parent beacon block root of the payload.
+    pub parent_beacon_block_root: B256,
+}
+
+impl ScriptPayload {
+    /// Converts the given block into the arguments of `engine_newPayloadV3`, see
+    /// [`block_to_execution_payload_v3`].
+    pub fn from_block(block: &SealedBlock) -> Self {
+        let (execution_payload, versioned_hashes, parent_beacon_block_root) =
+            block_to_execution_payload_v3(block);
+        Self { execution_payload, versioned_hashes, parent_beacon_block_root }
+    }
+
+    const fn block_number(&self) -> u64 {
+        self.execution_payload.payload_inner.payload_inner.block_number
+    }
+
+    const fn block_hash(&self) -> B256 {
+        self.execution_payload.payload_inner.payload_inner.block_hash
+    }
+
+    const fn parent_hash(&self) -> B256 {
+        self.execution_payload.payload_inner.payload_inner.parent_hash
+    }
+}
+
+/// The result of a payload that was delivered by a [`ScriptStep::Branch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptPayloadResult {
+    /// The name of the branch of the payload.
+    pub branch: String,
+    /// The number of the block.
+    pub block_number: u64,
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The status returned by the engine, which is ACCEPTED or SYNCING for a block of a side
+    /// chain that the engine did not execute yet.
+    pub status: PayloadStatusEnum,
+    /// The latency of the `engine_newPayloadV3` call.
+    pub latency: Duration,
+}
+
+/// The result of a [`ScriptStep::ForkchoiceUpdated`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptFcuResult {
+    /// The head block of the forkchoice update.
+    pub head: B256,
+    /// The number of blocks of the previous head that the forkchoice update removed from the
+    /// canonical chain, or `None` if it only extended the canonical chain.
+    pub reorg_depth: Option<u64>,
+    /// The time until the forkchoice update returned VALID.
+    pub latency: Duration,
+    /// The number of calls that were sent until the forkchoice update was VALID.
+    pub poll_count: usize,
+}
+
+impl ScriptFcuResult {
+    /// Returns whether the forkchoice update reorged the canonical chain.
+    pub const fn is_reorg(&self) -> bool {
+        self.reorg_depth.is_some()
+    }
+}
+
+/// The step [`replay_script`] stopped at.
+#[derive(Debug)]
+pub struct ScriptFailure {
+    /// The index of the step in [`ReplayScript::steps`].
+    pub step: usize,
+    /// The error returned by the engine call of the step.
+    pub error: EngineWaitError,
+}
+
+/// The outcome of [`replay_script`].
+#[derive(Debug, Default)]
+pub struct ScriptReport {
+    /// The results of all payloads that were delivered, in order.
+    pub payloads: Vec<ScriptPayloadResult>,
+    /// The results of all forkchoice updates that returned VALID, in order.
+    pub forkchoice_updates: Vec<ScriptFcuResult>,
+    /// The step the script stopped at, if any call failed.
+    pub failure: Option<ScriptFailure>,
+    /// The wall clock time of the whole script.
+    pub total_duration: Duration,
+}
+
+impl ScriptReport {
+    /// Returns the forkchoice updates that reorged the canonical chain.
+    pub fn reorgs(&self) -> impl Iterator<Item = &ScriptFcuResult> {
+        self.forkchoice_updates.iter().filter(|fcu| fcu.is_reorg())
+    }
+
+    /// Returns the given percentile (between `0.0` and `1.0`) of the latencies of the forkchoice
+    /// updates that reorged the canonical chain, or `None` if there was no reorg.
+    pub fn reorg_fcu_latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.reorgs().map(|fcu| fcu.latency).collect::<LatencyHistogram>().percentile(percentile)
+    }
+
+    /// Returns the given percentile (between `0.0` and `1.0`) of the latencies of the forkchoice
+    /// updates that only extended the canonical chain, or `None` if there was none.
+    pub fn extension_fcu_latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.forkchoice_updates
+            .iter()
+            .filter(|fcu| !fcu.is_reorg())
+            .map(|fcu| fcu.latency)
+            .collect::<LatencyHistogram>()
+            .percentile(percentile)
+    }
+}
+
+/// The blocks that were delivered by a script, to tell reorgs from extensions of the canonical
+/// chain.
+#[derive(Debug, Default)]
+struct BlockTree {
+    /// The parent of every delivered block.
+    parents: HashMap<B256, B256>,
+}
+
+impl BlockTree {
+    /// Returns the number of blocks of `old_head` that are not ancestors of `new_head`, or `None`
+    /// if `old_head` is an ancestor of `new_head`, or `new_head` itself.
+    ///
+    /// Blocks that were not delivered by the script, e.g. the blocks the engine had before the
+    /// script started, are assumed to be common ancestors of both heads.
+    fn reorg_depth(&self, old_head: B256, new_head: B256) -> Option<u64> {
+        let mut ancestors = HashSet::from([new_head]);
+        let mut block = new_head;
+        while let Some(parent) = self.parents.get(&block) {
+            ancestors.insert(*parent);
+            block = *parent;
+        }
+
+        let mut depth = 0;
+        let mut block = old_head;
+        while !ancestors.contains(&block) {
+            let Some(parent) = self.parents.get(&block) else { break };
+            depth += 1;
+            block = *parent;
+        }
+        (depth > 0).then_some(depth)
+    }
+}
+
+/// Runs the steps of the given script in order, and measures the latency of every call.
+///
+/// The payloads of a branch are sent with [`EngineApiImportExt::deliver_payload_v3`], so a block
+/// of a side chain that the engine answers with ACCEPTED or SYNCING is not treated as a failure,
+/// nor re-sent. The forkchoice updates are polled until VALID according to the given
+/// [`WaitConfig`], so an engine that responds with SYNCING while it executes the blocks of the new
+/// canonical chain is polled again.
+///
+/// A forkchoice update whose head is not a descendant of the head of the previous forkchoice
+/// update is a reorg, which is logged and whose latency is reported separately, see
+/// [`ScriptReport::reorg_fcu_latency_percentile`]. The first forkchoice update of the script is
+/// never a reorg, because the head of the engine before the script is not known.
+///
+/// The script stops at the first call that fails, including INVALID responses, and records the
+/// failing step in [`ScriptReport::failure`].
+pub async fn replay_script<N, T, P>(
+    provider: &P,
+    script: &ReplayScript,
+    config: &WaitConfig,
+) -> ScriptReport
+where
+    P: EngineApiImportExt<N, T> + EngineApiValidWaitExt<N, T>,
+{
+    let start = Instant::now();
+    let mut report = ScriptReport::default();
+    let mut tree = BlockTree::default();
+    let mut head = None;
+
+    'steps: for (step, script_step) in script.steps.iter().enumerate() {
+        match script_step {
+            ScriptStep::Branch { name, payloads } => {
+                for payload in payloads {
+                    let block_number = payload.block_number();
+                    debug!(branch = %name, block_number, "Delivering payload");
+                    let sidecar = CancunPayloadFields {
+                        parent_beacon_block_root: payload.parent_beacon_block_root,
+                        versioned_hashes: payload.versioned_hashes.clone(),
+                    };
+                    let res = provider
+                        .deliver_payload_v3(payload.execution_payload.clone(), sidecar, config)
+                        .await;
+                    let (status, latency) = match res {
+                        Ok(res) => res,
+                        Err(error) => {
+                            report.failure = Some(ScriptFailure { step, error });
+                            break 'steps
+                        }
+                    };
+                    tree.parents.insert(payload.block_hash(), payload.parent_hash());
+                    report.payloads.push(ScriptPayloadResult {
+                        branch: name.clone(),
+                        block_number,
+                        block_hash: payload.block_hash(),
+                        status: status.status,
+                        latency,
+                    });
+                }
+            }
+            ScriptStep::ForkchoiceUpdated(state) => {
+                let new_head = state.head_block_hash;
+                let reorg_depth = head.and_then(|old_head| tree.reorg_depth(old_head, new_head));
+                let metrics =
+                    match provider.fork_choice_updated_v3_wait_with(*state, None, config).await {
+                        Ok((_, metrics)) => metrics,
+                        Err(error) => {
+                            report.failure = Some(ScriptFailure { step, error });
+                            break
+                        }
+                    };
+                let fcu = ScriptFcuResult {
+                    head: new_head,
+                    reorg_depth,
+                    latency: metrics.valid_after,
+                    poll_count: metrics.poll_count,
+                };
+                if let Some(depth) = reorg_depth {
+                    info!(
+                        depth,
+                        %new_head,
+                        latency = ?fcu.latency,
+                        poll_count = fcu.poll_count,
+                        "Reorged to a competing branch"
+                    );
+                }
+                report.forkchoice_updates.push(fcu);
+                head = Some(new_head);
+            }
+        }
+    }
+
+    report.total_duration = start.elapsed();
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valid_payload::tests::MockEngineApi;
+    use alloy_rpc_types_engine::{ForkchoiceUpdated, PayloadStatus};
+    use reth_primitives::{Block, Bytes, Header};
+
+    fn block(number: u64, parent_hash: B256, extra_data: &'static [u8]) -> SealedBlock {
+        let header = Header {
+            number,
+            parent_hash,
+            extra_data: Bytes::from_static(extra_data),
+            parent_beacon_block_root: Some(B256::with_last_byte(1)),
+            ..Default::default()
+        };
+        Block { header, ..Default::default() }.seal_slow()
+    }
+
+    #[tokio::test]
+    async fn replay_script_measures_reorg() {
+        // A -> B is imported first, then the competing branch B' -> C' on top of A
+        let a = block(1, B256::ZERO, b"");
+        let b = block(2, a.hash(), b"");
+        let b_side = block(2, a.hash(), b"side");
+        let c_side = block(3, b_side.hash(), b"side");
+        let branch = |name: &str, blocks: &[&SealedBlock]| ScriptStep::Branch {
+            name: name.to_string(),
+            payloads: blocks.iter().map(|block| ScriptPayload::from_block(block)).collect(),
+        };
+        let fcu = |head: &SealedBlock| {
+            ScriptStep::ForkchoiceUpdated(ForkchoiceState {
+                head_block_hash: head.hash(),
+                safe_block_hash: a.hash(),
+                finalized_block_hash: a.hash(),
+            })
+        };
+        let script = ReplayScript {
+            steps: vec![
+                branch("canonical", &[&a, &b]),
+                fcu(&b),
+                branch("side", &[&b_side, &c_side]),
+                fcu(&c_side),
+            ],
+        };
+
+        // the script survives a round trip through its file format
+        let path =
+            std::env::temp_dir().join(format!("reth-bench-script-{}.json", std::process::id()));
+        script.save(&path).unwrap();
+        assert_eq!(ReplayScript::load(&path).unwrap(), script);
+        fs::remove_file(path).unwrap();
+
+        // the engine does not execute the side branch until the reorg, which it answers with
+        // SYNCING first
+        let mock = MockEngineApi::default();
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Accepted));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Syncing));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+
+        let report = replay_script(&mock.provider(), &script, &WaitConfig::default()).await;
+        assert!(report.failure.is_none(), "{:?}", report.failure);
+        let statuses = report.payloads.iter().map(|payload| payload.status.clone());
+        assert_eq!(
+            statuses.collect::<Vec<_>>(),
+            [
+                PayloadStatusEnum::Valid,
+                PayloadStatusEnum::Valid,
+                PayloadStatusEnum::Accepted,
+                PayloadStatusEnum::Syncing,
+            ]
+        );
+        assert_eq!(report.payloads[2].branch, "side");
+
+        let [extension, reorg] = report.forkchoice_updates[..] else {
+            panic!("expected two forkchoice updates, got {:?}", report.forkchoice_updates)
+        };
+        assert_eq!(extension.reorg_depth, None);
+        assert_eq!((reorg.head, reorg.reorg_depth, reorg.poll_count), (c_side.hash(), Some(1), 2));
+        assert_eq!(report.reorgs().count(), 1);
+        assert_eq!(report.reorg_fcu_latency_percentile(0.5), Some(reorg.latency));
+        assert_eq!(report.extension_fcu_latency_percentile(0.5), Some(extension.latency));
+        assert_eq!(mock.calls().len(), 7);
+    }
+
+    #[tokio::test]
+    async fn replay_script_stops_at_invalid_payload() {
+        let a = block(1, B256::ZERO, b"");
+        let script = ReplayScript {
+            steps: vec![ScriptStep::Branch {
+                name: "canonical".to_string(),
+                payloads: vec![ScriptPayload::from_block(&a)],
+            }],
+        };
+        let mock = MockEngineApi::with_statuses([PayloadStatusEnum::Invalid {
+            validation_error: "bad block".to_string(),
+        }]);
+
+        let report = replay_script(&mock.provider(), &script, &WaitConfig::default()).await;
+        let failure = report.failure.unwrap();
+        assert_eq!(failure.step, 0);
+        assert!(matches!(failure.error, EngineWaitError::Invalid { .. }), "{:?}", failure.error);
+        assert!(report.payloads.is_empty());
+    }
+
+    #[test]
+    fn reorg_depth() {
+        let hash = B256::with_last_byte;
+        // 1 -> 2 -> 3 and 1 -> 4, where 1 was not delivered by the script
+        let tree = BlockTree {
+            parents: HashMap::from([(hash(2), hash(1)), (hash(3), hash(2)), (hash(4), hash(1))]),
+        };
+        assert_eq!(tree.reorg_depth(hash(2), hash(3)), None);
+        assert_eq!(tree.reorg_depth(hash(3), hash(3)), None);
+        assert_eq!(tree.reorg_depth(hash(3), hash(4)), Some(2));
+        assert_eq!(tree.reorg_depth(hash(4), hash(2)), Some(1));
+        assert_eq!(tree.reorg_depth(hash(3), hash(2)), Some(1));
+    }
+}
@@ -0,0 +1,1255 @@
+//! Replays a contiguous sequence of payloads against an engine, sending a `newPayload` and
+//! optionally a `forkchoiceUpdated` call for each block, and collects the latencies of every call.
+
+use crate::{
+    bench::output::LatencyHistogram,
+    valid_payload::{
+        validate_post_merge, EngineApiValidWaitExt, EngineWaitError, ExecutionPayloadSidecar,
+        WaitConfig,
+    },
+};
+use alloy_rpc_types_engine::{ExecutionPayloadV4, ForkchoiceState};
+use futures::{stream, Stream, StreamExt};
+use reth_node_api::EngineApiMessageVersion;
+use reth_primitives::{constants::gas_units::MEGAGAS, Request, SealedBlock, B256};
+use reth_rpc_types::{ExecutionPayload, ExecutionPayloadV3};
+use reth_rpc_types_compat::engine::payload::block_to_payload_v3;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    convert::Infallible,
+    fs, io,
+    path::{Path, PathBuf},
+    pin::pin,
+    time::{Duration, Instant},
+};
+use tokio::sync::Semaphore;
+use tracing::{info, warn};
+
+/// A block to replay with [`replay_payloads`].
+#[derive(Debug, Clone)]
+pub struct ReplayBlock {
+    /// The payload to send with `engine_newPayload`, see [`replay_payloads`] for the version.
+    pub payload: ExecutionPayloadV3,
+    /// The versioned hashes of the blob transactions in the payload.
+    pub versioned_hashes: Vec<B256>,
+    /// The parent beacon block root of the payload.
+    pub parent_beacon_block_root: B256,
+    /// The EIP-7685 requests of the block, which are only sent with `engine_newPayloadV4`.
+    pub requests: Vec<Request>,
+    /// The forkchoice state to send with `engine_forkchoiceUpdated` after the payload is VALID,
+    /// or `None` to skip the forkchoice update of this block.
+    pub forkchoice_state: Option<ForkchoiceState>,
+}
+
+impl ReplayBlock {
+    /// Creates a [`ReplayBlock`] for a benchmark that imports blocks linearly, whose forkchoice
+    /// update makes the block the head, safe and finalized block, see
+    /// [`forkchoice_state_from_head`].
+    pub fn linear(block: &SealedBlock) -> Self {
+        let (payload, versioned_hashes, parent_beacon_block_root) =
+            block_to_execution_payload_v3(block);
+        Self {
+            payload,
+            versioned_hashes,
+            parent_beacon_block_root,
+            requests: block_requests(block),
+            forkchoice_state: Some(forkchoice_state_from_head(block.hash())),
+        }
+    }
+
+    /// Creates a [`ReplayBlock`] that is only sent with `engine_newPayload`, without a forkchoice
+    /// update.
+    ///
+    /// Replaying such blocks measures the execution-only latency of the payloads, without the
+    /// noise of the forkchoice round trip. This is not a full import: the engine never makes the
+    /// blocks canonical, so its head does not advance.
+    pub fn new_payload_only(block: &SealedBlock) -> Self {
+        let (payload, versioned_hashes, parent_beacon_block_root) =
+            block_to_execution_payload_v3(block);
+        Self {
+            payload,
+            versioned_hashes,
+            parent_beacon_block_root,
+            requests: block_requests(block),
+            forkchoice_state: None,
+        }
+    }
+}
+
+impl ReplayBlock {
+    /// Returns the number of the block.
+    pub const fn block_number(&self) -> u64 {
+        self.payload.payload_inner.payload_inner.block_number
+    }
+}
+
+/// Returns the requests of the given block, or an empty list if it has none.
+fn block_requests(block: &SealedBlock) -> Vec<Request> {
+    block.requests.clone().map(|requests| requests.0).unwrap_or_default()
+}
+
+/// Returns a [`ForkchoiceState`] that sets the head, safe and finalized block to the given block
+/// hash.
+///
+/// This is only correct if blocks are imported linearly, without reorgs: the engine considers the
+/// block finalized, so it must never be reorged out afterwards. Benchmarks that switch between
+/// forks should keep the safe and finalized blocks behind the head instead.
+pub const fn forkchoice_state_from_head(head_block_hash: B256) -> ForkchoiceState {
+    ForkchoiceState {
+        head_block_hash,
+        safe_block_hash: head_block_hash,
+        finalized_block_hash: head_block_hash,
+    }
+}
+
+/// Converts the given block into the arguments of `engine_newPayloadV3`: the payload, the
+/// versioned hashes of its blob transactions, and the parent beacon block root.
+///
+/// The parent beacon block root is zero if the header does not have one, i.e. if the block is from
+/// before Cancun and cannot be sent with `engine_newPayloadV3`.
+pub fn block_to_execution_payload_v3(block: &SealedBlock) -> (ExecutionPayloadV3, Vec<B256>, B256) {
+    let versioned_hashes = block.blob_versioned_hashes_iter().copied().collect();
+    let (payload, parent_beacon_block_root) = block_to_payload_v3(block.clone());
+    (payload, versioned_hashes, parent_beacon_block_root.unwrap_or_default())
+}
+
+/// A block whose payload was sent, and whose forkchoice update is still to be sent.
+#[derive(Debug)]
+struct SentBlock {
+    block_number: u64,
+    block_hash: B256,
+    gas_used: u64,
+    blob_gas_used: u64,
+    version: EngineApiMessageVersion,
+    forkchoice_state: Option<ForkchoiceState>,
+}
+
+impl SentBlock {
+    const fn failure(self, error: EngineWaitError) -> ReplayFailure {
+        ReplayFailure { block_number: self.block_number, block_hash: self.block_hash, error }
+    }
+}
+
+/// The latencies of a single replayed block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayBlockResult {
+    /// The number of the block.
+    pub block_number: u64,
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The gas used by the block.
+    pub gas_used: u64,
+    /// The blob gas used by the block, zero for blocks before Cancun, or Ecotone on OP stack
+    /// chains.
+    pub blob_gas_used: u64,
+    /// The time since the start of the replay until the block was imported.
+    pub elapsed: Duration,
+    /// The time until `engine_newPayloadV3` returned VALID.
+    pub new_payload_latency: Duration,
+    /// The time until `engine_forkchoiceUpdatedV3` returned VALID, or `None` if the block was
+    /// replayed without a forkchoice update.
+    pub fcu_latency: Option<Duration>,
+}
+
+/// The block the replay stopped at.
+#[derive(Debug)]
+pub struct ReplayFailure {
+    /// The number of the block.
+    pub block_number: u64,
+    /// The hash of the block.
+    pub block_hash: B256,
+    /// The error returned by the engine call for this block.
+    pub error: EngineWaitError,
+}
+
+/// A forkchoice update after which the engine reported a different head than the one that was
+/// requested, i.e. the engine reorged away from the replayed chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReorgEvent {
+    /// The number of the block whose forkchoice update reorged.
+    pub block_number: u64,
+    /// The head that was requested, which is the parent of the next block of the replayed chain.
+    pub old_head: B256,
+    /// The head the engine reported as the latest valid hash of the forkchoice update.
+    pub new_head: B256,
+    /// The number of replayed blocks between the reported head and the requested head, or `None`
+    /// if the reported head is not one of the replayed blocks.
+    pub depth: Option<u64>,
+}
+
+/// The outcome of [`replay_payloads`].
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    /// The results of all blocks that were replayed successfully, in order.
+    pub blocks: Vec<ReplayBlockResult>,
+    /// The block the replay stopped at, if any call failed.
+    pub failure: Option<ReplayFailure>,
+    /// The forkchoice updates after which the engine reported a different head than requested, in
+    /// order.
+    ///
+    /// A reorg does not stop the replay, but the blocks after it are built on a parent that is no
+    /// longer the head of the engine, so the results of a run with reorgs are not comparable.
+    pub reorgs: Vec<ReorgEvent>,
+    /// The wall clock time of the whole replay.
+    pub total_duration: Duration,
+}
+
+impl ReplayReport {
+    /// Returns the [`ReorgEvent`] of the forkchoice update of the given block, which requested
+    /// `old_head` but made `new_head` the head of the engine.
+    ///
+    /// The depth is derived from the replayed blocks, so it is only known if the new head is one of
+    /// them.
+    fn reorg(&self, block_number: u64, old_head: B256, new_head: B256) -> ReorgEvent {
+        let depth = self
+            .blocks
+            .iter()
+            .rev()
+            .find(|block| block.block_hash == new_head)
+            .map(|block| block_number.saturating_sub(block.block_number));
+        ReorgEvent { block_number, old_head, new_head, depth }
+    }
+
+    /// Returns the given percentile (between `0.0` and `1.0`) of the `newPayload` latencies, or
+    /// `None` if no block was replayed.
+    pub fn new_payload_latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        latency_percentile(self.blocks.iter().map(|block| block.new_payload_latency), percentile)
+    }
+
+    /// Returns the given percentile (between `0.0` and `1.0`) of the `forkchoiceUpdated`
+    /// latencies, or `None` if no block was replayed with a forkchoice update.
+    pub fn fcu_latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        latency_percentile(self.blocks.iter().filter_map(|block| block.fcu_latency), percentile)
+    }
+
+    /// Returns the total gas used by all blocks that were replayed successfully.
+    pub fn total_gas_used(&self) -> u64 {
+        self.blocks.iter().map(|block| block.gas_used).sum()
+    }
+
+    /// Returns the throughput of the whole replay in Mgas/s, i.e. the
+    /// [total gas used](Self::total_gas_used) divided by the wall clock time of the replay.
+    pub fn mgas_per_second(&self) -> f64 {
+        mgas_per_second(self.total_gas_used(), self.total_duration)
+    }
+
+    /// Returns the total blob gas used by all blocks that were replayed successfully.
+    pub fn total_blob_gas_used(&self) -> u64 {
+        self.blocks.iter().map(|block| block.blob_gas_used).sum()
+    }
+
+    /// Returns the blob gas throughput of the whole replay in Mgas/s, which is zero if only blocks
+    /// without blob gas were replayed.
+    pub fn blob_mgas_per_second(&self) -> f64 {
+        mgas_per_second(self.total_blob_gas_used(), self.total_duration)
+    }
+
+    /// Returns the sum of the latencies of all calls, which is roughly how long the replay would
+    /// have taken if no calls had overlapped.
+    pub fn sequential_duration(&self) -> Duration {
+        self.blocks
+            .iter()
+            .map(|block| block.new_payload_latency + block.fcu_latency.unwrap_or_default())
+            .sum()
+    }
+
+    /// Returns how much faster the replay was than sending its calls one after another, i.e. the
+    /// [`Self::sequential_duration`] divided by the wall clock time of the replay, or `None` if no
+    /// block was replayed.
+    ///
+    /// A value above `1.0` means pipelining improved the throughput. The estimate is conservative,
+    /// because the engine usually takes longer to answer a call while other calls are in flight.
+    pub fn pipelining_speedup(&self) -> Option<f64> {
+        if self.blocks.is_empty() || self.total_duration.is_zero() {
+            return None
+        }
+        Some(self.sequential_duration().as_secs_f64() / self.total_duration.as_secs_f64())
+    }
+
+    /// Returns the throughput in Mgas/s over a rolling window of the given number of blocks, with
+    /// one entry per replayed block.
+    ///
+    /// Each entry is the gas used by the block and the `window - 1` blocks before it, divided by
+    /// the time between the import of the block before the window (or the start of the replay)
+    /// and the import of the block. The first `window - 1` entries cover fewer blocks.
+    pub fn rolling_mgas_per_second(&self, window: usize) -> Vec<f64> {
+        let window = window.max(1);
+        (0..self.blocks.len())
+            .map(|end| {
+                let start = (end + 1).saturating_sub(window);
+                let since = start.checked_sub(1).map_or(Duration::ZERO, |i| self.blocks[i].elapsed);
+                let gas_used = self.blocks[start..=end].iter().map(|block| block.gas_used).sum();
+                mgas_per_second(gas_used, self.blocks[end].elapsed - since)
+            })
+            .collect()
+    }
+}
+
+/// The progress of a replay, written by [`replay_payloads_checkpointed`] to resume an interrupted
+/// replay.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayCheckpoint {
+    /// The number of the last block that was imported successfully.
+    pub block_number: u64,
+    /// The hash of the last block that was imported successfully.
+    pub block_hash: B256,
+    /// The number of blocks imported over all runs.
+    pub blocks_processed: u64,
+    /// The gas used by all blocks imported over all runs.
+    pub total_gas_used: u64,
+    /// The sum of the `newPayload` latencies over all runs.
+    pub total_new_payload_latency: Duration,
+    /// The sum of the `forkchoiceUpdated` latencies over all runs.
+    pub total_fcu_latency: Duration,
+    /// The wall clock time of all runs, up to the import of the last block.
+    pub elapsed: Duration,
+}
+
+impl ReplayCheckpoint {
+    /// Reads the checkpoint at the given path, or returns `None` if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Option<Self>> {
+        match fs::read(path) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes the checkpoint to the given path.
+    ///
+    /// The checkpoint is written to a temporary file that replaces the previous checkpoint, so a
+    /// benchmark that is killed while writing does not leave a corrupt checkpoint behind.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(self)?)?;
+        fs::rename(tmp, path)
+    }
+
+    /// Returns the throughput over all runs in Mgas/s.
+    pub fn mgas_per_second(&self) -> f64 {
+        mgas_per_second(self.total_gas_used, self.elapsed)
+    }
+
+    /// Adds the given imported block to the checkpoint, `previous_runs` being the wall clock time
+    /// of the runs before the current one.
+    fn record(&mut self, block: &ReplayBlockResult, previous_runs: Duration) {
+        self.block_number = block.block_number;
+        self.block_hash = block.block_hash;
+        self.blocks_processed += 1;
+        self.total_gas_used += block.gas_used;
+        self.total_new_payload_latency += block.new_payload_latency;
+        self.total_fcu_latency += block.fcu_latency.unwrap_or_default();
+        self.elapsed = previous_runs + block.elapsed;
+    }
+}
+
+/// Configures the checkpoints of [`replay_payloads_checkpointed`].
+#[derive(Debug, Clone)]
+pub struct CheckpointConfig {
+    /// The file the checkpoint is written to and resumed from.
+    pub path: PathBuf,
+    /// The number of imported blocks after which the checkpoint is written.
+    pub interval: u64,
+}
+
+/// Returns the given gas used over the given duration in Mgas/s, or zero if the duration is zero.
+fn mgas_per_second(gas_used: u64, duration: Duration) -> f64 {
+    if duration.is_zero() {
+        return 0.0
+    }
+    gas_used as f64 / MEGAGAS as f64 / duration.as_secs_f64()
+}
+
+/// Returns the nearest-rank percentile of the given latencies.
+fn latency_percentile(
+    latencies: impl Iterator<Item = Duration>,
+    percentile: f64,
+) -> Option<Duration> {
+    latencies.collect::<LatencyHistogram>().percentile(percentile)
+}
+
+/// Sends every block in order with `engine_newPayload` followed by `engine_forkchoiceUpdated`,
+/// waiting for each call to be VALID according to the given [`WaitConfig`].
+///
+/// If [`WaitConfig::chain_spec`] is set, the version of both calls is selected from the timestamp
+/// of the block with [`EngineApiMessageVersion::for_timestamp_with_overrides`], so a replay can
+/// cross hardforks. The activation timestamps of [`WaitConfig::fork_overrides`] take precedence
+/// over the chain spec, so the versions match a node with a custom fork schedule. Otherwise every
+/// block is sent with `engine_newPayloadV3` and `engine_forkchoiceUpdatedV3`. The chain spec also
+/// rejects blocks from before the merge with [`EngineWaitError::PreMergeBlock`] before they are
+/// sent, instead of the confusing error the engine returns for them, so a replay of a range that
+/// starts before the merge fails at its first block.
+///
+/// The forkchoice update is skipped for blocks without a [`ReplayBlock::forkchoice_state`], see
+/// [`ReplayBlock::new_payload_only`].
+///
+/// The replay stops at the first call that fails, including INVALID responses, and records the
+/// failing block in [`ReplayReport::failure`].
+///
+/// After every forkchoice update, the latest valid hash returned by the engine is compared against
+/// the requested head, which the next block expects as its parent. If the engine reports a
+/// different head, a [`ReorgEvent`] is logged and recorded in [`ReplayReport::reorgs`].
+///
+/// At most `depth` blocks are in flight at once, a block being in flight from its `newPayload`
+/// call until its `forkchoiceUpdated` call returned VALID. With a depth of `1` (or `0`) every call
+/// is sent once the previous one returned, with a larger depth the `newPayload` calls of the next
+/// blocks are sent while the `forkchoiceUpdated` calls of the previous blocks are in flight. The
+/// `newPayload` calls are always sent one after another in order, as are the `forkchoiceUpdated`
+/// calls, and the forkchoice of a block is only updated once its `newPayload` call returned
+/// VALID. See [`ReplayReport::pipelining_speedup`] for the effect of pipelining.
+pub async fn replay_payloads<N, T, P>(
+    provider: &P,
+    blocks: impl IntoIterator<Item = ReplayBlock>,
+    config: &WaitConfig,
+    depth: usize,
+) -> ReplayReport
+where
+    P: EngineApiValidWaitExt<N, T>,
+{
+    let blocks = stream::iter(blocks).map(Ok::<_, Infallible>);
+    match replay(provider, blocks, config, depth, |_| {}).await {
+        Ok(report) => report,
+        Err(never) => match never {},
+    }
+}
+
+/// Same as [`replay_payloads`], but pulls the blocks lazily from the given stream, e.g. a loader
+/// that reads them from disk or fetches them from a node, so they are never all held in memory.
+///
+/// The stream is only polled for the next block once the previous block was sent and fewer than
+/// `depth` blocks are in flight, so at most one block more than `depth` is buffered however fast
+/// the loader is.
+///
+/// An error of the stream stops the replay once the blocks before it were imported, and is
+/// returned instead of the report.
+pub async fn replay_payload_stream<N, T, P, E>(
+    provider: &P,
+    blocks: impl Stream<Item = Result<ReplayBlock, E>>,
+    config: &WaitConfig,
+    depth: usize,
+) -> Result<ReplayReport, E>
+where
+    P: EngineApiValidWaitExt<N, T>,
+{
+    replay(provider, blocks, config, depth, |_| {}).await
+}
+
+/// Same as [`replay_payloads`], but records the progress of the replay in a [`ReplayCheckpoint`]
+/// file, and resumes from the checkpoint if the file already exists.
+///
+/// On resume, all blocks up to the checkpointed block are skipped, and a forkchoice update sets
+/// the head, safe and finalized block to the checkpointed block before the next block is sent, as
+/// with [`ReplayBlock::linear`]. The checkpoint is written every [`CheckpointConfig::interval`]
+/// imported blocks and when the replay stops, so an interrupted replay re-sends at most that many
+/// blocks. A failed periodic write is logged, and only a failure to write the final checkpoint
+/// is returned as an error.
+///
+/// Returns the report of this run, and the checkpoint with the totals of all runs.
+pub async fn replay_payloads_checkpointed<N, T, P>(
+    provider: &P,
+    blocks: impl IntoIterator<Item = ReplayBlock>,
+    config: &WaitConfig,
+    depth: usize,
+    checkpoint_config: &CheckpointConfig,
+) -> io::Result<(ReplayReport, ReplayCheckpoint)>
+where
+    P: EngineApiValidWaitExt<N, T>,
+{
+    let path = &checkpoint_config.path;
+    let resumed = ReplayCheckpoint::load(path)?;
+    let mut blocks = blocks
+        .into_iter()
+        .skip_while(|block| {
+            resumed.is_some_and(|resumed| block.block_number() <= resumed.block_number)
+        })
+        .peekable();
+
+    if let Some(resumed) = resumed {
+        info!(block_number = resumed.block_number, "Resuming replay from checkpoint");
+        if let Some(next) = blocks.peek() {
+            let version =
+                engine_version(config, next.payload.payload_inner.payload_inner.timestamp);
+            let head = forkchoice_state_from_head(resumed.block_hash);
+            if let Err(error) = send_fork_choice_updated(provider, version, head, config).await {
+                let failure = ReplayFailure {
+                    block_number: resumed.block_number,
+                    block_hash: resumed.block_hash,
+                    error,
+                };
+                let report = ReplayReport { failure: Some(failure), ..Default::default() };
+                return Ok((report, resumed))
+            }
+        }
+    }
+
+    let mut checkpoint = resumed.unwrap_or_default();
+    let previous_runs = checkpoint.elapsed;
+    let interval = checkpoint_config.interval.max(1);
+    let mut unsaved = 0;
+    let blocks = stream::iter(blocks).map(Ok::<_, Infallible>);
+    let report = replay(provider, blocks, config, depth, |block| {
+        checkpoint.record(block, previous_runs);
+        unsaved += 1;
+        if unsaved >= interval {
+            match checkpoint.save(path) {
+                Ok(()) => unsaved = 0,
+                Err(err) => warn!(%err, path = %path.display(), "Failed to write checkpoint"),
+            }
+        }
+    })
+    .await;
+    let report = match report {
+        Ok(report) => report,
+        Err(never) => match never {},
+    };
+
+    if unsaved > 0 {
+        checkpoint.save(path)?;
+    }
+    Ok((report, checkpoint))
+}
+
+/// Runs the replay loop of [`replay_payload_stream`], calling `on_imported` with every block that
+/// was imported successfully.
+async fn replay<N, T, P, E>(
+    provider: &P,
+    blocks: impl Stream<Item = Result<ReplayBlock, E>>,
+    config: &WaitConfig,
+    depth: usize,
+    mut on_imported: impl FnMut(&ReplayBlockResult),
+) -> Result<ReplayReport, E>
+where
+    P: EngineApiValidWaitExt<N, T>,
+{
+    let start = Instant::now();
+    let mut report = ReplayReport::default();
+
+    // every block holds a permit from its `newPayload` call until it is imported, which bounds
+    // the number of blocks in flight
+    let in_flight = Semaphore::new(depth.max(1));
+    let mut new_payloads = pin!(blocks.then(|block| async {
+        let block = block?;
+        let permit = in_flight.acquire().await.expect("semaphore is never closed");
+        let (sent, new_payload) = send_new_payload(provider, block, config).await;
+        Ok((sent, new_payload, permit))
+    }));
+    // the `newPayload` results that arrived while a forkchoice update was in flight
+    let mut settled = VecDeque::new();
+
+    loop {
+        let next = match settled.pop_front() {
+            Some(next) => Some(next),
+            None => new_payloads.next().await,
+        };
+        let Some(next) = next else { break };
+        let (current, new_payload, _permit) = next?;
+        let new_payload_latency = match new_payload {
+            Ok(latency) => latency,
+            Err(error) => {
+                report.failure = Some(current.failure(error));
+                break
+            }
+        };
+
+        let fcu = match current.forkchoice_state {
+            None => Ok(None),
+            Some(state) => {
+                let mut fcu =
+                    pin!(send_fork_choice_updated(provider, current.version, state, config));
+                loop {
+                    tokio::select! {
+                        biased;
+                        fcu = &mut fcu => break fcu.map(Some),
+                        Some(next) = new_payloads.next() => settled.push_back(next),
+                    }
+                }
+            }
+        };
+
+        match fcu {
+            Ok(fcu) => {
+                let fcu_latency = fcu.map(|(latency, reported_head)| {
+                    let expected_head = current.forkchoice_state.map(|state| state.head_block_hash);
+                    if let (Some(old_head), Some(new_head)) = (expected_head, reported_head) {
+                        if old_head != new_head {
+                            let reorg = report.reorg(current.block_number, old_head, new_head);
+                            warn!(
+                                block_number = reorg.block_number,
+                                depth = ?reorg.depth,
+                                %old_head,
+                                %new_head,
+                                "Engine reorged away from the replayed chain"
+                            );
+                            report.reorgs.push(reorg);
+                        }
+                    }
+                    latency
+                });
+                let result = ReplayBlockResult {
+                    block_number: current.block_number,
+                    block_hash: current.block_hash,
+                    gas_used: current.gas_used,
+                    blob_gas_used: current.blob_gas_used,
+                    elapsed: start.elapsed(),
+                    new_payload_latency,
+                    fcu_latency,
+                };
+                on_imported(&result);
+                report.blocks.push(result);
+            }
+            Err(error) => {
+                report.failure = Some(current.failure(error));
+                break
+            }
+        }
+    }
+
+    report.total_duration = start.elapsed();
+    if depth > 1 {
+        if let Some(speedup) = report.pipelining_speedup() {
+            info!(depth, speedup = format!("{speedup:.2}x"), "Pipelined replay finished");
+        }
+    }
+    Ok(report)
+}
+
+/// Returns the engine API version to send a block with the given timestamp with, see
+/// [`replay_payloads`].
+fn engine_version(config: &WaitConfig, timestamp: u64) -> EngineApiMessageVersion {
+    config.engine_version(timestamp).unwrap_or(EngineApiMessageVersion::V3)
+}
+
+async fn send_new_payload<N, T, P: EngineApiValidWaitExt<N, T>>(
+    provider: &P,
+    block: ReplayBlock,
+    config: &WaitConfig,
+) -> (SentBlock, Result<Duration, EngineWaitError>) {
+    let ReplayBlock {
+        payload,
+        versioned_hashes,
+        parent_beacon_block_root,
+        requests,
+        forkchoice_state,
+    } = block;
+    let inner = &payload.payload_inner.payload_inner;
+    let version = engine_version(config, inner.timestamp);
+    let sent = SentBlock {
+        block_number: inner.block_number,
+        block_hash: inner.block_hash,
+        gas_used: inner.gas_used,
+        blob_gas_used: payload.blob_gas_used,
+        version,
+        forkchoice_state,
+    };
+    if let Some(chain_spec) = &config.chain_spec {
+        if let Err(err) = validate_post_merge(chain_spec, sent.block_number) {
+            return (sent, Err(err))
+        }
+    }
+
+    let (payload, sidecar) = match version {
+        EngineApiMessageVersion::V1 => (
+            ExecutionPayload::V1(payload.payload_inner.payload_inner),
+            ExecutionPayloadSidecar::none(),
+        ),
+        EngineApiMessageVersion::V2 => {
+            (ExecutionPayload::V2(payload.payload_inner), ExecutionPayloadSidecar::none())
+        }
+        EngineApiMessageVersion::V3 => (
+            ExecutionPayload::V3(payload),
+            ExecutionPayloadSidecar::v3(versioned_hashes, parent_beacon_block_root),
+        ),
+        EngineApiMessageVersion::V4 => {
+            let payload = ExecutionPayloadV4 {
+                payload_inner: payload,
+                deposit_requests: Vec::new(),
+                withdrawal_requests: Vec::new(),
+            };
+            (
+                ExecutionPayload::V4(payload),
+                ExecutionPayloadSidecar::v4(versioned_hashes, parent_beacon_block_root, requests),
+            )
+        }
+    };
+
+    let start = Instant::now();
+    let res =
+        provider.new_payload_wait_with(payload, sidecar, config).await.map(|_| start.elapsed());
+    (sent, res)
+}
+
+/// Sends the forkchoice update, and returns its latency together with the latest valid hash
+/// returned by the engine, which is its new head.
+async fn send_fork_choice_updated<N, T, P: EngineApiValidWaitExt<N, T>>(
+    provider: &P,
+    version: EngineApiMessageVersion,
+    forkchoice_state: ForkchoiceState,
+    config: &WaitConfig,
+) -> Result<(Duration, Option<B256>), EngineWaitError> {
+    let start = Instant::now();
+    let (updated, _) =
+        provider.fork_choice_updated_wait_with(version, forkchoice_state, None, config).await?;
+    Ok((start.elapsed(), updated.payload_status.latest_valid_hash))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine_handle::EngineHandleTransport;
+    use alloy_provider::RootProvider;
+    use alloy_rpc_types_engine::{PayloadStatus, PayloadStatusEnum};
+    use reth_beacon_consensus::{
+        BeaconConsensusEngineHandle, BeaconEngineMessage, OnForkChoiceUpdated,
+    };
+    use reth_ethereum_engine_primitives::EthEngineTypes;
+    use reth_primitives::{
+        Block, Header, Signature, Transaction, TransactionSigned, TxEip1559, TxEip4844,
+    };
+    use reth_tokio_util::EventSender;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[test]
+    fn block_to_payload_with_blob_transactions() {
+        let blob_tx = |hashes: Vec<B256>| {
+            TransactionSigned::from_transaction_and_signature(
+                Transaction::Eip4844(TxEip4844 {
+                    blob_versioned_hashes: hashes,
+                    ..Default::default()
+                }),
+                Signature::default(),
+            )
+        };
+        let block = Block {
+            header: Header {
+                number: 1,
+                timestamp: 2,
+                parent_beacon_block_root: Some(B256::with_last_byte(3)),
+                blob_gas_used: Some(0x40000),
+                ..Default::default()
+            },
+            body: vec![
+                blob_tx(vec![B256::with_last_byte(1)]),
+                TransactionSigned::from_transaction_and_signature(
+                    Transaction::Eip1559(TxEip1559::default()),
+                    Signature::default(),
+                ),
+                blob_tx(vec![B256::with_last_byte(2), B256::with_last_byte(3)]),
+            ],
+            ..Default::default()
+        }
+        .seal_slow();
+
+        let (payload, versioned_hashes, parent_beacon_block_root) =
+            block_to_execution_payload_v3(&block);
+        assert_eq!(payload.payload_inner.payload_inner.block_hash, block.hash());
+        assert_eq!(payload.payload_inner.payload_inner.transactions.len(), 3);
+        assert_eq!(payload.blob_gas_used, 0x40000);
+        assert_eq!(
+            versioned_hashes,
+            vec![B256::with_last_byte(1), B256::with_last_byte(2), B256::with_last_byte(3)]
+        );
+        assert_eq!(parent_beacon_block_root, B256::with_last_byte(3));
+    }
+
+    #[test]
+    fn linear_replay_block() {
+        let block = Block {
+            header: Header {
+                number: 1,
+                parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+        .seal_slow();
+
+        let replay = ReplayBlock::linear(&block);
+        assert_eq!(replay.payload.payload_inner.payload_inner.block_hash, block.hash());
+        assert_eq!(replay.parent_beacon_block_root, B256::with_last_byte(1));
+        assert_eq!(
+            replay.forkchoice_state,
+            Some(ForkchoiceState {
+                head_block_hash: block.hash(),
+                safe_block_hash: block.hash(),
+                finalized_block_hash: block.hash(),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_new_payload_only() {
+        let (to_engine, mut from_handle) = unbounded_channel();
+        let handle =
+            BeaconConsensusEngineHandle::<EthEngineTypes>::new(to_engine, EventSender::default());
+        let calls = Arc::new(Mutex::new(Vec::new()));
+
+        let engine_calls = calls.clone();
+        tokio::spawn(async move {
+            while let Some(message) = from_handle.recv().await {
+                match message {
+                    BeaconEngineMessage::NewPayload { payload, tx, .. } => {
+                        engine_calls.lock().unwrap().push(("newPayload", payload.block_hash()));
+                        let _ = tx.send(Ok(PayloadStatus::from_status(PayloadStatusEnum::Valid)));
+                    }
+                    BeaconEngineMessage::ForkchoiceUpdated { state, tx, .. } => {
+                        engine_calls
+                            .lock()
+                            .unwrap()
+                            .push(("forkchoiceUpdated", state.head_block_hash));
+                        let _ = tx.send(Ok(OnForkChoiceUpdated::syncing()));
+                    }
+                    BeaconEngineMessage::TransitionConfigurationExchanged => {}
+                }
+            }
+        });
+        let provider = EngineHandleTransport::new(handle).provider();
+
+        let blocks = (1..=3)
+            .map(|number| {
+                Block {
+                    header: Header {
+                        number,
+                        parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+                .seal_slow()
+            })
+            .collect::<Vec<_>>();
+        let replay = vec![
+            ReplayBlock::new_payload_only(&blocks[0]),
+            ReplayBlock::new_payload_only(&blocks[1]),
+            ReplayBlock::linear(&blocks[2]),
+        ];
+
+        // the engine never returns VALID for a forkchoice update, so only the last block fails
+        let config = WaitConfig::default().with_max_attempts(1);
+        let report = replay_payloads(&provider, replay, &config, 2).await;
+        let numbers = report.blocks.iter().map(|block| block.block_number).collect::<Vec<_>>();
+        assert_eq!(numbers, [1, 2]);
+        assert!(report.blocks.iter().all(|block| block.fcu_latency.is_none()));
+        assert_eq!(report.fcu_latency_percentile(0.5), None);
+        let failure = report.failure.unwrap();
+        assert_eq!(failure.block_number, 3);
+        assert!(matches!(failure.error, EngineWaitError::Timeout { .. }), "{:?}", failure.error);
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            [
+                ("newPayload", blocks[0].hash()),
+                ("newPayload", blocks[1].hash()),
+                ("newPayload", blocks[2].hash()),
+                ("forkchoiceUpdated", blocks[2].hash()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_selects_version_by_fork() {
+        use crate::valid_payload::tests::MockEngineApi;
+        use alloy_eips::eip6110::DepositRequest;
+        use alloy_rpc_types_engine::ForkchoiceUpdated;
+        use reth_chainspec::{ChainSpecBuilder, ForkCondition, Hardfork};
+        use reth_primitives::Requests;
+
+        let mock = MockEngineApi::default();
+        for _ in 0..3 {
+            mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+            mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        }
+        let block = |timestamp| {
+            let block = Block {
+                header: Header {
+                    timestamp,
+                    parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                    ..Default::default()
+                },
+                requests: Some(Requests(vec![Request::DepositRequest(DepositRequest::default())])),
+                ..Default::default()
+            };
+            ReplayBlock::linear(&block.seal_slow())
+        };
+        // the chain merged at genesis, otherwise the blocks up to its DAO fork are pre-merge
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .paris_activated()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(0))
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(10))
+            .with_fork(Hardfork::Prague, ForkCondition::Timestamp(20))
+            .build();
+
+        // the blocks at the activation timestamps already use the new version
+        let config = WaitConfig::default().with_chain_spec(Arc::new(chain_spec));
+        let replay = [block(9), block(10), block(20)];
+        let report = replay_payloads(&mock.provider(), replay, &config, 1).await;
+        assert!(report.failure.is_none(), "{:?}", report.failure);
+        assert_eq!(report.blocks.len(), 3);
+        assert_eq!(
+            mock.calls(),
+            [
+                "engine_newPayloadV2",
+                "engine_forkchoiceUpdatedV2",
+                "engine_newPayloadV3",
+                "engine_forkchoiceUpdatedV3",
+                "engine_newPayloadV4",
+                "engine_forkchoiceUpdatedV3",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn replay_rejects_pre_merge_blocks() {
+        use crate::valid_payload::tests::MockEngineApi;
+        use reth_chainspec::MAINNET;
+
+        let block = Block {
+            header: Header { number: 15_537_393, ..Default::default() },
+            ..Default::default()
+        };
+        let mock = MockEngineApi::default();
+        let config = WaitConfig::default().with_chain_spec(MAINNET.clone());
+        let report = replay_payloads(
+            &mock.provider(),
+            [ReplayBlock::linear(&block.seal_slow())],
+            &config,
+            1,
+        )
+        .await;
+
+        let failure = report.failure.unwrap();
+        assert_eq!(failure.block_number, 15_537_393);
+        assert!(
+            matches!(failure.error, EngineWaitError::PreMergeBlock { block_number: 15_537_393 }),
+            "{:?}",
+            failure.error
+        );
+        assert!(mock.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn replay_resumes_from_checkpoint() {
+        use crate::valid_payload::tests::MockEngineApi;
+        use alloy_rpc_types_engine::ForkchoiceUpdated;
+
+        let blocks = (1..=3)
+            .map(|number| {
+                let block = Block {
+                    header: Header {
+                        number,
+                        gas_used: 1_000,
+                        parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                ReplayBlock::linear(&block.seal_slow())
+            })
+            .collect::<Vec<_>>();
+        let path =
+            std::env::temp_dir().join(format!("reth-bench-checkpoint-{}.json", std::process::id()));
+        let checkpoint_config = CheckpointConfig { path: path.clone(), interval: 2 };
+        let config = WaitConfig::default();
+
+        // the replay stops at the invalid third block, after the checkpoint of the second block
+        let mock = MockEngineApi::default();
+        for _ in 0..2 {
+            mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+            mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        }
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+            validation_error: "bad block".to_string(),
+        }));
+        let (report, checkpoint) = replay_payloads_checkpointed(
+            &mock.provider(),
+            blocks.clone(),
+            &config,
+            1,
+            &checkpoint_config,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.failure.unwrap().block_number, 3);
+        assert_eq!(checkpoint.block_number, 2);
+        assert_eq!(checkpoint.blocks_processed, 2);
+        assert_eq!(ReplayCheckpoint::load(&path).unwrap(), Some(checkpoint));
+
+        // the resumed replay sets the head to the second block before sending the third block
+        let mock = MockEngineApi::default();
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        let (report, checkpoint) =
+            replay_payloads_checkpointed(&mock.provider(), blocks, &config, 1, &checkpoint_config)
+                .await
+                .unwrap();
+        assert!(report.failure.is_none(), "{:?}", report.failure);
+        assert_eq!(report.blocks.len(), 1);
+        assert_eq!(
+            mock.calls(),
+            ["engine_forkchoiceUpdatedV3", "engine_newPayloadV3", "engine_forkchoiceUpdatedV3"]
+        );
+        assert_eq!(checkpoint.block_number, 3);
+        assert_eq!(checkpoint.blocks_processed, 3);
+        assert_eq!(checkpoint.total_gas_used, 3_000);
+        assert_eq!(ReplayCheckpoint::load(&path).unwrap(), Some(checkpoint));
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(ReplayCheckpoint::load(&path).unwrap(), None);
+    }
+
+    /// An engine that answers `newPayload` calls immediately and `forkchoiceUpdated` calls after
+    /// a delay, and records the calls with the hash of their block.
+    #[derive(Debug, Clone, Default)]
+    struct SlowForkchoiceEngine {
+        calls: Arc<Mutex<Vec<(String, B256)>>>,
+    }
+
+    impl tower::Service<alloy_json_rpc::RequestPacket> for SlowForkchoiceEngine {
+        type Response = alloy_json_rpc::ResponsePacket;
+        type Error = alloy_transport::TransportError;
+        type Future = alloy_transport::TransportFut<'static>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: alloy_json_rpc::RequestPacket) -> Self::Future {
+            use alloy_json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload};
+            use alloy_rpc_types_engine::{ExecutionPayloadV3, ForkchoiceUpdated};
+
+            let RequestPacket::Single(req) = req else {
+                panic!("batch requests are not supported")
+            };
+            let params = serde_json::from_str::<Vec<serde_json::Value>>(
+                req.params().map_or("[]", |params| params.get()),
+            )
+            .unwrap();
+            let is_fcu = req.method().starts_with("engine_forkchoiceUpdated");
+            let (hash, result) = if is_fcu {
+                let state = serde_json::from_value::<ForkchoiceState>(params[0].clone()).unwrap();
+                let result = ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid);
+                (state.head_block_hash, serde_json::value::to_raw_value(&result).unwrap())
+            } else {
+                let payload = serde_json::from_value::<ExecutionPayloadV3>(params[0].clone());
+                let result = PayloadStatus::from_status(PayloadStatusEnum::Valid);
+                let hash = payload.unwrap().payload_inner.payload_inner.block_hash;
+                (hash, serde_json::value::to_raw_value(&result).unwrap())
+            };
+            self.calls.lock().unwrap().push((req.method().to_string(), hash));
+            let id = req.id().clone();
+            Box::pin(async move {
+                if is_fcu {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                let payload = ResponsePayload::Success(result);
+                Ok(ResponsePacket::Single(Response { id, payload }))
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn pipelined_replay_depth() {
+        let blocks = (1..=3)
+            .map(|number| {
+                let block = Block {
+                    header: Header {
+                        number,
+                        parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                ReplayBlock::linear(&block.seal_slow())
+            })
+            .collect::<Vec<_>>();
+        let hash = |index: usize| blocks[index].payload.payload_inner.payload_inner.block_hash;
+        let new_payload = |index| ("engine_newPayloadV3".to_string(), hash(index));
+        let fcu = |index| ("engine_forkchoiceUpdatedV3".to_string(), hash(index));
+
+        let replay = |depth| {
+            let engine = SlowForkchoiceEngine::default();
+            let provider: RootProvider<_> =
+                RootProvider::new(alloy_rpc_client::RpcClient::new(engine.clone(), true));
+            let blocks = blocks.clone();
+            async move {
+                let report =
+                    replay_payloads(&provider, blocks, &WaitConfig::default(), depth).await;
+                assert!(report.failure.is_none(), "{:?}", report.failure);
+                assert_eq!(report.blocks.len(), 3);
+                let calls = engine.calls.lock().unwrap().clone();
+                (report, calls)
+            }
+        };
+
+        // sequentially, every call waits for the previous one
+        let (sequential, calls) = replay(1).await;
+        assert_eq!(calls, [new_payload(0), fcu(0), new_payload(1), fcu(1), new_payload(2), fcu(2)]);
+        assert!(sequential.pipelining_speedup().unwrap() <= 1.0);
+
+        // the next blocks are sent while the first forkchoice update is in flight, but the
+        // forkchoice updates stay in order
+        let (pipelined, calls) = replay(3).await;
+        assert_eq!(calls, [new_payload(0), fcu(0), new_payload(1), new_payload(2), fcu(1), fcu(2)]);
+        assert!(pipelined.sequential_duration() >= Duration::from_millis(60));
+    }
+
+    #[tokio::test]
+    async fn replay_from_stream() {
+        use crate::valid_payload::tests::MockEngineApi;
+        use alloy_rpc_types_engine::ForkchoiceUpdated;
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let block = |number| {
+            let header = Header {
+                number,
+                parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                ..Default::default()
+            };
+            ReplayBlock::linear(&Block { header, ..Default::default() }.seal_slow())
+        };
+        let loaded = AtomicU64::new(0);
+        let loader = |fail_at| {
+            let loaded = &loaded;
+            stream::iter(1..).map(move |number| {
+                loaded.fetch_add(1, Ordering::Relaxed);
+                if number == fail_at {
+                    Err("failed to load block")
+                } else {
+                    Ok(block(number))
+                }
+            })
+        };
+
+        // the loader is not polled past a block the engine rejects, so the endless stream ends
+        let mock = MockEngineApi::default();
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+            validation_error: "bad block".to_string(),
+        }));
+        let config = WaitConfig::default();
+        let report =
+            replay_payload_stream(&mock.provider(), loader(u64::MAX), &config, 1).await.unwrap();
+        assert_eq!(report.blocks.len(), 1);
+        assert_eq!(report.failure.unwrap().block_number, 2);
+        assert_eq!(loaded.swap(0, Ordering::Relaxed), 2);
+
+        // an error of the loader stops the replay once the previous blocks were imported
+        let mock = MockEngineApi::default();
+        for _ in 0..2 {
+            mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+            mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        }
+        let err = replay_payload_stream(&mock.provider(), loader(3), &config, 2).await.unwrap_err();
+        assert_eq!(err, "failed to load block");
+        assert_eq!(mock.calls().len(), 4);
+        assert_eq!(loaded.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn replay_detects_reorgs() {
+        use crate::valid_payload::tests::MockEngineApi;
+        use alloy_rpc_types_engine::ForkchoiceUpdated;
+
+        let blocks = (1..=4)
+            .map(|number| {
+                let block = Block {
+                    header: Header {
+                        number,
+                        parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                };
+                ReplayBlock::linear(&block.seal_slow())
+            })
+            .collect::<Vec<_>>();
+        let hash = |index: usize| blocks[index].payload.payload_inner.payload_inner.block_hash;
+        let other_chain = B256::with_last_byte(0xff);
+
+        // the engine keeps the first block as its head after the third block, and then switches
+        // to a block that was not replayed
+        let mock = MockEngineApi::default();
+        for head in [hash(0), hash(1), hash(0), other_chain] {
+            mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+            mock.push_success(
+                ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+                    .with_latest_valid_hash(head),
+            );
+        }
+
+        let report =
+            replay_payloads(&mock.provider(), blocks.clone(), &WaitConfig::default(), 1).await;
+        assert!(report.failure.is_none(), "{:?}", report.failure);
+        assert_eq!(report.blocks.len(), 4);
+        assert_eq!(
+            report.reorgs,
+            [
+                ReorgEvent {
+                    block_number: 3,
+                    old_head: hash(2),
+                    new_head: hash(0),
+                    depth: Some(2)
+                },
+                ReorgEvent {
+                    block_number: 4,
+                    old_head: hash(3),
+                    new_head: other_chain,
+                    depth: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn gas_throughput() {
+        let result = |gas_used, blob_gas_used, elapsed| ReplayBlockResult {
+            block_number: 0,
+            block_hash: B256::ZERO,
+            gas_used,
+            blob_gas_used,
+            elapsed: Duration::from_millis(elapsed),
+            new_payload_latency: Duration::ZERO,
+            fcu_latency: None,
+        };
+        let report = ReplayReport {
+            blocks: vec![
+                result(10_000_000, 0, 100),
+                result(30_000_000, 0x60000, 200),
+                result(5_000_000, 0x20000, 300),
+            ],
+            failure: None,
+            reorgs: Vec::new(),
+            total_duration: Duration::from_millis(450),
+        };
+
+        assert_eq!(report.total_gas_used(), 45_000_000);
+        assert_eq!(report.mgas_per_second(), 100.0);
+        assert_eq!(report.total_blob_gas_used(), 0x80000);
+        assert_eq!(ReplayReport::default().total_blob_gas_used(), 0);
+        assert_eq!(report.rolling_mgas_per_second(2), [100.0, 200.0, 175.0]);
+        assert_eq!(report.rolling_mgas_per_second(1), [100.0, 300.0, 50.0]);
+        assert_eq!(ReplayReport::default().rolling_mgas_per_second(2), Vec::<f64>::new());
+
+        // a replay that took no measurable time has no throughput
+        let instant = ReplayReport { blocks: vec![result(10_000_000, 0, 0)], ..Default::default() };
+        assert_eq!(instant.mgas_per_second(), 0.0);
+        assert_eq!(instant.rolling_mgas_per_second(1), [0.0]);
+    }
+
+    #[test]
+    fn percentiles() {
+        let latencies = (1..=100).map(Duration::from_millis);
+        assert_eq!(latency_percentile(latencies.clone(), 0.5), Some(Duration::from_millis(50)));
+        assert_eq!(latency_percentile(latencies.clone(), 0.99), Some(Duration::from_millis(99)));
+        assert_eq!(latency_percentile(latencies.clone(), 1.0), Some(Duration::from_millis(100)));
+        assert_eq!(latency_percentile(latencies, 0.0), Some(Duration::from_millis(1)));
+        assert_eq!(latency_percentile(std::iter::empty(), 0.5), None);
+    }
+}
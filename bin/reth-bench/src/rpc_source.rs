@@ -0,0 +1,258 @@
+//! Replays blocks fetched from a JSON-RPC node, e.g. an archive node, into an engine.
+//!
+//! The blocks are fetched with `eth_getBlockByNumber` including their full transactions,
+//! converted to `engine_newPayloadV3` payloads, and sent with
+//! [`EngineApiValidWaitExt::new_payload_v3_wait_with`].
+
+use crate::{
+    replay::{ReplayBlock, ReplayBlockResult, ReplayFailure, ReplayReport},
+    valid_payload::{EngineApiValidWaitExt, WaitConfig},
+};
+use alloy_provider::{network::Ethereum, Provider};
+use alloy_transport::{Transport, TransportError};
+use reth_primitives::{Block, SealedBlock};
+use reth_rpc_types::ConversionError;
+use std::{ops::RangeInclusive, time::Instant};
+
+/// An error that occurred while fetching a block from the source node.
+#[derive(Debug, thiserror::Error)]
+pub enum RpcSourceError {
+    /// The source node does not have the block.
+    #[error("block {0} was not found on the source node")]
+    BlockNotFound(u64),
+    /// The block returned by the source node could not be converted to a block.
+    #[error("block {number} could not be converted: {error}")]
+    Conversion {
+        /// The number of the block.
+        number: u64,
+        /// The conversion error.
+        error: ConversionError,
+    },
+    /// The call to the source node failed on the transport level.
+    #[error(transparent)]
+    Transport(#[from] TransportError),
+}
+
+/// Fetches blocks from a JSON-RPC node.
+#[derive(Debug, Clone)]
+pub struct RpcBlockSource<P> {
+    provider: P,
+}
+
+impl<P> RpcBlockSource<P> {
+    /// Creates a new source fetching blocks with the given provider.
+    pub const fn new(provider: P) -> Self {
+        Self { provider }
+    }
+
+    /// Fetches the block with the given number, including its full transactions.
+    ///
+    /// The hash returned by the source node is reused, the block is only hashed if the response
+    /// does not contain it.
+    pub async fn fetch_block<T>(&self, number: u64) -> Result<SealedBlock, RpcSourceError>
+    where
+        T: Transport + Clone,
+        P: Provider<T, Ethereum>,
+    {
+        let block = self
+            .provider
+            .get_block_by_number(number.into(), true)
+            .await?
+            .ok_or(RpcSourceError::BlockNotFound(number))?;
+        let hash = block.header.hash;
+        let block =
+            Block::try_from(block).map_err(|error| RpcSourceError::Conversion { number, error })?;
+        Ok(match hash {
+            Some(hash) => block.seal(hash),
+            None => block.seal_slow(),
+        })
+    }
+
+    /// Fetches the block with the given number, and converts it to a [`ReplayBlock`] that only
+    /// sends `engine_newPayloadV3`, with the versioned hashes of its blob transactions, see
+    /// [`ReplayBlock::new_payload_only`].
+    pub async fn fetch_replay_block<T>(&self, number: u64) -> Result<ReplayBlock, RpcSourceError>
+    where
+        T: Transport + Clone,
+        P: Provider<T, Ethereum>,
+    {
+        Ok(ReplayBlock::new_payload_only(&self.fetch_block(number).await?))
+    }
+}
+
+/// Fetches the given range of blocks from the source, and sends every block in order with
+/// `engine_newPayloadV3` to the engine, waiting for each according to the given [`WaitConfig`].
+///
+/// The forkchoice is not updated. The next block is fetched while the engine processes the
+/// current one, so the latency of the source does not add to the duration of the replay.
+///
+/// The replay stops at the first block that fails, which is recorded as the
+/// [`ReplayReport::failure`]. A block that cannot be fetched stops the replay with an error.
+pub async fn replay_from_rpc<S, T, E, N, U>(
+    source: &RpcBlockSource<S>,
+    engine: &E,
+    blocks: RangeInclusive<u64>,
+    config: &WaitConfig,
+) -> Result<ReplayReport, RpcSourceError>
+where
+    T: Transport + Clone,
+    S: Provider<T, Ethereum>,
+    E: EngineApiValidWaitExt<N, U>,
+{
+    let start = Instant::now();
+    let mut report = ReplayReport::default();
+    let mut numbers = blocks;
+    let mut next = match numbers.next() {
+        Some(number) => Some(source.fetch_replay_block(number).await?),
+        None => None,
+    };
+
+    while let Some(block) = next.take() {
+        let fetch = async {
+            match numbers.next() {
+                Some(number) => source.fetch_replay_block(number).await.map(Some),
+                None => Ok(None),
+            }
+        };
+        let new_payload = engine.new_payload_v3_wait_with(
+            block.payload.clone(),
+            block.versioned_hashes.clone(),
+            block.parent_beacon_block_root,
+            config,
+        );
+        let (result, fetched) = tokio::join!(new_payload, fetch);
+
+        let payload = &block.payload.payload_inner.payload_inner;
+        match result {
+            Ok((_, metrics)) => report.blocks.push(ReplayBlockResult {
+                block_number: payload.block_number,
+                block_hash: payload.block_hash,
+                gas_used: payload.gas_used,
+                blob_gas_used: block.payload.blob_gas_used,
+                elapsed: start.elapsed(),
+                new_payload_latency: metrics.valid_after,
+                fcu_latency: None,
+            }),
+            Err(error) => {
+                report.failure = Some(ReplayFailure {
+                    block_number: payload.block_number,
+                    block_hash: payload.block_hash,
+                    error,
+                });
+                break
+            }
+        }
+        next = fetched?;
+    }
+
+    report.total_duration = start.elapsed();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::valid_payload::tests::MockEngineApi;
+    use alloy_rpc_types_engine::{PayloadStatus, PayloadStatusEnum};
+    use reth_primitives::{
+        Address, Header, Signature, Transaction, TransactionSigned, TxEip4844, B256, U256,
+    };
+    use reth_rpc_types::BlockTransactionsKind;
+    use reth_rpc_types_compat::block::from_block;
+
+    /// Returns the `eth_getBlockByNumber` response of a block with the given number that contains
+    /// a blob transaction with the given versioned hash.
+    fn rpc_block(number: u64, versioned_hash: B256) -> (reth_rpc_types::Block, B256) {
+        let blob_tx = TransactionSigned::from_transaction_and_signature(
+            Transaction::Eip4844(TxEip4844 {
+                chain_id: 1,
+                blob_versioned_hashes: vec![versioned_hash],
+                ..Default::default()
+            }),
+            Signature::default(),
+        );
+        let block = Block {
+            header: Header {
+                number,
+                parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                blob_gas_used: Some(0x20000),
+                ..Default::default()
+            },
+            body: vec![blob_tx],
+            ..Default::default()
+        };
+        let hash = block.header.hash_slow();
+        let block = block.with_senders_unchecked(vec![Address::ZERO]);
+        let rpc_block = from_block(block, U256::ZERO, BlockTransactionsKind::Full, None).unwrap();
+        (rpc_block, hash)
+    }
+
+    #[tokio::test]
+    async fn replay_blocks_from_rpc() {
+        let source = MockEngineApi::default();
+        let (block_1, hash_1) = rpc_block(1, B256::with_last_byte(0x11));
+        let (block_2, hash_2) = rpc_block(2, B256::with_last_byte(0x12));
+        source.push_success(block_1);
+        source.push_success(block_2);
+        let engine =
+            MockEngineApi::with_statuses([PayloadStatusEnum::Valid, PayloadStatusEnum::Valid]);
+
+        let report = replay_from_rpc(
+            &RpcBlockSource::new(source.provider()),
+            &engine.provider(),
+            1..=2,
+            &WaitConfig::default(),
+        )
+        .await
+        .unwrap();
+        assert!(report.failure.is_none(), "{:?}", report.failure);
+        let hashes = report.blocks.iter().map(|block| block.block_hash).collect::<Vec<_>>();
+        assert_eq!(hashes, [hash_1, hash_2]);
+        assert_eq!(source.calls(), vec!["eth_getBlockByNumber"; 2]);
+        assert_eq!(engine.calls(), vec!["engine_newPayloadV3"; 2]);
+    }
+
+    #[tokio::test]
+    async fn fetch_block_from_rpc() {
+        let source = MockEngineApi::default();
+        let (block, hash) = rpc_block(1, B256::with_last_byte(0x11));
+        source.push_success(block);
+        source.push_success(None::<reth_rpc_types::Block>);
+        let source = RpcBlockSource::new(source.provider());
+
+        // the versioned hashes are recovered from the blob transactions
+        let block = source.fetch_replay_block(1).await.unwrap();
+        assert_eq!(block.payload.payload_inner.payload_inner.block_hash, hash);
+        assert_eq!(block.versioned_hashes, [B256::with_last_byte(0x11)]);
+        assert_eq!(block.parent_beacon_block_root, B256::with_last_byte(1));
+
+        let err = source.fetch_block(2).await.unwrap_err();
+        assert!(matches!(err, RpcSourceError::BlockNotFound(2)), "{err:?}");
+    }
+
+    #[tokio::test]
+    async fn replay_from_rpc_stops_at_failure() {
+        let source = MockEngineApi::default();
+        for number in 1..=3 {
+            source.push_success(rpc_block(number, B256::with_last_byte(0x11)).0);
+        }
+        let engine = MockEngineApi::default();
+        engine.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+        engine.push_success(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+            validation_error: "bad block".to_string(),
+        }));
+
+        let report = replay_from_rpc(
+            &RpcBlockSource::new(source.provider()),
+            &engine.provider(),
+            1..=3,
+            &WaitConfig::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.blocks.len(), 1);
+        assert_eq!(report.failure.unwrap().block_number, 2);
+        // the third block was fetched while the engine processed the second one
+        assert_eq!(source.calls().len(), 3);
+    }
+}
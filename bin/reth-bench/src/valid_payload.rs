@@ -2,32 +2,1404 @@
 //! response. This is useful for benchmarking, as it allows us to wait for a payload to be valid
 //! before sending additional calls.
 
-use alloy_provider::{ext::EngineApi, Network};
+use alloy_eips::{eip4844::Bytes48, eip7685::Encodable7685};
+use alloy_json_rpc::{RpcError, RpcReturn};
+use alloy_provider::{Network, Provider};
 use alloy_rpc_types_engine::{
-    ExecutionPayloadInputV2, ForkchoiceState, ForkchoiceUpdated, PayloadAttributes, PayloadStatus,
+    CancunPayloadFields, ExecutionPayloadEnvelopeV3, ExecutionPayloadEnvelopeV4,
+    ExecutionPayloadInputV2, ExecutionPayloadV4, ForkchoiceState, ForkchoiceUpdated,
+    MaybeCancunPayloadFields, PayloadAttributes, PayloadError, PayloadId, PayloadStatus,
+    PayloadStatusEnum,
 };
-use alloy_transport::{Transport, TransportResult};
+use alloy_transport::{Transport, TransportError, TransportErrorKind, TransportResult};
+use rand::Rng;
+use reth_chainspec::{ChainSpec, ForkCondition, Hardfork};
 use reth_node_api::EngineApiMessageVersion;
-use reth_primitives::B256;
-use reth_rpc_types::{ExecutionPayload, ExecutionPayloadV1, ExecutionPayloadV3};
-use tracing::error;
+use reth_primitives::{Bytes, Header, Request, SealedBlock, TransactionSigned, B256};
+use reth_rpc_types::{
+    ExecutionPayload, ExecutionPayloadV1, ExecutionPayloadV2, ExecutionPayloadV3,
+};
+use reth_rpc_types_compat::engine::payload::{
+    convert_payload_input_v2_to_payload, try_into_sealed_block,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    future::Future,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, field, info, instrument, warn, Span};
+
+/// Configures how the `*_wait` methods of [`EngineApiValidWaitExt`] re-send a call while the
+/// engine responds with `SYNCING` or `ACCEPTED`.
+///
+/// The default re-sends immediately, never gives up, does not retry failed calls, gives every
+/// call [`DEFAULT_CALL_TIMEOUT`] to respond, and logs the progress of a wait every
+/// [`DEFAULT_PROGRESS_INTERVAL`].
+#[derive(Debug, Clone)]
+pub struct WaitConfig {
+    /// The delay before re-sending a call that did not return VALID.
+    pub poll_interval: Duration,
+    /// The maximum number of calls to send before returning [`EngineWaitError::Timeout`], or
+    /// `None` to poll forever.
+    pub max_attempts: Option<u64>,
+    /// If set, the poll interval is doubled after every attempt, up to this value.
+    pub max_backoff: Option<Duration>,
+    /// If set, every delay is randomly lengthened or shortened by up to this percentage.
+    ///
+    /// This is off by default to keep latency measurements against a single node reproducible.
+    /// Benchmarks driving several engines concurrently should enable it, so the re-sent calls of
+    /// waiters with the same poll interval do not arrive in synchronized bursts.
+    pub jitter: Option<u8>,
+    /// If set, waiting is aborted with [`EngineWaitError::Cancelled`] once the token is cancelled.
+    pub cancel: Option<CancellationToken>,
+    /// The number of times a call that failed with a transient transport error is re-sent in a
+    /// row, see [`is_retryable`].
+    pub transport_retries: u32,
+    /// If set, a single call that does not respond within this duration fails with
+    /// [`CallTimeoutError`], which is retried like a transient transport error.
+    ///
+    /// This bounds how long an engine that never answers can block the benchmark. The engine
+    /// does not respond to `engine_newPayload` before it has executed the payload, so the timeout
+    /// must be well above the time it takes to execute the largest block of the benchmark.
+    pub call_timeout: Option<Duration>,
+    /// If set, a `*_wait` call that does not settle within this duration fails with
+    /// [`EngineWaitError::Timeout`].
+    ///
+    /// The deadline spans all calls that are sent for a single payload, including the delays
+    /// between polls and the re-sent calls after transport errors, and also aborts a call that is
+    /// in flight. It is independent of [`Self::call_timeout`], which bounds every single call.
+    pub per_block_deadline: Option<Duration>,
+    /// If set, payloads are checked against the chain spec before they are sent, see
+    /// [`validate_withdrawals`] and [`validate_parent_beacon_block_root`], and the engine API
+    /// version of a payload is selected from its fork schedule, see [`Self::engine_version`].
+    pub chain_spec: Option<Arc<ChainSpec>>,
+    /// If set, the structure of every payload is checked against [`Self::chain_spec`] with
+    /// [`validate_payload_shape`] before it is sent, so malformed payloads do not cost a round
+    /// trip to the engine.
+    ///
+    /// This has no effect if [`Self::chain_spec`] is not set.
+    pub validate_payloads: bool,
+    /// Activation timestamps of hardforks that override the ones of [`Self::chain_spec`] when
+    /// selecting the engine API version of a payload, see
+    /// [`EngineApiMessageVersion::for_timestamp_with_overrides`].
+    ///
+    /// These must match the fork schedule the node was actually started with: a node rejects a
+    /// payload that is sent with the version of another hardfork with an unsupported fork error.
+    /// This has no effect if [`Self::chain_spec`] is not set.
+    pub fork_overrides: BTreeMap<Hardfork, u64>,
+    /// If set, the `*_wait` methods return INVALID responses like VALID ones, instead of failing
+    /// with [`EngineWaitError::Invalid`].
+    ///
+    /// This allows benchmarking how fast the engine rejects known bad payloads.
+    pub accept_invalid: bool,
+    /// If set, a wait that takes longer than this duration logs its current status and elapsed
+    /// time once per interval, so a long sync does not look like a hung benchmark.
+    pub progress_interval: Option<Duration>,
+    /// If set, this handler is invoked with every INVALID response before the `*_wait` method
+    /// fails with [`EngineWaitError::Invalid`], e.g. to dump the rejected payload to disk.
+    ///
+    /// It is not invoked for INVALID responses that are accepted, see [`Self::accept_invalid`].
+    pub on_invalid: Option<InvalidHandler>,
+    /// If set, the latest valid hash of every VALID `engine_newPayload` response must be the hash
+    /// of the payload, otherwise the call fails with [`EngineWaitError::HashMismatch`].
+    ///
+    /// This turns a benchmark into a correctness check of the engine against the source blocks.
+    pub verify_hashes: bool,
+    /// If set, a V4 payload is re-sent with `engine_newPayloadV3` if the engine does not support
+    /// `engine_newPayloadV4`, i.e. fails with a `-32601` method not found error, see
+    /// [`EngineApiValidWaitExt::new_payload_wait_with`].
+    ///
+    /// This allows benchmarking a node that does not support Prague yet with blocks from before
+    /// Prague. A payload that contains requests, or is after Prague according to
+    /// [`Self::chain_spec`], cannot be sent as V3, and fails with
+    /// [`EngineWaitError::UnsupportedVersion`] instead.
+    pub version_fallback: bool,
+}
+
+impl Default for WaitConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::ZERO,
+            max_attempts: None,
+            max_backoff: None,
+            jitter: None,
+            cancel: None,
+            transport_retries: 0,
+            call_timeout: Some(DEFAULT_CALL_TIMEOUT),
+            per_block_deadline: None,
+            chain_spec: None,
+            validate_payloads: false,
+            fork_overrides: BTreeMap::new(),
+            accept_invalid: false,
+            progress_interval: Some(DEFAULT_PROGRESS_INTERVAL),
+            on_invalid: None,
+            verify_hashes: false,
+            version_fallback: false,
+        }
+    }
+}
+
+impl WaitConfig {
+    /// Sets the delay before re-sending a call that did not return VALID.
+    pub const fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets the maximum number of calls to send before giving up.
+    pub const fn with_max_attempts(mut self, max_attempts: u64) -> Self {
+        self.max_attempts = Some(max_attempts);
+        self
+    }
+
+    /// Enables exponential backoff, doubling the poll interval after every attempt up to
+    /// `max_backoff`.
+    pub const fn with_exponential_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = Some(max_backoff);
+        self
+    }
+
+    /// Enables jitter, randomly lengthening or shortening every delay by up to `percent` percent.
+    ///
+    /// Values above 100 are treated as 100.
+    pub const fn with_jitter(mut self, percent: u8) -> Self {
+        self.jitter = Some(percent);
+        self
+    }
+
+    /// Sets a token that aborts the wait when cancelled, e.g. when the benchmark is torn down.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Sets the number of times a call that failed with a transient transport error is re-sent in
+    /// a row.
+    pub const fn with_transport_retries(mut self, transport_retries: u32) -> Self {
+        self.transport_retries = transport_retries;
+        self
+    }
+
+    /// Sets the duration after which a single call that did not respond fails.
+    pub const fn with_call_timeout(mut self, call_timeout: Duration) -> Self {
+        self.call_timeout = Some(call_timeout);
+        self
+    }
+
+    /// Disables the per-call timeout, so a call waits for the engine to respond indefinitely.
+    pub const fn without_call_timeout(mut self) -> Self {
+        self.call_timeout = None;
+        self
+    }
+
+    /// Sets the duration after which a `*_wait` call that did not settle fails, see
+    /// [`Self::per_block_deadline`].
+    pub const fn with_per_block_deadline(mut self, deadline: Duration) -> Self {
+        self.per_block_deadline = Some(deadline);
+        self
+    }
+
+    /// Sets the chain spec that payloads are checked against before they are sent.
+    pub fn with_chain_spec(mut self, chain_spec: Arc<ChainSpec>) -> Self {
+        self.chain_spec = Some(chain_spec);
+        self
+    }
+
+    /// Enables checking the structure of every payload against the given chain spec before it is
+    /// sent, see [`validate_payload_shape`].
+    pub fn with_payload_validation(mut self, chain_spec: Arc<ChainSpec>) -> Self {
+        self.chain_spec = Some(chain_spec);
+        self.validate_payloads = true;
+        self
+    }
+
+    /// Activates the given hardfork at the given timestamp when selecting the engine API version
+    /// of a payload, regardless of the chain spec, see [`Self::fork_overrides`].
+    pub fn with_fork_override(mut self, fork: Hardfork, timestamp: u64) -> Self {
+        self.fork_overrides.insert(fork, timestamp);
+        self
+    }
+
+    /// Returns the engine API version to send a payload with the given timestamp with, selected
+    /// from the fork schedule of [`Self::chain_spec`] and [`Self::fork_overrides`], or `None` if
+    /// the chain spec is not set.
+    pub fn engine_version(&self, timestamp: u64) -> Option<EngineApiMessageVersion> {
+        self.chain_spec.as_ref().map(|chain_spec| {
+            EngineApiMessageVersion::for_timestamp_with_overrides(
+                chain_spec,
+                &self.fork_overrides,
+                timestamp,
+            )
+        })
+    }
+
+    /// Makes the `*_wait` methods return INVALID responses instead of failing with
+    /// [`EngineWaitError::Invalid`].
+    pub const fn with_accept_invalid(mut self) -> Self {
+        self.accept_invalid = true;
+        self
+    }
+
+    /// Sets the interval at which the progress of a long wait is logged.
+    pub const fn with_progress_interval(mut self, progress_interval: Duration) -> Self {
+        self.progress_interval = Some(progress_interval);
+        self
+    }
+
+    /// Disables logging the progress of long waits.
+    pub const fn without_progress_interval(mut self) -> Self {
+        self.progress_interval = None;
+        self
+    }
+
+    /// Sets the handler that is invoked with every INVALID response, see [`Self::on_invalid`].
+    pub fn with_on_invalid(
+        mut self,
+        on_invalid: impl Fn(&'static str, &PayloadStatus, &RawValue) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_invalid = Some(InvalidHandler(Arc::new(on_invalid)));
+        self
+    }
+
+    /// Enables checking the latest valid hash of every VALID `engine_newPayload` response, see
+    /// [`Self::verify_hashes`].
+    pub const fn with_verify_hashes(mut self) -> Self {
+        self.verify_hashes = true;
+        self
+    }
+
+    /// Enables re-sending V4 payloads with `engine_newPayloadV3` if the engine does not support
+    /// `engine_newPayloadV4`, see [`Self::version_fallback`].
+    pub const fn with_version_fallback(mut self) -> Self {
+        self.version_fallback = true;
+        self
+    }
+
+    /// Returns whether the progress of a wait should be logged, if the last progress was logged
+    /// `since_last` ago.
+    fn progress_due(&self, since_last: Duration) -> bool {
+        self.progress_interval.is_some_and(|interval| since_last >= interval)
+    }
+
+    /// Returns the validation error of the given status if it is INVALID and INVALID responses are
+    /// not accepted, see [`Self::accept_invalid`].
+    fn rejection<'a>(&self, status: &'a PayloadStatusEnum) -> Option<&'a str> {
+        match status {
+            PayloadStatusEnum::Invalid { validation_error } if !self.accept_invalid => {
+                Some(validation_error)
+            }
+            _ => None,
+        }
+    }
+
+    /// Invokes the [`Self::on_invalid`] handler with the given INVALID response of a call with the
+    /// given parameters, and returns the [`EngineWaitError::Invalid`] error of the call.
+    fn reject(
+        &self,
+        method: &'static str,
+        status: PayloadStatus,
+        params: &SerializedParams,
+    ) -> EngineWaitError {
+        if let Some(InvalidHandler(on_invalid)) = &self.on_invalid {
+            on_invalid(method, &status, &params.0);
+        }
+        EngineWaitError::invalid(method, status)
+    }
+
+    /// Checks that the latest valid hash of the given VALID response is the hash of the payload, if
+    /// enabled through [`Self::verify_hashes`].
+    pub(crate) fn verify_hash(
+        &self,
+        method: &'static str,
+        block_number: u64,
+        expected: B256,
+        status: &PayloadStatus,
+    ) -> Result<(), EngineWaitError> {
+        if self.verify_hashes &&
+            status.status.is_valid() &&
+            status.latest_valid_hash != Some(expected)
+        {
+            error!(method, block_number, %expected, ?status, "Engine accepted a different block hash");
+            return Err(EngineWaitError::HashMismatch {
+                method,
+                block_number,
+                expected,
+                actual: status.latest_valid_hash,
+            })
+        }
+        Ok(())
+    }
+
+    /// Runs [`validate_payload_shape`] on the payload returned by `payload`, if enabled through
+    /// [`Self::validate_payloads`].
+    fn validate_payload(
+        &self,
+        payload: impl FnOnce() -> ExecutionPayload,
+        parent_beacon_block_root: Option<B256>,
+    ) -> Result<(), EngineWaitError> {
+        match &self.chain_spec {
+            Some(chain_spec) if self.validate_payloads => {
+                validate_payload_shape(&payload(), parent_beacon_block_root, chain_spec)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs `fut` to completion, unless the cancellation token is cancelled first.
+    async fn cancellable<F: Future>(
+        &self,
+        method: &'static str,
+        fut: F,
+    ) -> Result<F::Output, EngineWaitError> {
+        let Some(cancel) = &self.cancel else { return Ok(fut.await) };
+        tokio::select! {
+            biased;
+            _ = cancel.cancelled() => Err(EngineWaitError::Cancelled { method }),
+            output = fut => Ok(output),
+        }
+    }
+
+    /// Runs `fut` to completion, or fails with [`CallTimeoutError`] if it does not complete within
+    /// [`Self::call_timeout`].
+    async fn timed<R, F>(&self, method: &'static str, fut: F) -> TransportResult<R>
+    where
+        F: Future<Output = TransportResult<R>>,
+    {
+        let Some(timeout) = self.call_timeout else { return fut.await };
+        tokio::time::timeout(timeout, fut).await.unwrap_or_else(|_| {
+            Err(TransportErrorKind::custom(CallTimeoutError { method, timeout }))
+        })
+    }
+
+    /// Sends `call`, and re-sends it up to [`Self::transport_retries`] times in a row if it fails
+    /// with a transient transport error or does not respond within [`Self::call_timeout`].
+    async fn send<R, F, Fut>(
+        &self,
+        method: &'static str,
+        call: &mut F,
+    ) -> Result<R, EngineWaitError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = TransportResult<R>>,
+    {
+        let mut retries = 0;
+        loop {
+            match self.cancellable(method, self.timed(method, call())).await? {
+                Ok(response) => return Ok(response),
+                Err(err) if retries < self.transport_retries && is_retryable(&err) => {
+                    retries += 1;
+                    warn!(method, %err, retries, "Transient transport error, retrying");
+                    self.sleep(method, self.poll_interval).await?;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// Waits for the given interval with jitter applied, unless the cancellation token is
+    /// cancelled first.
+    async fn sleep(&self, method: &'static str, interval: Duration) -> Result<(), EngineWaitError> {
+        let interval = self.jittered(interval);
+        if !interval.is_zero() {
+            self.cancellable(method, tokio::time::sleep(interval)).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns the given interval, randomly lengthened or shortened by up to [`Self::jitter`]
+    /// percent.
+    fn jittered(&self, interval: Duration) -> Duration {
+        match self.jitter {
+            Some(percent) if percent > 0 && !interval.is_zero() => {
+                let percent = i32::from(percent.min(100));
+                let offset = rand::thread_rng().gen_range(-percent..=percent);
+                interval.mul_f64(f64::from(100 + offset) / 100.0)
+            }
+            _ => interval,
+        }
+    }
+
+    /// Returns the interval to wait after an attempt that waited `interval`.
+    fn next_interval(&self, interval: Duration) -> Duration {
+        match self.max_backoff {
+            Some(max_backoff) => interval.saturating_mul(2).min(max_backoff),
+            None => interval,
+        }
+    }
+}
+
+/// The default [`WaitConfig::call_timeout`].
+///
+/// This is deliberately generous: a single call only times out if the engine did not respond for
+/// two minutes, which is far longer than the engine takes to execute even the largest mainnet
+/// blocks.
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// The default [`WaitConfig::progress_interval`].
+pub const DEFAULT_PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The deadline of [`EngineApiGetPayloadExt`] if the config has no
+/// [`WaitConfig::per_block_deadline`].
+///
+/// This is one mainnet slot, which is more than enough for the engine to build a payload with
+/// transactions if there are any pending.
+pub const DEFAULT_BUILD_DEADLINE: Duration = Duration::from_secs(12);
+
+/// The error of a single engine API call that did not respond within
+/// [`WaitConfig::call_timeout`].
+///
+/// It is returned as a [`TransportErrorKind::Custom`] error, so [`is_retryable`] treats it as
+/// transient.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("{method} did not respond within {timeout:?}")]
+pub struct CallTimeoutError {
+    /// The engine API method that was called.
+    pub method: &'static str,
+    /// The timeout that elapsed.
+    pub timeout: Duration,
+}
+
+/// The error of [`EngineApiValidWaitExt::negotiate_capabilities`] if the engine does not support
+/// all required methods.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("engine does not support the required methods: {}", missing.join(", "))]
+pub struct MissingCapabilitiesError {
+    /// The required methods that the engine did not return, in the order they were required.
+    pub missing: Vec<String>,
+}
+
+/// Returns `true` if the call that failed with the given error may succeed when re-sent.
+///
+/// This is the case for HTTP 429 and 5xx responses, missing batch responses and calls that timed
+/// out with [`CallTimeoutError`]. Other errors of the underlying client, like a failed JWT
+/// authentication or connection setup, JSON-RPC error responses and (de)serialization errors are
+/// fatal.
+pub fn is_retryable(err: &TransportError) -> bool {
+    match err {
+        RpcError::Transport(TransportErrorKind::HttpError(err)) => {
+            err.status == 429 || err.status >= 500
+        }
+        RpcError::Transport(TransportErrorKind::MissingBatchResponse(_)) => true,
+        RpcError::Transport(TransportErrorKind::Custom(err)) => err.is::<CallTimeoutError>(),
+        _ => false,
+    }
+}
+
+/// Errors returned by the methods of [`EngineApiValidWaitExt`].
+#[derive(Debug, thiserror::Error)]
+pub enum EngineWaitError {
+    /// The engine responded with INVALID.
+    #[error("{method} returned INVALID: {}", validation_error.as_deref().unwrap_or("no reason given"))]
+    Invalid {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The status returned by the engine.
+        status: PayloadStatus,
+        /// The validation error returned by the engine, if any.
+        validation_error: Option<String>,
+    },
+    /// The engine did not respond with VALID within [`WaitConfig::max_attempts`] calls, or
+    /// before the [`WaitConfig::per_block_deadline`].
+    #[error(
+        "{method} did not return VALID after {attempts} attempts, last status: {}",
+        status.as_ref().map_or_else(|| "none".to_string(), ToString::to_string)
+    )]
+    Timeout {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The number of calls that were sent.
+        attempts: u64,
+        /// The last status returned by the engine, or `None` if the deadline passed before the
+        /// engine responded to the first call.
+        status: Option<PayloadStatus>,
+    },
+    /// The engine returned VALID for a forkchoice update, but no payload id, so it did not start
+    /// building a payload.
+    ///
+    /// If payload attributes were given, the engine did not accept the build request, e.g.
+    /// because the attributes do not match the fork that is active at their timestamp, or the
+    /// head is not the block the attributes build on.
+    #[error("{method} did not return a payload id, status: {}", response.payload_status)]
+    MissingPayloadId {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The response of the engine, without a payload id.
+        response: ForkchoiceUpdated,
+    },
+    /// The engine did not know the payload within [`WaitConfig::max_attempts`] calls, or before
+    /// the [`WaitConfig::per_block_deadline`], see [`EngineApiGetPayloadExt`].
+    #[error("{method} did not return payload {payload_id} after {attempts} attempts")]
+    PayloadUnavailable {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The id of the requested payload.
+        payload_id: PayloadId,
+        /// The number of calls that were sent.
+        attempts: u64,
+    },
+    /// The engine only returned empty payloads within [`WaitConfig::max_attempts`] calls, or
+    /// before the deadline, see [`EngineApiGetPayloadExt`].
+    #[error("{method} only returned empty payloads for {payload_id} after {attempts} attempts")]
+    EmptyPayload {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The id of the requested payload.
+        payload_id: PayloadId,
+        /// The number of calls that were sent.
+        attempts: u64,
+    },
+    /// A V3 or V4 payload was given without the parent beacon block root and versioned hashes.
+    #[error("{method} requires the parent beacon block root and versioned hashes")]
+    MissingCancunFields {
+        /// The engine API method that was called.
+        method: &'static str,
+    },
+    /// The [`ExecutionPayloadSidecar`] contains a kind of request that the payload cannot carry.
+    #[error("{method} does not support {kind} requests")]
+    UnsupportedRequest {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The kind of the request.
+        kind: &'static str,
+    },
+    /// The engine returned a different number of blobs than were requested.
+    #[error("{method} returned {returned} blobs for {requested} versioned hashes")]
+    BlobCountMismatch {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The number of requested versioned hashes.
+        requested: usize,
+        /// The number of returned blobs.
+        returned: usize,
+    },
+    /// The presence of withdrawals in a payload does not match the activation of Shanghai at the
+    /// payload's timestamp.
+    #[error(
+        "{method} payload with timestamp {timestamp} must {}contain withdrawals",
+        if *shanghai_active { "" } else { "not " }
+    )]
+    WithdrawalsMismatch {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The timestamp of the payload.
+        timestamp: u64,
+        /// Whether Shanghai is active at the timestamp.
+        shanghai_active: bool,
+    },
+    /// A payload contains execution layer requests, although Prague is not active at the payload's
+    /// timestamp.
+    #[error("{method} payload with timestamp {timestamp} must not contain requests before Prague")]
+    RequestsBeforePrague {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The timestamp of the payload.
+        timestamp: u64,
+    },
+    /// The execution layer requests of a payload are not ordered by their type, see
+    /// [`validate_execution_requests`].
+    #[error(
+        "{method} request {index} has type {request_type}, after a request of type {previous_type}"
+    )]
+    UnorderedRequests {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The index of the out of order request.
+        index: usize,
+        /// The type of the out of order request.
+        request_type: u8,
+        /// The type of the request before it.
+        previous_type: u8,
+    },
+    /// The parent beacon block root of a payload is zero, although Cancun is active at the
+    /// payload's timestamp.
+    #[error(
+        "{method} payload with timestamp {timestamp} must have a non-zero parent beacon block root"
+    )]
+    ZeroParentBeaconBlockRoot {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The timestamp of the payload.
+        timestamp: u64,
+    },
+    /// The block is from before the merge, which the engine API cannot import, see
+    /// [`validate_post_merge`].
+    #[error(
+        "block {block_number} is from before the merge, pre-merge blocks cannot be imported via \
+         engine API"
+    )]
+    PreMergeBlock {
+        /// The number of the block.
+        block_number: u64,
+    },
+    /// The payload failed the checks of [`validate_payload_shape`].
+    #[error("{method} payload is malformed: {error}")]
+    MalformedPayload {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The reason the payload is malformed.
+        error: PayloadError,
+    },
+    /// A transaction of the payload could not be decoded to derive the versioned hashes.
+    #[error("{method} payload transaction {index} could not be decoded: {error}")]
+    InvalidTransaction {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The index of the transaction in the payload.
+        index: usize,
+        /// The decoding error.
+        error: alloy_rlp::Error,
+    },
+    /// The engine responded with VALID, but without a latest valid hash.
+    #[error("{method} returned VALID without a latest valid hash")]
+    MissingLatestValidHash {
+        /// The engine API method that was called.
+        method: &'static str,
+    },
+    /// The engine returned VALID with a latest valid hash that is not the hash of the payload, see
+    /// [`WaitConfig::verify_hashes`].
+    #[error(
+        "{method} returned latest valid hash {} for block {block_number}, expected {expected}",
+        actual.map_or_else(|| "none".to_string(), |hash| hash.to_string())
+    )]
+    HashMismatch {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The number of the block.
+        block_number: u64,
+        /// The hash of the payload.
+        expected: B256,
+        /// The latest valid hash returned by the engine.
+        actual: Option<B256>,
+    },
+    /// The wait was cancelled through [`WaitConfig::cancel`].
+    #[error("{method} was cancelled")]
+    Cancelled {
+        /// The engine API method that was called.
+        method: &'static str,
+    },
+    /// The engine does not support the method of the payload, and the payload cannot be sent with
+    /// an earlier version, see [`WaitConfig::version_fallback`].
+    #[error(
+        "engine does not support {method}, and block {block_number} cannot be sent with an \
+         earlier version because {reason}"
+    )]
+    UnsupportedVersion {
+        /// The engine API method that was called.
+        method: &'static str,
+        /// The number of the block.
+        block_number: u64,
+        /// Why the payload cannot be sent with an earlier version.
+        reason: &'static str,
+    },
+    /// The call failed on the transport level.
+    #[error(transparent)]
+    Transport(#[from] TransportError),
+}
+
+impl EngineWaitError {
+    /// Returns `true` if the engine responded with a method not found error, i.e. it does not
+    /// support the called method.
+    pub fn is_method_not_found(&self) -> bool {
+        matches!(
+            self,
+            Self::Transport(err)
+                if err.as_error_resp().is_some_and(|err| err.code == METHOD_NOT_FOUND_CODE)
+        )
+    }
+
+    fn invalid(method: &'static str, status: PayloadStatus) -> Self {
+        let validation_error = status.status.validation_error().map(ToString::to_string);
+        Self::Invalid { method, status, validation_error }
+    }
+
+    /// Panics if this is an [`EngineWaitError::Invalid`], which is what the `*_wait_or_panic`
+    /// methods do.
+    fn panic_if_invalid(self) -> Self {
+        if let Self::Invalid { .. } = self {
+            panic!("{self}");
+        }
+        self
+    }
+}
+
+/// Checks that the given payload contains withdrawals if and only if Shanghai is active at its
+/// timestamp, so a malformed payload fails before it is sent to the engine.
+pub fn validate_withdrawals(
+    chain_spec: &ChainSpec,
+    payload: &ExecutionPayloadInputV2,
+) -> Result<(), EngineWaitError> {
+    let timestamp = payload.execution_payload.timestamp;
+    let shanghai_active = chain_spec.is_shanghai_active_at_timestamp(timestamp);
+    if payload.withdrawals.is_some() != shanghai_active {
+        return Err(EngineWaitError::WithdrawalsMismatch {
+            method: "engine_newPayloadV2",
+            timestamp,
+            shanghai_active,
+        })
+    }
+    Ok(())
+}
+
+/// Checks the structure of the given payload before it is sent to the engine, without executing
+/// it:
+///
+/// - The payload contains withdrawals if and only if Shanghai is active at its timestamp.
+/// - The payload contains the blob gas fields and a parent beacon block root if and only if Cancun
+///   is active at its timestamp. Note that V1 and V2 payloads never contain the blob gas fields.
+/// - The block hash of the payload matches the hash of the block it describes.
+///
+/// This catches payloads that were assembled or converted incorrectly, it does not check the
+/// transactions or the state root.
+pub fn validate_payload_shape(
+    payload: &ExecutionPayload,
+    parent_beacon_block_root: Option<B256>,
+    chain_spec: &ChainSpec,
+) -> Result<(), EngineWaitError> {
+    let method = match payload {
+        ExecutionPayload::V1(_) => "engine_newPayloadV1",
+        ExecutionPayload::V2(_) => "engine_newPayloadV2",
+        ExecutionPayload::V3(_) => "engine_newPayloadV3",
+        ExecutionPayload::V4(_) => "engine_newPayloadV4",
+    };
+    let malformed = |error| EngineWaitError::MalformedPayload { method, error };
+
+    let timestamp = payload.timestamp();
+    match (payload.withdrawals().is_some(), chain_spec.is_shanghai_active_at_timestamp(timestamp)) {
+        (true, false) => return Err(malformed(PayloadError::PreShanghaiBlockWithWitdrawals)),
+        (false, true) => return Err(malformed(PayloadError::PostShanghaiBlockWithoutWitdrawals)),
+        _ => {}
+    }
+
+    let has_blob_gas = matches!(payload, ExecutionPayload::V3(_) | ExecutionPayload::V4(_));
+    match (has_blob_gas, chain_spec.is_cancun_active_at_timestamp(timestamp)) {
+        (true, false) => return Err(malformed(PayloadError::PreCancunBlockWithBlobGasUsed)),
+        (false, true) => return Err(malformed(PayloadError::PostCancunBlockWithoutBlobGasUsed)),
+        _ => {}
+    }
+    if parent_beacon_block_root.is_some() != has_blob_gas {
+        return Err(malformed(if has_blob_gas {
+            PayloadError::PostCancunWithoutCancunFields
+        } else {
+            PayloadError::PreCancunWithCancunFields
+        }))
+    }
+
+    try_into_sealed_block(payload.clone(), parent_beacon_block_root).map_err(malformed)?;
+    Ok(())
+}
+
+/// Checks that the given parent beacon block root of a V3 payload is not zero if Cancun is active
+/// at the payload's timestamp.
+///
+/// A zero root usually means the root was missing from the source block, see
+/// [`block_to_execution_payload_v3`](crate::replay::block_to_execution_payload_v3). The engine
+/// would reject such a payload with a block hash mismatch, which is harder to diagnose.
+pub fn validate_parent_beacon_block_root(
+    chain_spec: &ChainSpec,
+    payload: &ExecutionPayloadV3,
+    parent_beacon_block_root: B256,
+) -> Result<(), EngineWaitError> {
+    let timestamp = payload.payload_inner.payload_inner.timestamp;
+    if parent_beacon_block_root.is_zero() && chain_spec.is_cancun_active_at_timestamp(timestamp) {
+        return Err(EngineWaitError::ZeroParentBeaconBlockRoot {
+            method: "engine_newPayloadV3",
+            timestamp,
+        })
+    }
+    Ok(())
+}
+
+/// Checks the execution layer requests of a V4 payload with the given timestamp before it is sent
+/// to the engine:
+///
+/// - Before Prague the payload must not contain any requests.
+/// - The requests are ordered by their [EIP-7685] type, i.e. deposits before withdrawals before
+///   consolidations.
+///
+/// A payload after Prague without any requests is valid, since most blocks contain none.
+///
+/// [EIP-7685]: https://eips.ethereum.org/EIPS/eip-7685
+pub fn validate_execution_requests(
+    requests: &[Request],
+    chain_spec: &ChainSpec,
+    timestamp: u64,
+) -> Result<(), EngineWaitError> {
+    let method = "engine_newPayloadV4";
+    if !requests.is_empty() && !chain_spec.is_prague_active_at_timestamp(timestamp) {
+        return Err(EngineWaitError::RequestsBeforePrague { method, timestamp })
+    }
+    for (index, pair) in requests.windows(2).enumerate() {
+        let (previous_type, request_type) = (pair[0].request_type(), pair[1].request_type());
+        if request_type < previous_type {
+            return Err(EngineWaitError::UnorderedRequests {
+                method,
+                index: index + 1,
+                request_type,
+                previous_type,
+            })
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the block with the given number is from after the merge, as pre-merge blocks are
+/// proof-of-work blocks that can only be imported by syncing, not via the engine API.
+///
+/// Blocks are assumed to be post-merge if the chain spec does not know the Paris block, e.g. for
+/// chains that merged by total difficulty only, unless the chain requires the DAO fork, see
+/// [`ChainSpec::requires_dao_hardfork`], and the block is not after it: the irregular state change
+/// of the DAO fork is only applied by proof-of-work blocks.
+pub fn validate_post_merge(
+    chain_spec: &ChainSpec,
+    block_number: u64,
+) -> Result<(), EngineWaitError> {
+    let pre_merge = match chain_spec.is_paris_active_at_block(block_number) {
+        Some(paris_active) => !paris_active,
+        None => match chain_spec.fork(Hardfork::Dao) {
+            ForkCondition::Block(dao_block) => {
+                chain_spec.requires_dao_hardfork() && block_number <= dao_block
+            }
+            _ => false,
+        },
+    };
+    if pre_merge {
+        return Err(EngineWaitError::PreMergeBlock { block_number })
+    }
+    Ok(())
+}
+
+/// Returns the execution layer requests carried by the given payload, deposits first.
+fn payload_requests(payload: &ExecutionPayloadV4) -> Vec<Request> {
+    let deposits = payload.deposit_requests.iter().copied().map(Request::DepositRequest);
+    let withdrawals = payload.withdrawal_requests.iter().copied().map(Request::WithdrawalRequest);
+    deposits.chain(withdrawals).collect()
+}
+
+/// Returns the payload to send with `engine_newPayloadV3` in place of the given V4 payload, or the
+/// reason why the payload cannot be sent as V3, see [`WaitConfig::version_fallback`].
+///
+/// The payload can only be downgraded if it has no requests, which V3 cannot carry, and the chain
+/// spec, if any, does not activate Prague at its timestamp.
+fn downgrade_payload_v4(
+    payload: &ExecutionPayloadV4,
+    chain_spec: Option<&ChainSpec>,
+) -> Result<ExecutionPayloadV3, &'static str> {
+    if !payload.deposit_requests.is_empty() || !payload.withdrawal_requests.is_empty() {
+        return Err("it contains requests")
+    }
+    if chain_spec
+        .is_some_and(|chain_spec| chain_spec.is_prague_active_at_timestamp(payload.timestamp()))
+    {
+        return Err("it is after Prague")
+    }
+    Ok(payload.payload_inner.clone())
+}
+
+/// Returns the versioned hashes of all blob transactions in the given payload, in order, as
+/// expected by `engine_newPayloadV3`.
+pub fn payload_versioned_hashes(
+    payload: &ExecutionPayloadV1,
+) -> Result<Vec<B256>, EngineWaitError> {
+    let mut versioned_hashes = Vec::new();
+    for (index, tx) in payload.transactions.iter().enumerate() {
+        let tx = TransactionSigned::decode_enveloped(&mut tx.as_ref()).map_err(|error| {
+            EngineWaitError::InvalidTransaction { method: "engine_newPayloadV3", index, error }
+        })?;
+        versioned_hashes.extend(tx.blob_versioned_hashes().unwrap_or_default());
+    }
+    Ok(versioned_hashes)
+}
+
+/// Returns the blob gas used by the given payload, which is zero for payloads before
+/// `ExecutionPayloadV3`, i.e. before Cancun, or Ecotone on OP stack chains.
+pub const fn payload_blob_gas_used(payload: &ExecutionPayload) -> u64 {
+    match payload {
+        ExecutionPayload::V1(_) | ExecutionPayload::V2(_) => 0,
+        ExecutionPayload::V3(payload) => payload.blob_gas_used,
+        ExecutionPayload::V4(payload) => payload.payload_inner.blob_gas_used,
+    }
+}
+
+/// Returns the latest valid hash of the given status, which is the hash of the imported block if
+/// a `*_wait` call returned VALID, or [`EngineWaitError::MissingLatestValidHash`] if the engine did
+/// not return it.
+///
+/// The given method is only used for the error.
+pub fn latest_valid_hash(
+    method: &'static str,
+    status: &PayloadStatus,
+) -> Result<B256, EngineWaitError> {
+    status.latest_valid_hash.ok_or(EngineWaitError::MissingLatestValidHash { method })
+}
+
+/// Timings of a `*_wait` call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WaitMetrics {
+    /// The time from sending the first call until its response arrived.
+    pub first_response: Duration,
+    /// The time from sending the first call until the VALID (or INVALID) response arrived.
+    pub valid_after: Duration,
+    /// The number of calls that were sent.
+    pub poll_count: usize,
+}
+
+/// Engine API responses that carry a [`PayloadStatus`].
+trait EngineResponse {
+    fn payload_status(&self) -> &PayloadStatus;
+}
+
+impl EngineResponse for PayloadStatus {
+    fn payload_status(&self) -> &PayloadStatus {
+        self
+    }
+}
+
+impl EngineResponse for ForkchoiceUpdated {
+    fn payload_status(&self) -> &PayloadStatus {
+        &self.payload_status
+    }
+}
+
+/// Sends `call` until the returned status is either VALID or INVALID, according to the given
+/// [`WaitConfig`].
+///
+/// `SYNCING` and `ACCEPTED` responses are retried, the first `ACCEPTED` response is logged as a
+/// warning because it means the engine did not execute the payload. Transient transport errors are
+/// retried according to [`WaitConfig::transport_retries`].
+///
+/// The number of calls that were sent is recorded as the `poll_count` field of the current span.
+/// While the engine has not settled, the status is logged every
+/// [`WaitConfig::progress_interval`]. The whole wait is bounded by
+/// [`WaitConfig::per_block_deadline`].
+async fn poll_until_settled<R, F, Fut>(
+    method: &'static str,
+    config: &WaitConfig,
+    call: F,
+) -> Result<(R, WaitMetrics), EngineWaitError>
+where
+    R: EngineResponse,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = TransportResult<R>>,
+{
+    let mut attempts = 1;
+    let mut last_status = None;
+    let wait = poll_attempts(method, config, call, &mut attempts, &mut last_status);
+    let Some(deadline) = config.per_block_deadline else { return wait.await };
+    match tokio::time::timeout(deadline, wait).await {
+        Ok(res) => res,
+        Err(_) => {
+            Span::current().record("poll_count", attempts);
+            warn!(method, attempts, ?deadline, "Engine did not settle before the deadline");
+            Err(EngineWaitError::Timeout { method, attempts, status: last_status })
+        }
+    }
+}
+
+/// The polling loop of [`poll_until_settled`], which keeps the number of calls sent so far and
+/// the last status in `attempts` and `last_status`, so they can be reported if the deadline
+/// passes.
+async fn poll_attempts<R, F, Fut>(
+    method: &'static str,
+    config: &WaitConfig,
+    mut call: F,
+    attempts: &mut u64,
+    last_status: &mut Option<PayloadStatus>,
+) -> Result<(R, WaitMetrics), EngineWaitError>
+where
+    R: EngineResponse,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = TransportResult<R>>,
+{
+    let start = Instant::now();
+    let mut interval = config.poll_interval;
+    let mut warned_accepted = false;
+    let mut last_progress = start;
+    let mut response = config.send(method, &mut call).await?;
+    let first_response = start.elapsed();
+    loop {
+        let status = response.payload_status();
+        *last_status = Some(status.clone());
+        match status.status {
+            PayloadStatusEnum::Valid | PayloadStatusEnum::Invalid { .. } => {
+                Span::current().record("poll_count", *attempts);
+                let metrics = WaitMetrics {
+                    first_response,
+                    valid_after: start.elapsed(),
+                    poll_count: *attempts as usize,
+                };
+                return Ok((response, metrics))
+            }
+            PayloadStatusEnum::Accepted if !warned_accepted => {
+                warn!(method, ?status, "Engine accepted but did not execute the payload, polling");
+                warned_accepted = true;
+            }
+            PayloadStatusEnum::Accepted | PayloadStatusEnum::Syncing => {}
+        }
+        if config.max_attempts.is_some_and(|max_attempts| *attempts >= max_attempts) {
+            Span::current().record("poll_count", *attempts);
+            let status = Some(status.clone());
+            return Err(EngineWaitError::Timeout { method, attempts: *attempts, status })
+        }
+        if config.progress_due(last_progress.elapsed()) {
+            info!(
+                method,
+                status = %status.status,
+                attempts = *attempts,
+                elapsed = ?start.elapsed(),
+                "Still waiting for the engine to settle",
+            );
+            last_progress = Instant::now();
+        }
+
+        config.sleep(method, interval).await?;
+        interval = config.next_interval(interval);
+        *attempts += 1;
+        response = config.send(method, &mut call).await?;
+    }
+}
+
+/// A handler for INVALID responses, see [`WaitConfig::on_invalid`].
+///
+/// It is called with the engine API method, the INVALID status, and the serialized parameters of
+/// the call, which contain the rejected payload.
+#[derive(Clone)]
+pub struct InvalidHandler(Arc<InvalidHandlerFn>);
+
+type InvalidHandlerFn = dyn Fn(&'static str, &PayloadStatus, &RawValue) + Send + Sync;
+
+impl fmt::Debug for InvalidHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InvalidHandler").finish_non_exhaustive()
+    }
+}
+
+/// Engine API call parameters, serialized once and shared between all attempts of a `*_wait` call,
+/// so re-sending a call does not have to clone the payload.
+#[derive(Debug, Clone)]
+struct SerializedParams(Arc<RawValue>);
+
+impl SerializedParams {
+    fn new(params: impl Serialize) -> TransportResult<Self> {
+        let raw = serde_json::value::to_raw_value(&params).map_err(TransportError::ser_err)?;
+        Ok(Self(raw.into()))
+    }
+}
+
+impl Serialize for SerializedParams {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+/// The version specific data that is sent to `engine_newPayload` alongside an
+/// [`ExecutionPayload`].
+///
+/// V1 and V2 payloads need no sidecar, V3 payloads need the Cancun fields, and V4 payloads
+/// additionally carry the EIP-7685 requests of the block.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionPayloadSidecar {
+    cancun: MaybeCancunPayloadFields,
+    requests: Option<Vec<Request>>,
+}
+
+impl ExecutionPayloadSidecar {
+    /// Creates an empty sidecar, for V1 and V2 payloads.
+    pub const fn none() -> Self {
+        Self { cancun: MaybeCancunPayloadFields::none(), requests: None }
+    }
+
+    /// Creates a sidecar for a V3 payload.
+    pub fn v3(versioned_hashes: Vec<B256>, parent_beacon_block_root: B256) -> Self {
+        let cancun = CancunPayloadFields { parent_beacon_block_root, versioned_hashes };
+        Self { cancun: cancun.into(), requests: None }
+    }
+
+    /// Creates a sidecar for a V4 payload.
+    ///
+    /// The requests replace the deposit and withdrawal requests of the payload when it is sent.
+    pub fn v4(
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+        requests: Vec<Request>,
+    ) -> Self {
+        Self { requests: Some(requests), ..Self::v3(versioned_hashes, parent_beacon_block_root) }
+    }
+
+    /// Returns the Cancun fields, if set.
+    pub const fn cancun(&self) -> Option<&CancunPayloadFields> {
+        self.cancun.as_ref()
+    }
+
+    /// Returns the requests, if set.
+    pub fn requests(&self) -> Option<&[Request]> {
+        self.requests.as_deref()
+    }
+
+    /// Assembles the sidecar of the given block, according to the fork that is active at the
+    /// timestamp of the block.
+    ///
+    /// Before Cancun the sidecar is empty. After Cancun it holds the versioned hashes of the blob
+    /// transactions and the parent beacon block root of the header, and after Prague additionally
+    /// the requests of the block, which are empty if the block has none.
+    ///
+    /// Returns [`EngineWaitError::MissingCancunFields`] if the header of a block after Cancun has
+    /// no parent beacon block root.
+    pub fn from_block(
+        block: &SealedBlock,
+        chain_spec: &ChainSpec,
+    ) -> Result<Self, EngineWaitError> {
+        let timestamp = block.timestamp;
+        if !chain_spec.is_cancun_active_at_timestamp(timestamp) {
+            return Ok(Self::none())
+        }
+
+        let is_prague = chain_spec.is_prague_active_at_timestamp(timestamp);
+        let method = if is_prague { "engine_newPayloadV4" } else { "engine_newPayloadV3" };
+        let parent_beacon_block_root = block
+            .parent_beacon_block_root
+            .ok_or(EngineWaitError::MissingCancunFields { method })?;
+        let versioned_hashes = block.blob_versioned_hashes_iter().copied().collect();
+        if !is_prague {
+            return Ok(Self::v3(versioned_hashes, parent_beacon_block_root))
+        }
+
+        let requests = block.requests.clone().map(|requests| requests.0).unwrap_or_default();
+        Ok(Self::v4(versioned_hashes, parent_beacon_block_root, requests))
+    }
+
+    /// Returns the Cancun fields, or [`EngineWaitError::MissingCancunFields`] if they are not set.
+    fn into_cancun_fields(
+        self,
+        method: &'static str,
+    ) -> Result<CancunPayloadFields, EngineWaitError> {
+        self.cancun.into_inner().ok_or(EngineWaitError::MissingCancunFields { method })
+    }
+}
+
+impl From<MaybeCancunPayloadFields> for ExecutionPayloadSidecar {
+    fn from(cancun: MaybeCancunPayloadFields) -> Self {
+        Self { cancun, requests: None }
+    }
+}
+
+/// Replaces the deposit and withdrawal requests of the given payload with the given requests.
+fn set_payload_requests(
+    payload: &mut ExecutionPayloadV4,
+    requests: Vec<Request>,
+) -> Result<(), EngineWaitError> {
+    payload.deposit_requests.clear();
+    payload.withdrawal_requests.clear();
+    for request in requests {
+        match request {
+            Request::DepositRequest(request) => payload.deposit_requests.push(request),
+            Request::WithdrawalRequest(request) => payload.withdrawal_requests.push(request),
+            Request::ConsolidationRequest(_) => {
+                return Err(EngineWaitError::UnsupportedRequest {
+                    method: "engine_newPayloadV4",
+                    kind: "consolidation",
+                })
+            }
+            _ => {
+                return Err(EngineWaitError::UnsupportedRequest {
+                    method: "engine_newPayloadV4",
+                    kind: "unknown",
+                })
+            }
+        }
+    }
+    Ok(())
+}
 
 /// An extension trait for providers that implement the engine API, to wait for a VALID response.
+///
+/// Every `*_wait` method polls with the default [`WaitConfig`], the `*_wait_with` variants accept
+/// a custom one.
+///
+/// If the engine responds with INVALID, [`EngineWaitError::Invalid`] is returned so the caller can
+/// record the failure and continue, the `*_wait_or_panic` variants panic instead. The `*_wait_with`
+/// variants return the INVALID response instead if [`WaitConfig::accept_invalid`] is set.
 #[async_trait::async_trait]
 pub trait EngineApiValidWaitExt<N, T>: Send + Sync {
+    /// Warms up the connection to the engine, so the first timed call does not pay for connection
+    /// setup or authentication.
+    ///
+    /// This should be called before any `*_wait` method in a timed section. The default
+    /// implementation does nothing.
+    async fn prepare(&self) -> Result<(), EngineWaitError> {
+        Ok(())
+    }
+
+    /// Calls `engine_exchangeCapabilities` with the given methods, and fails with a
+    /// [`MissingCapabilitiesError`] that lists the methods the engine does not support.
+    ///
+    /// This should be called before a benchmark with all methods it is going to call, e.g.
+    /// `engine_newPayloadV4`, so an engine without them fails the setup with a clear error instead
+    /// of failing the first call of such a method mid-run. The error is returned as an
+    /// [`RpcError::LocalUsageError`], which [`is_retryable`] considers fatal.
+    async fn negotiate_capabilities(&self, required: &[&str]) -> TransportResult<()>;
+
+    /// Calls the `engine_newPayload` method matching the version of the given [ExecutionPayload],
+    /// and waits until the response is VALID.
+    ///
+    /// The Cancun fields of the [ExecutionPayloadSidecar] are required for V3 and V4 payloads, its
+    /// requests are only used for V4 payloads.
+    async fn new_payload_wait(
+        &self,
+        payload: ExecutionPayload,
+        sidecar: ExecutionPayloadSidecar,
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_wait_with(payload, sidecar, &WaitConfig::default())
+            .await
+            .map(|(status, _)| status)
+    }
+
+    /// Calls the `engine_newPayload` method matching the version of the given [ExecutionPayload],
+    /// and waits until the response is VALID, polling according to the given [WaitConfig].
+    ///
+    /// If [`WaitConfig::version_fallback`] is set and the engine does not support
+    /// `engine_newPayloadV4`, a V4 payload is re-sent with `engine_newPayloadV3` if it permits it,
+    /// and fails with [`EngineWaitError::UnsupportedVersion`] otherwise.
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn new_payload_wait_with(
+        &self,
+        payload: ExecutionPayload,
+        mut sidecar: ExecutionPayloadSidecar,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError> {
+        match payload {
+            ExecutionPayload::V4(mut payload) => {
+                if let Some(requests) = sidecar.requests.take() {
+                    if let Some(chain_spec) = &config.chain_spec {
+                        validate_execution_requests(&requests, chain_spec, payload.timestamp())?;
+                    }
+                    set_payload_requests(&mut payload, requests)?;
+                }
+                let CancunPayloadFields { parent_beacon_block_root, versioned_hashes } =
+                    sidecar.into_cancun_fields("engine_newPayloadV4")?;
+                let block_number = payload.payload_inner.payload_inner.payload_inner.block_number;
+                let fallback = config.version_fallback.then(|| {
+                    downgrade_payload_v4(&payload, config.chain_spec.as_deref())
+                        .map(|payload| (payload, versioned_hashes.clone()))
+                });
+                let res = self
+                    .new_payload_v4_wait_with(
+                        payload,
+                        versioned_hashes,
+                        parent_beacon_block_root,
+                        config,
+                    )
+                    .await;
+                match (res, fallback) {
+                    (Err(err), Some(fallback)) if err.is_method_not_found() => {
+                        let (payload, versioned_hashes) =
+                            fallback.map_err(|reason| EngineWaitError::UnsupportedVersion {
+                                method: "engine_newPayloadV4",
+                                block_number,
+                                reason,
+                            })?;
+                        warn!(
+                            block_number,
+                            "Engine does not support engine_newPayloadV4, falling back to \
+                             engine_newPayloadV3"
+                        );
+                        self.new_payload_v3_wait_with(
+                            payload,
+                            versioned_hashes,
+                            parent_beacon_block_root,
+                            config,
+                        )
+                        .await
+                    }
+                    (res, _) => res,
+                }
+            }
+            ExecutionPayload::V3(payload) => {
+                let CancunPayloadFields { parent_beacon_block_root, versioned_hashes } =
+                    sidecar.into_cancun_fields("engine_newPayloadV3")?;
+                self.new_payload_v3_wait_with(
+                    payload,
+                    versioned_hashes,
+                    parent_beacon_block_root,
+                    config,
+                )
+                .await
+            }
+            ExecutionPayload::V2(payload) => {
+                let input = ExecutionPayloadInputV2 {
+                    execution_payload: payload.payload_inner,
+                    withdrawals: Some(payload.withdrawals),
+                };
+                self.new_payload_v2_wait_with(input, config).await
+            }
+            ExecutionPayload::V1(payload) => self.new_payload_v1_wait_with(payload, config).await,
+        }
+    }
+
     /// Calls `engine_newPayloadV1` with the given [ExecutionPayloadV1], and waits until the
     /// response is VALID.
     async fn new_payload_v1_wait(
         &self,
         payload: ExecutionPayloadV1,
-    ) -> TransportResult<PayloadStatus>;
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v1_wait_with(payload, &WaitConfig::default())
+            .await
+            .map(|(status, _)| status)
+    }
+
+    /// Calls `engine_newPayloadV1` with the given [ExecutionPayloadV1], and waits until the
+    /// response is VALID, polling according to the given [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn new_payload_v1_wait_with(
+        &self,
+        payload: ExecutionPayloadV1,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError>;
+
+    /// Same as [`EngineApiValidWaitExt::new_payload_v1_wait`], but panics if the engine responds
+    /// with INVALID.
+    async fn new_payload_v1_wait_or_panic(
+        &self,
+        payload: ExecutionPayloadV1,
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v1_wait(payload).await.map_err(EngineWaitError::panic_if_invalid)
+    }
 
     /// Calls `engine_newPayloadV2` with the given [ExecutionPayloadInputV2], and waits until the
     /// response is VALID.
     async fn new_payload_v2_wait(
         &self,
         payload: ExecutionPayloadInputV2,
-    ) -> TransportResult<PayloadStatus>;
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v2_wait_with(payload, &WaitConfig::default())
+            .await
+            .map(|(status, _)| status)
+    }
+
+    /// Calls `engine_newPayloadV2` with the given [ExecutionPayloadInputV2], and waits until the
+    /// response is VALID, polling according to the given [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn new_payload_v2_wait_with(
+        &self,
+        payload: ExecutionPayloadInputV2,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError>;
+
+    /// Same as [`EngineApiValidWaitExt::new_payload_v2_wait`], but panics if the engine responds
+    /// with INVALID.
+    async fn new_payload_v2_wait_or_panic(
+        &self,
+        payload: ExecutionPayloadInputV2,
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v2_wait(payload).await.map_err(EngineWaitError::panic_if_invalid)
+    }
 
     /// Calls `engine_newPayloadV3` with the given [ExecutionPayloadV3], parent beacon block root,
     /// and versioned hashes, and waits until the response is VALID.
@@ -36,7 +1408,173 @@ pub trait EngineApiValidWaitExt<N, T>: Send + Sync {
         payload: ExecutionPayloadV3,
         versioned_hashes: Vec<B256>,
         parent_beacon_block_root: B256,
-    ) -> TransportResult<PayloadStatus>;
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v3_wait_with(
+            payload,
+            versioned_hashes,
+            parent_beacon_block_root,
+            &WaitConfig::default(),
+        )
+        .await
+        .map(|(status, _)| status)
+    }
+
+    /// Calls `engine_newPayloadV3` with the given [ExecutionPayloadV3], parent beacon block root,
+    /// and versioned hashes, and waits until the response is VALID, polling according to the
+    /// given [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn new_payload_v3_wait_with(
+        &self,
+        payload: ExecutionPayloadV3,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError>;
+
+    /// Same as [`EngineApiValidWaitExt::new_payload_v3_wait`], but panics if the engine responds
+    /// with INVALID.
+    async fn new_payload_v3_wait_or_panic(
+        &self,
+        payload: ExecutionPayloadV3,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v3_wait(payload, versioned_hashes, parent_beacon_block_root)
+            .await
+            .map_err(EngineWaitError::panic_if_invalid)
+    }
+
+    /// Calls `engine_newPayloadV3` with the given [ExecutionPayloadV3] and waits until the
+    /// response is VALID.
+    ///
+    /// The versioned hashes are derived from the blob transactions of the payload, see
+    /// [`payload_versioned_hashes`], and the parent beacon block root is taken from the header of
+    /// the block. Prefer [`EngineApiValidWaitExt::new_payload_v3_wait`] if the versioned hashes are
+    /// already known, which does not decode the transactions.
+    async fn new_payload_v3_wait_auto(
+        &self,
+        payload: ExecutionPayloadV3,
+        header: &Header,
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v3_wait_auto_with(payload, header, &WaitConfig::default())
+            .await
+            .map(|(status, _)| status)
+    }
+
+    /// Same as [`EngineApiValidWaitExt::new_payload_v3_wait_auto`], but polls according to the
+    /// given [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn new_payload_v3_wait_auto_with(
+        &self,
+        payload: ExecutionPayloadV3,
+        header: &Header,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError> {
+        let Some(parent_beacon_block_root) = header.parent_beacon_block_root else {
+            return Err(EngineWaitError::MissingCancunFields { method: "engine_newPayloadV3" })
+        };
+        let versioned_hashes = payload_versioned_hashes(&payload.payload_inner.payload_inner)?;
+        self.new_payload_v3_wait_with(payload, versioned_hashes, parent_beacon_block_root, config)
+            .await
+    }
+
+    /// Calls `engine_newPayloadV4` with the given [ExecutionPayloadV4], parent beacon block root,
+    /// and versioned hashes, and waits until the response is VALID.
+    ///
+    /// The execution layer requests (deposits, withdrawals, consolidations) are carried by the
+    /// [ExecutionPayloadV4] itself.
+    async fn new_payload_v4_wait(
+        &self,
+        payload: ExecutionPayloadV4,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v4_wait_with(
+            payload,
+            versioned_hashes,
+            parent_beacon_block_root,
+            &WaitConfig::default(),
+        )
+        .await
+        .map(|(status, _)| status)
+    }
+
+    /// Calls `engine_newPayloadV4` with the given [ExecutionPayloadV4], parent beacon block root,
+    /// and versioned hashes, and waits until the response is VALID, polling according to the
+    /// given [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn new_payload_v4_wait_with(
+        &self,
+        payload: ExecutionPayloadV4,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError>;
+
+    /// Same as [`EngineApiValidWaitExt::new_payload_v4_wait`], but panics if the engine responds
+    /// with INVALID.
+    async fn new_payload_v4_wait_or_panic(
+        &self,
+        payload: ExecutionPayloadV4,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+    ) -> Result<PayloadStatus, EngineWaitError> {
+        self.new_payload_v4_wait(payload, versioned_hashes, parent_beacon_block_root)
+            .await
+            .map_err(EngineWaitError::panic_if_invalid)
+    }
+
+    /// Calls the `engine_forkchoiceUpdated` method matching the given [EngineApiMessageVersion]
+    /// with the given [ForkchoiceState] and optional [PayloadAttributes], and waits until the
+    /// response is VALID.
+    async fn fork_choice_updated_wait(
+        &self,
+        message_version: EngineApiMessageVersion,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, EngineWaitError> {
+        self.fork_choice_updated_wait_with(
+            message_version,
+            fork_choice_state,
+            payload_attributes,
+            &WaitConfig::default(),
+        )
+        .await
+        .map(|(status, _)| status)
+    }
+
+    /// Calls the `engine_forkchoiceUpdated` method matching the given [EngineApiMessageVersion]
+    /// with the given [ForkchoiceState] and optional [PayloadAttributes], and waits until the
+    /// response is VALID, polling according to the given [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn fork_choice_updated_wait_with(
+        &self,
+        message_version: EngineApiMessageVersion,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError> {
+        match message_version {
+            // Prague does not introduce a new forkchoiceUpdated version, so V4 payloads are
+            // followed by `engine_forkchoiceUpdatedV3`
+            EngineApiMessageVersion::V3 | EngineApiMessageVersion::V4 => {
+                self.fork_choice_updated_v3_wait_with(fork_choice_state, payload_attributes, config)
+                    .await
+            }
+            EngineApiMessageVersion::V2 => {
+                self.fork_choice_updated_v2_wait_with(fork_choice_state, payload_attributes, config)
+                    .await
+            }
+            EngineApiMessageVersion::V1 => {
+                self.fork_choice_updated_v1_wait_with(fork_choice_state, payload_attributes, config)
+                    .await
+            }
+        }
+    }
 
     /// Calls `engine_forkChoiceUpdatedV1` with the given [ForkchoiceState] and optional
     /// [PayloadAttributes], and waits until the response is VALID.
@@ -44,7 +1582,39 @@ pub trait EngineApiValidWaitExt<N, T>: Send + Sync {
         &self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
-    ) -> TransportResult<ForkchoiceUpdated>;
+    ) -> Result<ForkchoiceUpdated, EngineWaitError> {
+        self.fork_choice_updated_v1_wait_with(
+            fork_choice_state,
+            payload_attributes,
+            &WaitConfig::default(),
+        )
+        .await
+        .map(|(status, _)| status)
+    }
+
+    /// Calls `engine_forkChoiceUpdatedV1` with the given [ForkchoiceState] and optional
+    /// [PayloadAttributes], and waits until the response is VALID, polling according to the given
+    /// [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn fork_choice_updated_v1_wait_with(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError>;
+
+    /// Same as [`EngineApiValidWaitExt::fork_choice_updated_v1_wait`], but panics if the engine
+    /// responds with INVALID.
+    async fn fork_choice_updated_v1_wait_or_panic(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, EngineWaitError> {
+        self.fork_choice_updated_v1_wait(fork_choice_state, payload_attributes)
+            .await
+            .map_err(EngineWaitError::panic_if_invalid)
+    }
 
     /// Calls `engine_forkChoiceUpdatedV2` with the given [ForkchoiceState] and optional
     /// [PayloadAttributes], and waits until the response is VALID.
@@ -52,7 +1622,39 @@ pub trait EngineApiValidWaitExt<N, T>: Send + Sync {
         &self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
-    ) -> TransportResult<ForkchoiceUpdated>;
+    ) -> Result<ForkchoiceUpdated, EngineWaitError> {
+        self.fork_choice_updated_v2_wait_with(
+            fork_choice_state,
+            payload_attributes,
+            &WaitConfig::default(),
+        )
+        .await
+        .map(|(status, _)| status)
+    }
+
+    /// Calls `engine_forkChoiceUpdatedV2` with the given [ForkchoiceState] and optional
+    /// [PayloadAttributes], and waits until the response is VALID, polling according to the given
+    /// [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn fork_choice_updated_v2_wait_with(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError>;
+
+    /// Same as [`EngineApiValidWaitExt::fork_choice_updated_v2_wait`], but panics if the engine
+    /// responds with INVALID.
+    async fn fork_choice_updated_v2_wait_or_panic(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, EngineWaitError> {
+        self.fork_choice_updated_v2_wait(fork_choice_state, payload_attributes)
+            .await
+            .map_err(EngineWaitError::panic_if_invalid)
+    }
 
     /// Calls `engine_forkChoiceUpdatedV3` with the given [ForkchoiceState] and optional
     /// [PayloadAttributes], and waits until the response is VALID.
@@ -60,194 +1662,804 @@ pub trait EngineApiValidWaitExt<N, T>: Send + Sync {
         &self,
         fork_choice_state: ForkchoiceState,
         payload_attributes: Option<PayloadAttributes>,
-    ) -> TransportResult<ForkchoiceUpdated>;
+    ) -> Result<ForkchoiceUpdated, EngineWaitError> {
+        self.fork_choice_updated_v3_wait_with(
+            fork_choice_state,
+            payload_attributes,
+            &WaitConfig::default(),
+        )
+        .await
+        .map(|(status, _)| status)
+    }
+
+    /// Calls `engine_forkChoiceUpdatedV3` with the given [ForkchoiceState] and optional
+    /// [PayloadAttributes], and waits until the response is VALID, polling according to the given
+    /// [WaitConfig].
+    ///
+    /// Returns the final response together with the [WaitMetrics] of the call.
+    async fn fork_choice_updated_v3_wait_with(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError>;
+
+    /// Same as [`EngineApiValidWaitExt::fork_choice_updated_v3_wait`], but panics if the engine
+    /// responds with INVALID.
+    async fn fork_choice_updated_v3_wait_or_panic(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<ForkchoiceUpdated, EngineWaitError> {
+        self.fork_choice_updated_v3_wait(fork_choice_state, payload_attributes)
+            .await
+            .map_err(EngineWaitError::panic_if_invalid)
+    }
+
+    /// Calls `engine_forkChoiceUpdatedV3` with the given [ForkchoiceState] and optional
+    /// [PayloadAttributes], waits until the response is VALID, and returns the [PayloadId] of the
+    /// started payload build.
+    ///
+    /// Returns [`EngineWaitError::MissingPayloadId`] with the response of the engine if it did not
+    /// start a build, e.g. because no payload attributes were given or the engine did not accept
+    /// them.
+    async fn fork_choice_updated_v3_build(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+    ) -> Result<PayloadId, EngineWaitError> {
+        self.fork_choice_updated_v3_build_with(
+            fork_choice_state,
+            payload_attributes,
+            &WaitConfig::default(),
+        )
+        .await
+        .map(|(payload_id, _)| payload_id)
+    }
+
+    /// Same as [`EngineApiValidWaitExt::fork_choice_updated_v3_build`], but polls according to the
+    /// given [WaitConfig].
+    ///
+    /// Returns the [PayloadId] together with the [WaitMetrics] of the call.
+    async fn fork_choice_updated_v3_build_with(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(PayloadId, WaitMetrics), EngineWaitError> {
+        let (updated, metrics) = self
+            .fork_choice_updated_v3_wait_with(fork_choice_state, payload_attributes, config)
+            .await?;
+        let Some(payload_id) = updated.payload_id else {
+            return Err(EngineWaitError::MissingPayloadId {
+                method: "engine_forkchoiceUpdatedV3",
+                response: updated,
+            })
+        };
+        Ok((payload_id, metrics))
+    }
+}
+
+#[async_trait::async_trait]
+impl<T, N, P> EngineApiValidWaitExt<N, T> for P
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N>,
+{
+    /// Sends a throwaway `engine_exchangeCapabilities` call.
+    async fn prepare(&self) -> Result<(), EngineWaitError> {
+        let _: Vec<String> =
+            self.client().request("engine_exchangeCapabilities", (Vec::<String>::new(),)).await?;
+        Ok(())
+    }
+
+    async fn negotiate_capabilities(&self, required: &[&str]) -> TransportResult<()> {
+        let required = required.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let supported: Vec<String> =
+            self.client().request("engine_exchangeCapabilities", (&required,)).await?;
+        let missing =
+            required.into_iter().filter(|method| !supported.contains(method)).collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(RpcError::local_usage(MissingCapabilitiesError { missing }))
+        }
+        Ok(())
+    }
+
+    #[instrument(level = "debug", name = "engine_newPayloadV1", skip_all, fields(block_number = payload.block_number, poll_count = field::Empty))]
+    async fn new_payload_v1_wait_with(
+        &self,
+        payload: ExecutionPayloadV1,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError> {
+        config.validate_payload(|| ExecutionPayload::V1(payload.clone()), None)?;
+        let params = SerializedParams::new((&payload,))?;
+        let (status, metrics): (PayloadStatus, _) =
+            poll_until_settled("engine_newPayloadV1", config, || {
+                self.client().request("engine_newPayloadV1", params.clone())
+            })
+            .await?;
+        if let Some(validation_error) = config.rejection(&status.status) {
+            error!(
+                %validation_error,
+                block_number = payload.block_number,
+                block_hash = %payload.block_hash,
+                ?status,
+                ?payload,
+                "Invalid newPayloadV1",
+            );
+            return Err(config.reject("engine_newPayloadV1", status, &params))
+        }
+        let block = &payload;
+        config.verify_hash("engine_newPayloadV1", block.block_number, block.block_hash, &status)?;
+        Ok((status, metrics))
+    }
+
+    #[instrument(level = "debug", name = "engine_newPayloadV2", skip_all, fields(block_number = payload.execution_payload.block_number, poll_count = field::Empty))]
+    async fn new_payload_v2_wait_with(
+        &self,
+        payload: ExecutionPayloadInputV2,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError> {
+        if let Some(chain_spec) = &config.chain_spec {
+            validate_withdrawals(chain_spec, &payload)?;
+        }
+        config.validate_payload(|| convert_payload_input_v2_to_payload(payload.clone()), None)?;
+        let params = SerializedParams::new((&payload,))?;
+        let (status, metrics): (PayloadStatus, _) =
+            poll_until_settled("engine_newPayloadV2", config, || {
+                self.client().request("engine_newPayloadV2", params.clone())
+            })
+            .await?;
+        if let Some(validation_error) = config.rejection(&status.status) {
+            let block = &payload.execution_payload;
+            error!(
+                %validation_error,
+                block_number = block.block_number,
+                block_hash = %block.block_hash,
+                ?status,
+                ?payload,
+                "Invalid newPayloadV2",
+            );
+            return Err(config.reject("engine_newPayloadV2", status, &params))
+        }
+        let block = &payload.execution_payload;
+        config.verify_hash("engine_newPayloadV2", block.block_number, block.block_hash, &status)?;
+        Ok((status, metrics))
+    }
+
+    #[instrument(level = "debug", name = "engine_newPayloadV3", skip_all, fields(block_number = payload.payload_inner.payload_inner.block_number, poll_count = field::Empty))]
+    async fn new_payload_v3_wait_with(
+        &self,
+        payload: ExecutionPayloadV3,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError> {
+        if let Some(chain_spec) = &config.chain_spec {
+            validate_parent_beacon_block_root(chain_spec, &payload, parent_beacon_block_root)?;
+        }
+        config.validate_payload(
+            || ExecutionPayload::V3(payload.clone()),
+            Some(parent_beacon_block_root),
+        )?;
+        let params =
+            SerializedParams::new((&payload, &versioned_hashes, parent_beacon_block_root))?;
+        let (status, metrics): (PayloadStatus, _) =
+            poll_until_settled("engine_newPayloadV3", config, || {
+                self.client().request("engine_newPayloadV3", params.clone())
+            })
+            .await?;
+        if let Some(validation_error) = config.rejection(&status.status) {
+            let block = &payload.payload_inner.payload_inner;
+            error!(
+                %validation_error,
+                block_number = block.block_number,
+                block_hash = %block.block_hash,
+                ?status,
+                ?payload,
+                ?versioned_hashes,
+                ?parent_beacon_block_root,
+                "Invalid newPayloadV3",
+            );
+            return Err(config.reject("engine_newPayloadV3", status, &params))
+        }
+        let block = &payload.payload_inner.payload_inner;
+        config.verify_hash("engine_newPayloadV3", block.block_number, block.block_hash, &status)?;
+        Ok((status, metrics))
+    }
+
+    #[instrument(level = "debug", name = "engine_newPayloadV4", skip_all, fields(block_number = payload.payload_inner.payload_inner.payload_inner.block_number, poll_count = field::Empty))]
+    async fn new_payload_v4_wait_with(
+        &self,
+        payload: ExecutionPayloadV4,
+        versioned_hashes: Vec<B256>,
+        parent_beacon_block_root: B256,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, WaitMetrics), EngineWaitError> {
+        if let Some(chain_spec) = &config.chain_spec {
+            validate_execution_requests(
+                &payload_requests(&payload),
+                chain_spec,
+                payload.timestamp(),
+            )?;
+        }
+        config.validate_payload(
+            || ExecutionPayload::V4(payload.clone()),
+            Some(parent_beacon_block_root),
+        )?;
+        let params =
+            SerializedParams::new((&payload, &versioned_hashes, parent_beacon_block_root))?;
+        let (status, metrics): (PayloadStatus, _) =
+            poll_until_settled("engine_newPayloadV4", config, || {
+                self.client().request("engine_newPayloadV4", params.clone())
+            })
+            .await?;
+        if let Some(validation_error) = config.rejection(&status.status) {
+            let block = &payload.payload_inner.payload_inner.payload_inner;
+            error!(
+                %validation_error,
+                block_number = block.block_number,
+                block_hash = %block.block_hash,
+                ?status,
+                ?payload,
+                ?versioned_hashes,
+                ?parent_beacon_block_root,
+                "Invalid newPayloadV4",
+            );
+            return Err(config.reject("engine_newPayloadV4", status, &params))
+        }
+        let block = &payload.payload_inner.payload_inner.payload_inner;
+        config.verify_hash("engine_newPayloadV4", block.block_number, block.block_hash, &status)?;
+        Ok((status, metrics))
+    }
+
+    #[instrument(level = "debug", name = "engine_forkchoiceUpdatedV1", skip_all, fields(head_block_hash = %fork_choice_state.head_block_hash, poll_count = field::Empty))]
+    async fn fork_choice_updated_v1_wait_with(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError> {
+        let params = SerializedParams::new((fork_choice_state, &payload_attributes))?;
+        let (status, metrics): (ForkchoiceUpdated, _) =
+            poll_until_settled("engine_forkchoiceUpdatedV1", config, || {
+                self.client().request("engine_forkchoiceUpdatedV1", params.clone())
+            })
+            .await?;
+        if let Some(validation_error) = config.rejection(&status.payload_status.status) {
+            error!(
+                %validation_error,
+                head_block_hash = %fork_choice_state.head_block_hash,
+                ?status,
+                ?fork_choice_state,
+                ?payload_attributes,
+                "Invalid forkchoiceUpdatedV1 message",
+            );
+            return Err(config.reject("engine_forkchoiceUpdatedV1", status.payload_status, &params))
+        }
+        Ok((status, metrics))
+    }
+
+    #[instrument(level = "debug", name = "engine_forkchoiceUpdatedV2", skip_all, fields(head_block_hash = %fork_choice_state.head_block_hash, poll_count = field::Empty))]
+    async fn fork_choice_updated_v2_wait_with(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError> {
+        let params = SerializedParams::new((fork_choice_state, &payload_attributes))?;
+        let (status, metrics): (ForkchoiceUpdated, _) =
+            poll_until_settled("engine_forkchoiceUpdatedV2", config, || {
+                self.client().request("engine_forkchoiceUpdatedV2", params.clone())
+            })
+            .await?;
+        if let Some(validation_error) = config.rejection(&status.payload_status.status) {
+            error!(
+                %validation_error,
+                head_block_hash = %fork_choice_state.head_block_hash,
+                ?status,
+                ?fork_choice_state,
+                ?payload_attributes,
+                "Invalid forkchoiceUpdatedV2 message",
+            );
+            return Err(config.reject("engine_forkchoiceUpdatedV2", status.payload_status, &params))
+        }
+        Ok((status, metrics))
+    }
+
+    #[instrument(level = "debug", name = "engine_forkchoiceUpdatedV3", skip_all, fields(head_block_hash = %fork_choice_state.head_block_hash, poll_count = field::Empty))]
+    async fn fork_choice_updated_v3_wait_with(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: Option<PayloadAttributes>,
+        config: &WaitConfig,
+    ) -> Result<(ForkchoiceUpdated, WaitMetrics), EngineWaitError> {
+        let params = SerializedParams::new((fork_choice_state, &payload_attributes))?;
+        let (status, metrics): (ForkchoiceUpdated, _) =
+            poll_until_settled("engine_forkchoiceUpdatedV3", config, || {
+                self.client().request("engine_forkchoiceUpdatedV3", params.clone())
+            })
+            .await?;
+        if let Some(validation_error) = config.rejection(&status.payload_status.status) {
+            error!(
+                %validation_error,
+                head_block_hash = %fork_choice_state.head_block_hash,
+                ?status,
+                ?fork_choice_state,
+                ?payload_attributes,
+                "Invalid forkchoiceUpdatedV3 message",
+            );
+            return Err(config.reject("engine_forkchoiceUpdatedV3", status.payload_status, &params))
+        }
+        Ok((status, metrics))
+    }
+}
+
+/// The JSON-RPC error code for an unknown method, which the engine returns for the versions of the
+/// engine API methods it does not support.
+const METHOD_NOT_FOUND_CODE: i64 = -32601;
+
+/// Error code returned by `engine_getPayload` if the payload id is not known (yet).
+///
+/// See also <https://github.com/ethereum/execution-apis/blob/main/src/engine/common.md#errors>
+const UNKNOWN_PAYLOAD_CODE: i64 = -38001;
+
+/// A payload built by the engine, together with the timings of building it.
+#[derive(Debug, Clone)]
+pub struct BuiltPayload<P> {
+    /// The payload returned by `engine_getPayload`.
+    pub payload: P,
+    /// The id the engine assigned to the payload.
+    pub payload_id: PayloadId,
+    /// The time until the forkchoice update with payload attributes returned VALID.
+    pub fcu_latency: Duration,
+    /// The time from the VALID forkchoice update until `engine_getPayload` returned the payload.
+    pub get_payload_latency: Duration,
+    /// The number of `engine_getPayload` calls that were sent.
+    pub poll_count: usize,
+}
+
+/// The responses of `engine_getPayload`, see [`EngineApiGetPayloadExt`].
+trait PayloadEnvelope: RpcReturn {
+    /// Returns the number of transactions in the payload.
+    fn transaction_count(&self) -> usize;
+}
+
+impl PayloadEnvelope for ExecutionPayloadEnvelopeV3 {
+    fn transaction_count(&self) -> usize {
+        self.execution_payload.payload_inner.payload_inner.transactions.len()
+    }
+}
+
+impl PayloadEnvelope for ExecutionPayloadEnvelopeV4 {
+    fn transaction_count(&self) -> usize {
+        self.execution_payload.payload_inner.payload_inner.payload_inner.transactions.len()
+    }
+}
+
+/// An extension trait for providers that implement the engine API, to benchmark payload building.
+///
+/// Each method sends a single forkchoice update with payload attributes, waits until it is VALID,
+/// and then polls `engine_getPayload` with the returned [PayloadId] until the engine returns a
+/// payload with transactions, according to the given [WaitConfig].
+///
+/// The forkchoice update is only re-sent while the engine is SYNCING, before it started a build.
+/// It is never re-sent once the engine returned a payload id, because every forkchoice update
+/// with payload attributes replaces the build job, and the engine forgets the previous id.
+///
+/// The engine starts building on the forkchoice update and keeps improving the payload on its
+/// build interval, and `engine_getPayload` returns the best payload built so far, which is empty
+/// until the first build with transactions finished. The
+/// [`get_payload_latency`](BuiltPayload::get_payload_latency) is therefore not the time it takes
+/// to build a single payload, but is dominated by the build interval of the node, e.g. reth's
+/// `--builder.interval`, and the poll interval of the config.
+///
+/// Empty payloads are polled again until [`WaitConfig::per_block_deadline`] passed, or
+/// [`DEFAULT_BUILD_DEADLINE`] if the config has none, or [`WaitConfig::max_attempts`] calls were
+/// sent. Then [`EngineWaitError::EmptyPayload`] is returned, or
+/// [`EngineWaitError::PayloadUnavailable`] if the engine never returned the payload. The deadline
+/// starts with the first `engine_getPayload` call.
+#[async_trait::async_trait]
+pub trait EngineApiGetPayloadExt<N, T>: EngineApiValidWaitExt<N, T> {
+    /// Calls `engine_forkchoiceUpdatedV3` with the given [ForkchoiceState] and
+    /// [PayloadAttributes], then polls `engine_getPayloadV3` until the built payload is returned.
+    async fn get_payload_v3_wait(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: PayloadAttributes,
+        config: &WaitConfig,
+    ) -> Result<BuiltPayload<ExecutionPayloadEnvelopeV3>, EngineWaitError>;
+
+    /// Calls `engine_forkchoiceUpdatedV3` with the given [ForkchoiceState] and
+    /// [PayloadAttributes], then polls `engine_getPayloadV4` until the built payload is returned.
+    async fn get_payload_v4_wait(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: PayloadAttributes,
+        config: &WaitConfig,
+    ) -> Result<BuiltPayload<ExecutionPayloadEnvelopeV4>, EngineWaitError>;
+}
+
+#[async_trait::async_trait]
+impl<T, N, P> EngineApiGetPayloadExt<N, T> for P
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N>,
+{
+    async fn get_payload_v3_wait(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: PayloadAttributes,
+        config: &WaitConfig,
+    ) -> Result<BuiltPayload<ExecutionPayloadEnvelopeV3>, EngineWaitError> {
+        build_payload(self, "engine_getPayloadV3", fork_choice_state, payload_attributes, config)
+            .await
+    }
+
+    async fn get_payload_v4_wait(
+        &self,
+        fork_choice_state: ForkchoiceState,
+        payload_attributes: PayloadAttributes,
+        config: &WaitConfig,
+    ) -> Result<BuiltPayload<ExecutionPayloadEnvelopeV4>, EngineWaitError> {
+        // Prague does not introduce a new forkchoiceUpdated version, so this also starts the build
+        // with `engine_forkchoiceUpdatedV3`
+        build_payload(self, "engine_getPayloadV4", fork_choice_state, payload_attributes, config)
+            .await
+    }
+}
+
+/// Starts a payload build with `engine_forkchoiceUpdatedV3`, and polls the given
+/// `engine_getPayload` method with the returned payload id until the engine returns a payload
+/// with transactions, see [`EngineApiGetPayloadExt`].
+async fn build_payload<N, T, P, R>(
+    provider: &P,
+    method: &'static str,
+    fork_choice_state: ForkchoiceState,
+    payload_attributes: PayloadAttributes,
+    config: &WaitConfig,
+) -> Result<BuiltPayload<R>, EngineWaitError>
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N>,
+    R: PayloadEnvelope,
+{
+    let start = Instant::now();
+    let (payload_id, _) = provider
+        .fork_choice_updated_v3_build_with(fork_choice_state, Some(payload_attributes), config)
+        .await?;
+    let fcu_latency = start.elapsed();
+
+    let start = Instant::now();
+    let deadline = config.per_block_deadline.unwrap_or(DEFAULT_BUILD_DEADLINE);
+    let mut interval = config.poll_interval;
+    let mut attempts = 1;
+    let mut returned_payload = false;
+    loop {
+        let res =
+            config.send(method, &mut || provider.client().request(method, (payload_id,))).await;
+        match res {
+            Ok(payload) => {
+                let payload: R = payload;
+                if payload.transaction_count() > 0 {
+                    return Ok(BuiltPayload {
+                        payload,
+                        payload_id,
+                        fcu_latency,
+                        get_payload_latency: start.elapsed(),
+                        poll_count: attempts as usize,
+                    })
+                }
+                returned_payload = true;
+            }
+            Err(EngineWaitError::Transport(err))
+                if err.as_error_resp().is_some_and(|err| err.code == UNKNOWN_PAYLOAD_CODE) => {}
+            Err(err) => return Err(err),
+        }
+
+        let out_of_attempts =
+            config.max_attempts.is_some_and(|max_attempts| attempts >= max_attempts);
+        if out_of_attempts || start.elapsed() + interval >= deadline {
+            return Err(if returned_payload {
+                EngineWaitError::EmptyPayload { method, payload_id, attempts }
+            } else {
+                EngineWaitError::PayloadUnavailable { method, payload_id, attempts }
+            })
+        }
+
+        config.sleep(method, interval).await?;
+        interval = config.next_interval(interval);
+        attempts += 1;
+    }
+}
+
+/// The timings of a block imported with [`EngineApiImportExt::import_block_v3`].
+#[derive(Debug, Clone)]
+pub struct ImportedBlock {
+    /// The time until the forkchoice update to the parent block returned VALID.
+    pub head_fcu_latency: Duration,
+    /// The time until `engine_newPayloadV3` responded.
+    pub new_payload_latency: Duration,
+    /// The status returned by `engine_newPayloadV3`, which is either VALID, or ACCEPTED or
+    /// SYNCING if the engine only executes the block on the final forkchoice update.
+    pub new_payload_status: PayloadStatus,
+    /// The time until the forkchoice update to the imported block returned VALID.
+    pub fcu_latency: Duration,
+    /// The number of calls of the forkchoice update to the imported block that were sent.
+    pub fcu_poll_count: usize,
+    /// The time until the whole import finished.
+    pub total_latency: Duration,
+}
+
+/// An extension trait for providers that implement the engine API, to benchmark importing blocks
+/// the way a consensus client does.
+#[async_trait::async_trait]
+pub trait EngineApiImportExt<N, T>: EngineApiValidWaitExt<N, T> {
+    /// Imports a block with three calls: `engine_forkchoiceUpdatedV3` to the parent of the
+    /// payload, `engine_newPayloadV3` with the payload, and `engine_forkchoiceUpdatedV3` with the
+    /// given [ForkchoiceState].
+    ///
+    /// The first forkchoice update sets the head, safe and finalized block to the parent, because
+    /// the safe or finalized block of the given state may be the payload, which the engine rejects
+    /// as an invalid forkchoice state before the payload is imported.
+    ///
+    /// The forkchoice updates are polled until VALID according to the given [WaitConfig]. The
+    /// `engine_newPayloadV3` call is sent with [`Self::deliver_payload_v3`], so it is not re-sent
+    /// if the engine responds with ACCEPTED or SYNCING.
+    async fn import_block_v3(
+        &self,
+        payload: ExecutionPayloadV3,
+        sidecar: CancunPayloadFields,
+        fork_choice_state: ForkchoiceState,
+        config: &WaitConfig,
+    ) -> Result<ImportedBlock, EngineWaitError>;
+
+    /// Sends `engine_newPayloadV3` once, without polling until VALID, and returns the status
+    /// together with the latency of the call.
+    ///
+    /// ACCEPTED and SYNCING are returned like VALID, because the engine only executes such a
+    /// payload, e.g. a block of a side chain, once a forkchoice update makes it canonical. Only
+    /// INVALID fails with [`EngineWaitError::Invalid`], unless [`WaitConfig::accept_invalid`] is
+    /// set. A VALID status is checked like the one of the `*_wait` methods if
+    /// [`WaitConfig::verify_hashes`] is set.
+    async fn deliver_payload_v3(
+        &self,
+        payload: ExecutionPayloadV3,
+        sidecar: CancunPayloadFields,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, Duration), EngineWaitError>;
 }
 
 #[async_trait::async_trait]
-impl<T, N, P> EngineApiValidWaitExt<N, T> for P
+impl<T, N, P> EngineApiImportExt<N, T> for P
 where
     N: Network,
     T: Transport + Clone,
-    P: EngineApi<N, T>,
+    P: Provider<T, N>,
 {
-    async fn new_payload_v1_wait(
+    async fn import_block_v3(
         &self,
-        payload: ExecutionPayloadV1,
-    ) -> TransportResult<PayloadStatus> {
-        let mut status = self.new_payload_v1(payload.clone()).await?;
-        while !status.is_valid() {
-            if status.is_invalid() {
-                error!(?status, ?payload, "Invalid newPayloadV1",);
-                panic!("Invalid newPayloadV1: {status:?}");
-            }
-            status = self.new_payload_v1(payload.clone()).await?;
-        }
-        Ok(status)
-    }
+        payload: ExecutionPayloadV3,
+        sidecar: CancunPayloadFields,
+        fork_choice_state: ForkchoiceState,
+        config: &WaitConfig,
+    ) -> Result<ImportedBlock, EngineWaitError> {
+        let start = Instant::now();
+        // the safe and finalized blocks of the given state may be the payload itself, which the
+        // engine does not know yet, so the first update only uses the parent
+        let parent_hash = payload.payload_inner.payload_inner.parent_hash;
+        let parent_state = ForkchoiceState {
+            head_block_hash: parent_hash,
+            safe_block_hash: parent_hash,
+            finalized_block_hash: parent_hash,
+        };
+        let (_, head_metrics) =
+            self.fork_choice_updated_v3_wait_with(parent_state, None, config).await?;
 
-    async fn new_payload_v2_wait(
-        &self,
-        payload: ExecutionPayloadInputV2,
-    ) -> TransportResult<PayloadStatus> {
-        let mut status = self.new_payload_v2(payload.clone()).await?;
-        while !status.is_valid() {
-            if status.is_invalid() {
-                error!(?status, ?payload, "Invalid newPayloadV2",);
-                panic!("Invalid newPayloadV2: {status:?}");
-            }
-            status = self.new_payload_v2(payload.clone()).await?;
-        }
-        Ok(status)
+        let (new_payload_status, new_payload_latency) =
+            self.deliver_payload_v3(payload, sidecar, config).await?;
+
+        let (_, fcu_metrics) =
+            self.fork_choice_updated_v3_wait_with(fork_choice_state, None, config).await?;
+
+        Ok(ImportedBlock {
+            head_fcu_latency: head_metrics.valid_after,
+            new_payload_latency,
+            new_payload_status,
+            fcu_latency: fcu_metrics.valid_after,
+            fcu_poll_count: fcu_metrics.poll_count,
+            total_latency: start.elapsed(),
+        })
     }
 
-    async fn new_payload_v3_wait(
+    async fn deliver_payload_v3(
         &self,
         payload: ExecutionPayloadV3,
-        versioned_hashes: Vec<B256>,
-        parent_beacon_block_root: B256,
-    ) -> TransportResult<PayloadStatus> {
-        let mut status = self
-            .new_payload_v3(payload.clone(), versioned_hashes.clone(), parent_beacon_block_root)
-            .await?;
-        while !status.is_valid() {
-            if status.is_invalid() {
-                error!(
-                    ?status,
-                    ?payload,
-                    ?versioned_hashes,
-                    ?parent_beacon_block_root,
-                    "Invalid newPayloadV3",
-                );
-                panic!("Invalid newPayloadV3: {status:?}");
-            }
-            status = self
-                .new_payload_v3(payload.clone(), versioned_hashes.clone(), parent_beacon_block_root)
-                .await?;
+        sidecar: CancunPayloadFields,
+        config: &WaitConfig,
+    ) -> Result<(PayloadStatus, Duration), EngineWaitError> {
+        const METHOD: &str = "engine_newPayloadV3";
+
+        let CancunPayloadFields { parent_beacon_block_root, versioned_hashes } = sidecar;
+        let params =
+            SerializedParams::new((&payload, &versioned_hashes, parent_beacon_block_root))?;
+        let start = Instant::now();
+        let status: PayloadStatus =
+            config.send(METHOD, &mut || self.client().request(METHOD, params.clone())).await?;
+        let latency = start.elapsed();
+        if config.rejection(&status.status).is_some() {
+            return Err(config.reject(METHOD, status, &params))
         }
-        Ok(status)
+        let block = &payload.payload_inner.payload_inner;
+        config.verify_hash(METHOD, block.block_number, block.block_hash, &status)?;
+        Ok((status, latency))
     }
+}
 
-    async fn fork_choice_updated_v1_wait(
-        &self,
-        fork_choice_state: ForkchoiceState,
-        payload_attributes: Option<PayloadAttributes>,
-    ) -> TransportResult<ForkchoiceUpdated> {
-        let mut status =
-            self.fork_choice_updated_v1(fork_choice_state, payload_attributes.clone()).await?;
-
-        while !status.is_valid() {
-            if status.is_invalid() {
-                error!(
-                    ?status,
-                    ?fork_choice_state,
-                    ?payload_attributes,
-                    "Invalid forkchoiceUpdatedV1 message",
-                );
-                panic!("Invalid forkchoiceUpdatedV1: {status:?}");
-            }
-            status =
-                self.fork_choice_updated_v1(fork_choice_state, payload_attributes.clone()).await?;
-        }
+/// A blob and its KZG proof, as returned by `engine_getBlobsV1`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlobAndProofV1 {
+    /// The blob, which is [`BYTES_PER_BLOB`](alloy_eips::eip4844::BYTES_PER_BLOB) bytes long.
+    ///
+    /// This is not a fixed size array, because deserializing one keeps several copies of it on
+    /// the stack in debug builds, which overflows the default thread stack.
+    pub blob: Bytes,
+    /// The KZG proof of the blob.
+    pub proof: Bytes48,
+}
+
+/// The response of [`EngineApiBlobsExt::get_blobs_v1`].
+#[derive(Debug, Clone)]
+pub struct GetBlobsV1 {
+    /// The blob of every requested versioned hash, in the order of the request, or `None` if the
+    /// engine does not have the blob.
+    pub blobs: Vec<Option<BlobAndProofV1>>,
+    /// The time until the engine responded.
+    pub latency: Duration,
+}
 
-        Ok(status)
+impl GetBlobsV1 {
+    /// Returns the number of requested blobs that the engine returned.
+    pub fn available(&self) -> usize {
+        self.blobs.iter().flatten().count()
     }
 
-    async fn fork_choice_updated_v2_wait(
+    /// Returns whether the engine returned every requested blob.
+    pub fn is_complete(&self) -> bool {
+        self.blobs.iter().all(Option::is_some)
+    }
+}
+
+/// An extension trait for providers that implement the engine API, to benchmark how fast the
+/// engine serves the blobs of its transaction pool.
+#[async_trait::async_trait]
+pub trait EngineApiBlobsExt<N, T>: Send + Sync {
+    /// Calls `engine_getBlobsV1` with the given versioned hashes, and returns the blobs together
+    /// with the latency of the call.
+    ///
+    /// Blobs that the engine does not have are returned as `None`, which is not an error. The
+    /// call is sent like the calls of the `*_wait` methods, according to the given [WaitConfig].
+    async fn get_blobs_v1_with(
         &self,
-        fork_choice_state: ForkchoiceState,
-        payload_attributes: Option<PayloadAttributes>,
-    ) -> TransportResult<ForkchoiceUpdated> {
-        let mut status =
-            self.fork_choice_updated_v2(fork_choice_state, payload_attributes.clone()).await?;
-
-        while !status.is_valid() {
-            if status.is_invalid() {
-                error!(
-                    ?status,
-                    ?fork_choice_state,
-                    ?payload_attributes,
-                    "Invalid forkchoiceUpdatedV2 message",
-                );
-                panic!("Invalid forkchoiceUpdatedV2: {status:?}");
-            }
-            status =
-                self.fork_choice_updated_v2(fork_choice_state, payload_attributes.clone()).await?;
-        }
+        versioned_hashes: Vec<B256>,
+        config: &WaitConfig,
+    ) -> Result<GetBlobsV1, EngineWaitError>;
 
-        Ok(status)
+    /// Same as [`EngineApiBlobsExt::get_blobs_v1_with`], with the default [WaitConfig].
+    async fn get_blobs_v1(
+        &self,
+        versioned_hashes: Vec<B256>,
+    ) -> Result<GetBlobsV1, EngineWaitError> {
+        self.get_blobs_v1_with(versioned_hashes, &WaitConfig::default()).await
     }
+}
 
-    async fn fork_choice_updated_v3_wait(
+#[async_trait::async_trait]
+impl<T, N, P> EngineApiBlobsExt<N, T> for P
+where
+    N: Network,
+    T: Transport + Clone,
+    P: Provider<T, N>,
+{
+    async fn get_blobs_v1_with(
         &self,
-        fork_choice_state: ForkchoiceState,
-        payload_attributes: Option<PayloadAttributes>,
-    ) -> TransportResult<ForkchoiceUpdated> {
-        let mut status =
-            self.fork_choice_updated_v3(fork_choice_state, payload_attributes.clone()).await?;
-
-        while !status.is_valid() {
-            if status.is_invalid() {
-                error!(
-                    ?status,
-                    ?fork_choice_state,
-                    ?payload_attributes,
-                    "Invalid forkchoiceUpdatedV3 message",
-                );
-                panic!("Invalid forkchoiceUpdatedV3: {status:?}");
-            }
-            status =
-                self.fork_choice_updated_v3(fork_choice_state, payload_attributes.clone()).await?;
-        }
+        versioned_hashes: Vec<B256>,
+        config: &WaitConfig,
+    ) -> Result<GetBlobsV1, EngineWaitError> {
+        const METHOD: &str = "engine_getBlobsV1";
 
-        Ok(status)
+        let params = SerializedParams::new((&versioned_hashes,))?;
+        let start = Instant::now();
+        let blobs: Vec<Option<BlobAndProofV1>> =
+            config.send(METHOD, &mut || self.client().request(METHOD, params.clone())).await?;
+        let latency = start.elapsed();
+        if blobs.len() != versioned_hashes.len() {
+            return Err(EngineWaitError::BlobCountMismatch {
+                method: METHOD,
+                requested: versioned_hashes.len(),
+                returned: blobs.len(),
+            })
+        }
+        Ok(GetBlobsV1 { blobs, latency })
     }
 }
 
 /// Calls the correct `engine_newPayload` method depending on the given [`ExecutionPayload`] and its
-/// versioned variant. Returns the [`EngineApiMessageVersion`] depending on the payload's version.
+/// versioned variant. Returns the [`EngineApiMessageVersion`] depending on the payload's version,
+/// together with the [`WaitMetrics`] of the call.
+///
+/// If [`WaitConfig::chain_spec`] is set, the version is selected from the fork schedule of the
+/// chain at the timestamp of the payload instead, see [`WaitConfig::engine_version`], and the
+/// payload is converted to that version with [`payload_with_version`].
 ///
-/// # Panics
-/// If the given payload is a V3 payload, but a parent beacon block root is provided as `None`.
+/// The chain spec also rejects blocks from before the merge with [`EngineWaitError::PreMergeBlock`]
+/// before they are sent, see [`validate_post_merge`].
+///
+/// Returns [`EngineWaitError::MissingCancunFields`] if the payload is sent as a V3 or V4 payload,
+/// but a parent beacon block root is provided as `None`.
 pub(crate) async fn call_new_payload<N, T, P: EngineApiValidWaitExt<N, T>>(
     provider: P,
     payload: ExecutionPayload,
     parent_beacon_block_root: Option<B256>,
     versioned_hashes: Vec<B256>,
-) -> TransportResult<EngineApiMessageVersion> {
-    match payload {
-        ExecutionPayload::V4(_payload) => {
-            todo!("V4 payloads not supported yet");
-            // auth_provider
-            //     .new_payload_v4_wait(payload, versioned_hashes, parent_beacon_block_root, ...)
-            //     .await?;
-            //
-            // Ok(EngineApiMessageVersion::V4)
-        }
-        ExecutionPayload::V3(payload) => {
-            // We expect the caller
-            let parent_beacon_block_root = parent_beacon_block_root
-                .expect("parent_beacon_block_root is required for V3 payloads");
-            provider
-                .new_payload_v3_wait(payload, versioned_hashes, parent_beacon_block_root)
-                .await?;
-
-            Ok(EngineApiMessageVersion::V3)
-        }
-        ExecutionPayload::V2(payload) => {
-            let input = ExecutionPayloadInputV2 {
-                execution_payload: payload.payload_inner,
-                withdrawals: Some(payload.withdrawals),
-            };
+    config: &WaitConfig,
+) -> Result<(EngineApiMessageVersion, WaitMetrics), EngineWaitError> {
+    let version = config.engine_version(payload.timestamp()).unwrap_or(match payload {
+        ExecutionPayload::V4(_) => EngineApiMessageVersion::V4,
+        ExecutionPayload::V3(_) => EngineApiMessageVersion::V3,
+        ExecutionPayload::V2(_) => EngineApiMessageVersion::V2,
+        ExecutionPayload::V1(_) => EngineApiMessageVersion::V1,
+    });
+    if let Some(chain_spec) = &config.chain_spec {
+        validate_post_merge(chain_spec, payload.block_number())?;
+    }
+    let payload = payload_with_version(payload, version);
+    let sidecar = parent_beacon_block_root.map_or_else(ExecutionPayloadSidecar::none, |root| {
+        ExecutionPayloadSidecar::v3(versioned_hashes, root)
+    });
 
-            provider.new_payload_v2_wait(input).await?;
+    let (_, metrics) = provider.new_payload_wait_with(payload, sidecar, config).await?;
+    Ok((version, metrics))
+}
 
-            Ok(EngineApiMessageVersion::V2)
+/// Converts the given payload to the payload of the given engine API version.
+///
+/// Fields that the version does not have are dropped, and the fields it adds are left empty. The
+/// engine rejects a converted payload whose fields do not match the block, so this is only
+/// lossless if the version matches the fork of the block.
+fn payload_with_version(
+    payload: ExecutionPayload,
+    version: EngineApiMessageVersion,
+) -> ExecutionPayload {
+    let (payload_v3, deposit_requests, withdrawal_requests) = match payload {
+        ExecutionPayload::V1(payload_inner) => {
+            let payload_inner = ExecutionPayloadV2 { payload_inner, withdrawals: Vec::new() };
+            let payload =
+                ExecutionPayloadV3 { payload_inner, blob_gas_used: 0, excess_blob_gas: 0 };
+            (payload, Vec::new(), Vec::new())
         }
-        ExecutionPayload::V1(payload) => {
-            provider.new_payload_v1_wait(payload).await?;
-
-            Ok(EngineApiMessageVersion::V1)
+        ExecutionPayload::V2(payload_inner) => {
+            let payload =
+                ExecutionPayloadV3 { payload_inner, blob_gas_used: 0, excess_blob_gas: 0 };
+            (payload, Vec::new(), Vec::new())
+        }
+        ExecutionPayload::V3(payload) => (payload, Vec::new(), Vec::new()),
+        ExecutionPayload::V4(payload) => {
+            (payload.payload_inner, payload.deposit_requests, payload.withdrawal_requests)
         }
+    };
+    match version {
+        EngineApiMessageVersion::V1 => ExecutionPayload::V1(payload_v3.payload_inner.payload_inner),
+        EngineApiMessageVersion::V2 => ExecutionPayload::V2(payload_v3.payload_inner),
+        EngineApiMessageVersion::V3 => ExecutionPayload::V3(payload_v3),
+        EngineApiMessageVersion::V4 => ExecutionPayload::V4(ExecutionPayloadV4 {
+            payload_inner: payload_v3,
+            deposit_requests,
+            withdrawal_requests,
+        }),
     }
 }
 
@@ -259,17 +2471,1294 @@ pub(crate) async fn call_forkchoice_updated<N, T, P: EngineApiValidWaitExt<N, T>
     message_version: EngineApiMessageVersion,
     forkchoice_state: ForkchoiceState,
     payload_attributes: Option<PayloadAttributes>,
-) -> TransportResult<ForkchoiceUpdated> {
-    match message_version {
-        EngineApiMessageVersion::V4 => todo!("V4 payloads not supported yet"),
-        EngineApiMessageVersion::V3 => {
-            provider.fork_choice_updated_v3_wait(forkchoice_state, payload_attributes).await
+) -> Result<ForkchoiceUpdated, EngineWaitError> {
+    provider.fork_choice_updated_wait(message_version, forkchoice_state, payload_attributes).await
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use alloy_json_rpc::{ErrorPayload, RequestPacket, Response, ResponsePacket, ResponsePayload};
+    use alloy_provider::RootProvider;
+    use alloy_rpc_client::RpcClient;
+    use alloy_transport::TransportFut;
+    use reth_chainspec::MAINNET;
+    use std::{
+        collections::VecDeque,
+        sync::Mutex,
+        task::{Context, Poll},
+    };
+
+    /// An engine API transport that answers every request with the next scripted response, and
+    /// records the methods that were called.
+    #[derive(Debug, Clone, Default)]
+    pub(crate) struct MockEngineApi {
+        responses: Arc<Mutex<VecDeque<ResponsePayload>>>,
+        calls: Arc<Mutex<Vec<String>>>,
+        params: Arc<Mutex<Vec<serde_json::Value>>>,
+    }
+
+    impl MockEngineApi {
+        /// Creates a mock that answers with the given statuses, in order.
+        pub(crate) fn with_statuses(statuses: impl IntoIterator<Item = PayloadStatusEnum>) -> Self {
+            let mock = Self::default();
+            for status in statuses {
+                mock.push_success(PayloadStatus::from_status(status));
+            }
+            mock
+        }
+
+        /// Appends a successful response with the given result.
+        pub(crate) fn push_success(&self, result: impl Serialize) {
+            let raw = serde_json::value::to_raw_value(&result).unwrap();
+            self.responses.lock().unwrap().push_back(ResponsePayload::Success(raw));
+        }
+
+        /// Appends an error response with the given code.
+        pub(crate) fn push_error(&self, code: i64, message: &str) {
+            let error = ErrorPayload { code, message: message.to_string(), data: None };
+            self.responses.lock().unwrap().push_back(ResponsePayload::Failure(error));
+        }
+
+        /// Returns a provider sending all requests to this mock.
+        pub(crate) fn provider(&self) -> RootProvider<Self> {
+            RootProvider::new(RpcClient::new(self.clone(), true))
+        }
+
+        /// Returns the methods that were called, in order.
+        pub(crate) fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        /// Returns the params of the calls, in order.
+        pub(crate) fn params(&self) -> Vec<serde_json::Value> {
+            self.params.lock().unwrap().clone()
+        }
+    }
+
+    impl tower::Service<RequestPacket> for MockEngineApi {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: RequestPacket) -> Self::Future {
+            let RequestPacket::Single(req) = req else {
+                panic!("batch requests are not supported by the mock engine API")
+            };
+            self.calls.lock().unwrap().push(req.method().to_string());
+            let params = req.params().map_or(serde_json::Value::Null, |params| {
+                serde_json::from_str(params.get()).unwrap()
+            });
+            self.params.lock().unwrap().push(params);
+            let payload = self.responses.lock().unwrap().pop_front().unwrap_or_else(|| {
+                ResponsePayload::Failure(ErrorPayload {
+                    code: -32603,
+                    message: format!("no scripted response left for {}", req.method()),
+                    data: None,
+                })
+            });
+            let response = Response { id: req.id().clone(), payload };
+            Box::pin(async move { Ok(ResponsePacket::Single(response)) })
+        }
+    }
+
+    fn payload_v1(timestamp: u64) -> ExecutionPayloadV1 {
+        ExecutionPayloadV1 {
+            parent_hash: B256::ZERO,
+            fee_recipient: Default::default(),
+            state_root: B256::ZERO,
+            receipts_root: B256::ZERO,
+            logs_bloom: Default::default(),
+            prev_randao: B256::ZERO,
+            block_number: 0,
+            gas_limit: 0,
+            gas_used: 0,
+            timestamp,
+            extra_data: Default::default(),
+            base_fee_per_gas: Default::default(),
+            block_hash: B256::ZERO,
+            transactions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn wait_config_backoff() {
+        let config = WaitConfig::default().with_poll_interval(Duration::from_millis(10));
+        assert_eq!(config.next_interval(Duration::from_millis(10)), Duration::from_millis(10));
+
+        let config = config.with_exponential_backoff(Duration::from_millis(35));
+        let mut interval = config.poll_interval;
+        let mut intervals = Vec::new();
+        for _ in 0..4 {
+            interval = config.next_interval(interval);
+            intervals.push(interval.as_millis());
+        }
+        assert_eq!(intervals, vec![20, 35, 35, 35]);
+    }
+
+    #[test]
+    fn wait_config_progress_interval() {
+        let config = WaitConfig::default();
+        assert_eq!(config.progress_interval, Some(DEFAULT_PROGRESS_INTERVAL));
+        assert!(!config.progress_due(Duration::from_secs(9)));
+        assert!(config.progress_due(Duration::from_secs(10)));
+
+        let config = config.with_progress_interval(Duration::from_secs(1));
+        assert!(config.progress_due(Duration::from_secs(1)));
+        assert!(!config.without_progress_interval().progress_due(Duration::MAX));
+    }
+
+    #[test]
+    fn wait_config_jitter() {
+        let interval = Duration::from_millis(100);
+        assert_eq!(WaitConfig::default().jittered(interval), interval);
+        assert_eq!(WaitConfig::default().with_jitter(0).jittered(interval), interval);
+
+        let config = WaitConfig::default().with_jitter(20);
+        for _ in 0..100 {
+            let jittered = config.jittered(interval);
+            assert!(
+                jittered >= Duration::from_millis(80) && jittered <= Duration::from_millis(120)
+            );
+        }
+        assert!(WaitConfig::default().with_jitter(200).jittered(interval) <= interval * 2);
+    }
+
+    #[tokio::test]
+    async fn wait_is_cancelled() {
+        let cancel = CancellationToken::new();
+        let config = WaitConfig::default().with_cancellation(cancel.clone());
+        cancel.cancel();
+
+        let res = poll_until_settled("engine_newPayloadV3", &config, || {
+            std::future::pending::<TransportResult<PayloadStatus>>()
+        })
+        .await;
+        assert!(matches!(res, Err(EngineWaitError::Cancelled { method: "engine_newPayloadV3" })));
+    }
+
+    #[test]
+    fn retryable_transport_errors() {
+        assert!(is_retryable(&TransportErrorKind::http_error(503, String::new())));
+        assert!(is_retryable(&TransportErrorKind::http_error(429, String::new())));
+        assert!(is_retryable(&TransportErrorKind::custom(CallTimeoutError {
+            method: "engine_newPayloadV3",
+            timeout: Duration::from_secs(1),
+        })));
+        assert!(!is_retryable(&TransportErrorKind::custom_str("invalid JWT")));
+        assert!(!is_retryable(&TransportErrorKind::custom_str("connection refused")));
+        assert!(!is_retryable(&TransportErrorKind::http_error(401, String::new())));
+        assert!(!is_retryable(&TransportErrorKind::backend_gone()));
+        assert!(!is_retryable(&TransportError::ser_err(serde_json::Error::io(
+            std::io::ErrorKind::Other.into()
+        ))));
+    }
+
+    #[tokio::test]
+    async fn negotiate_capabilities_lists_missing_methods() {
+        let mock = MockEngineApi::default();
+        mock.push_success(["engine_newPayloadV3", "engine_forkchoiceUpdatedV3"]);
+        mock.push_success(["engine_newPayloadV3", "engine_forkchoiceUpdatedV3"]);
+        let provider = mock.provider();
+
+        provider.negotiate_capabilities(&["engine_newPayloadV3"]).await.unwrap();
+
+        let err = provider
+            .negotiate_capabilities(&[
+                "engine_getPayloadV4",
+                "engine_newPayloadV3",
+                "engine_newPayloadV4",
+            ])
+            .await
+            .unwrap_err();
+        assert!(!is_retryable(&err));
+        let RpcError::LocalUsageError(err) = err else { panic!("unexpected error: {err:?}") };
+        let err = err.downcast_ref::<MissingCapabilitiesError>().unwrap();
+        assert_eq!(err.missing, ["engine_getPayloadV4", "engine_newPayloadV4"]);
+        assert_eq!(
+            err.to_string(),
+            "engine does not support the required methods: engine_getPayloadV4, engine_newPayloadV4"
+        );
+        assert_eq!(mock.calls(), ["engine_exchangeCapabilities", "engine_exchangeCapabilities"]);
+    }
+
+    #[tokio::test]
+    async fn transport_errors_are_retried() {
+        let config = WaitConfig::default().with_transport_retries(2);
+        let mut errors = 2;
+        let (_, metrics) = poll_until_settled("engine_newPayloadV3", &config, || {
+            let res = if errors > 0 {
+                errors -= 1;
+                Err(TransportErrorKind::http_error(503, String::new()))
+            } else {
+                Ok(PayloadStatus::from_status(PayloadStatusEnum::Valid))
+            };
+            std::future::ready(res)
+        })
+        .await
+        .unwrap();
+        assert_eq!(metrics.poll_count, 1);
+
+        let mut errors = 3;
+        let res = poll_until_settled("engine_newPayloadV3", &config, || {
+            errors -= 1;
+            std::future::ready(TransportResult::<PayloadStatus>::Err(
+                TransportErrorKind::http_error(503, String::new()),
+            ))
+        })
+        .await;
+        assert!(matches!(res, Err(EngineWaitError::Transport(_))));
+        assert_eq!(errors, 0);
+    }
+
+    #[tokio::test]
+    async fn call_timeouts_are_retried() {
+        let config = WaitConfig::default()
+            .with_call_timeout(Duration::from_millis(10))
+            .with_transport_retries(1);
+        let mut hangs = 1;
+        let (_, metrics) = poll_until_settled("engine_newPayloadV3", &config, || {
+            let hang = hangs > 0;
+            hangs -= 1;
+            async move {
+                if hang {
+                    std::future::pending::<()>().await;
+                }
+                Ok(PayloadStatus::from_status(PayloadStatusEnum::Valid))
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(metrics.poll_count, 1);
+
+        let err = poll_until_settled("engine_newPayloadV3", &config, || {
+            std::future::pending::<TransportResult<PayloadStatus>>()
+        })
+        .await
+        .unwrap_err();
+        let EngineWaitError::Transport(err) = err else { panic!("unexpected error: {err:?}") };
+        assert!(is_retryable(&err));
+        assert_eq!(
+            err.to_string(),
+            CallTimeoutError { method: "engine_newPayloadV3", timeout: Duration::from_millis(10) }
+                .to_string()
+        );
+        assert_eq!(WaitConfig::default().call_timeout, Some(DEFAULT_CALL_TIMEOUT));
+        assert_eq!(WaitConfig::default().without_call_timeout().call_timeout, None);
+    }
+
+    #[tokio::test]
+    async fn per_block_deadline_spans_all_attempts() {
+        // every call responds within the call timeout, but the engine never settles
+        let config = WaitConfig::default()
+            .with_call_timeout(Duration::from_millis(100))
+            .with_poll_interval(Duration::from_millis(5))
+            .with_per_block_deadline(Duration::from_millis(40));
+        let start = Instant::now();
+        let err = poll_until_settled("engine_newPayloadV3", &config, || async {
+            Ok(PayloadStatus::from_status(PayloadStatusEnum::Syncing))
+        })
+        .await
+        .unwrap_err();
+        assert!(start.elapsed() < Duration::from_millis(100));
+        let EngineWaitError::Timeout { attempts, status, .. } = err else {
+            panic!("unexpected error: {err:?}")
+        };
+        assert!(attempts > 1);
+        assert_eq!(status, Some(PayloadStatus::from_status(PayloadStatusEnum::Syncing)));
+
+        // the deadline also aborts retries of calls that time out, and a call that is in flight
+        let config = config.with_call_timeout(Duration::from_millis(15)).with_transport_retries(10);
+        let err = poll_until_settled("engine_newPayloadV3", &config, || {
+            std::future::pending::<TransportResult<PayloadStatus>>()
+        })
+        .await
+        .unwrap_err();
+        assert!(matches!(err, EngineWaitError::Timeout { status: None, .. }), "{err:?}");
+        assert_eq!(
+            err.to_string(),
+            "engine_newPayloadV3 did not return VALID after 1 attempts, last status: none"
+        );
+    }
+
+    #[tokio::test]
+    async fn new_payload_polls_until_valid() {
+        let mock = MockEngineApi::with_statuses([
+            PayloadStatusEnum::Syncing,
+            PayloadStatusEnum::Syncing,
+            PayloadStatusEnum::Valid,
+        ]);
+        let (status, metrics) = mock
+            .provider()
+            .new_payload_v1_wait_with(payload_v1(0), &WaitConfig::default())
+            .await
+            .unwrap();
+        assert_eq!(status.status, PayloadStatusEnum::Valid);
+        assert_eq!(metrics.poll_count, 3);
+        assert_eq!(mock.calls(), vec!["engine_newPayloadV1"; 3]);
+    }
+
+    #[tokio::test]
+    async fn latest_valid_hash_of_import() {
+        let hash = B256::with_last_byte(1);
+        let mock = MockEngineApi::default();
+        mock.push_success(PayloadStatus::new(PayloadStatusEnum::Valid, Some(hash)));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+        let provider = mock.provider();
+
+        let status = provider.new_payload_v1_wait(payload_v1(0)).await.unwrap();
+        assert_eq!(latest_valid_hash("engine_newPayloadV1", &status).unwrap(), hash);
+        let status = provider.new_payload_v1_wait(payload_v1(0)).await.unwrap();
+        let err = latest_valid_hash("engine_newPayloadV1", &status).unwrap_err();
+        assert!(matches!(err, EngineWaitError::MissingLatestValidHash { .. }), "{err:?}");
+        assert_eq!(
+            err.to_string(),
+            "engine_newPayloadV1 returned VALID without a latest valid hash"
+        );
+    }
+
+    #[test]
+    fn validate_post_merge_without_paris_block() {
+        validate_post_merge(&MAINNET, 15_537_394).unwrap();
+        validate_post_merge(&MAINNET, 15_537_393).unwrap_err();
+
+        // without the Paris block, only the blocks up to the DAO fork are known to be pre-merge
+        let chain_spec =
+            ChainSpec { paris_block_and_final_difficulty: None, ..(**MAINNET).clone() };
+        let err = validate_post_merge(&chain_spec, 1_920_000).unwrap_err();
+        assert!(matches!(err, EngineWaitError::PreMergeBlock { block_number: 1_920_000 }));
+        validate_post_merge(&chain_spec, 1_920_001).unwrap();
+    }
+
+    #[tokio::test]
+    async fn new_payload_verify_hashes() {
+        let mut payload = payload_v1(0);
+        payload.block_number = 7;
+        payload.block_hash = B256::with_last_byte(1);
+        let mock = MockEngineApi::default();
+        for hash in [Some(payload.block_hash), Some(B256::with_last_byte(2)), None] {
+            mock.push_success(PayloadStatus::new(PayloadStatusEnum::Valid, hash));
+        }
+        mock.push_success(PayloadStatus::new(PayloadStatusEnum::Valid, None));
+        let provider = mock.provider();
+        let config = WaitConfig::default().with_verify_hashes();
+
+        provider.new_payload_v1_wait_with(payload.clone(), &config).await.unwrap();
+        let err = provider.new_payload_v1_wait_with(payload.clone(), &config).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                EngineWaitError::HashMismatch { block_number: 7, actual: Some(actual), .. }
+                    if actual == B256::with_last_byte(2)
+            ),
+            "{err:?}"
+        );
+        let err = provider.new_payload_v1_wait_with(payload.clone(), &config).await.unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "engine_newPayloadV1 returned latest valid hash none for block 7, expected {}",
+                payload.block_hash
+            )
+        );
+
+        // hashes are not checked by default
+        provider.new_payload_v1_wait_with(payload, &WaitConfig::default()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn new_payload_invalid() {
+        let mock = MockEngineApi::with_statuses([
+            PayloadStatusEnum::Syncing,
+            PayloadStatusEnum::Invalid { validation_error: "bad block hash".to_string() },
+        ]);
+        let err = mock.provider().new_payload_v1_wait(payload_v1(0)).await.unwrap_err();
+        assert!(matches!(
+            &err,
+            EngineWaitError::Invalid { method: "engine_newPayloadV1", validation_error: Some(reason), .. }
+                if reason == "bad block hash"
+        ));
+        assert_eq!(mock.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn new_payload_accept_invalid() {
+        let invalid = PayloadStatusEnum::Invalid { validation_error: "bad block hash".to_string() };
+        let mock = MockEngineApi::with_statuses([PayloadStatusEnum::Syncing, invalid.clone()]);
+        mock.push_success(ForkchoiceUpdated::from_status(invalid.clone()));
+        let config = WaitConfig::default().with_accept_invalid();
+
+        let (status, metrics) =
+            mock.provider().new_payload_v1_wait_with(payload_v1(0), &config).await.unwrap();
+        assert_eq!(status.status, invalid);
+        assert_eq!(metrics.poll_count, 2);
+
+        let (updated, _) = mock
+            .provider()
+            .fork_choice_updated_v3_wait_with(ForkchoiceState::default(), None, &config)
+            .await
+            .unwrap();
+        assert_eq!(updated.payload_status.status, invalid);
+    }
+
+    #[tokio::test]
+    async fn new_payload_on_invalid() {
+        let invalid = PayloadStatusEnum::Invalid { validation_error: "bad block hash".to_string() };
+        let mock = MockEngineApi::with_statuses([invalid.clone(), invalid.clone()]);
+        let rejected = Arc::new(Mutex::new(Vec::new()));
+        let handler_rejected = rejected.clone();
+        let config = WaitConfig::default().with_on_invalid(move |method, status, params| {
+            handler_rejected.lock().unwrap().push((method, status.clone(), params.to_string()));
+        });
+
+        let err =
+            mock.provider().new_payload_v1_wait_with(payload_v1(7), &config).await.unwrap_err();
+        assert!(matches!(err, EngineWaitError::Invalid { method: "engine_newPayloadV1", .. }));
+        let (method, status, params) = rejected.lock().unwrap().pop().unwrap();
+        assert_eq!(method, "engine_newPayloadV1");
+        assert_eq!(status.status, invalid);
+        assert_eq!(params, serde_json::to_string(&(payload_v1(7),)).unwrap());
+
+        // accepted INVALID responses are not passed to the handler
+        let config = config.with_accept_invalid();
+        mock.provider().new_payload_v1_wait_with(payload_v1(7), &config).await.unwrap();
+        assert!(rejected.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_blobs_v1() {
+        use alloy_eips::eip4844::BYTES_PER_BLOB;
+
+        let blob =
+            BlobAndProofV1 { blob: vec![1; BYTES_PER_BLOB].into(), proof: Bytes48::repeat_byte(2) };
+        let mock = MockEngineApi::default();
+        mock.push_success([None, Some(blob.clone())]);
+        mock.push_success([Some(blob.clone())]);
+
+        let hashes = vec![B256::with_last_byte(1), B256::with_last_byte(2)];
+        let response = mock.provider().get_blobs_v1(hashes.clone()).await.unwrap();
+        assert_eq!(response.blobs, [None, Some(blob)]);
+        assert_eq!(response.available(), 1);
+        assert!(!response.is_complete());
+
+        let err = mock.provider().get_blobs_v1(hashes).await.unwrap_err();
+        assert!(
+            matches!(err, EngineWaitError::BlobCountMismatch { requested: 2, returned: 1, .. }),
+            "{err:?}"
+        );
+        assert_eq!(mock.calls(), ["engine_getBlobsV1", "engine_getBlobsV1"]);
+    }
+
+    #[tokio::test]
+    async fn new_payload_times_out() {
+        let mock = MockEngineApi::with_statuses(vec![PayloadStatusEnum::Syncing; 3]);
+        let config = WaitConfig::default().with_max_attempts(2);
+        let err =
+            mock.provider().new_payload_v1_wait_with(payload_v1(0), &config).await.unwrap_err();
+        assert!(matches!(err, EngineWaitError::Timeout { attempts: 2, .. }));
+        assert_eq!(mock.calls().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn new_payload_honors_backoff() {
+        let mock = MockEngineApi::with_statuses([
+            PayloadStatusEnum::Syncing,
+            PayloadStatusEnum::Accepted,
+            PayloadStatusEnum::Syncing,
+            PayloadStatusEnum::Valid,
+        ]);
+        let config = WaitConfig::default()
+            .with_poll_interval(Duration::from_millis(10))
+            .with_exponential_backoff(Duration::from_millis(20));
+
+        // The calls are re-sent after 10ms, 20ms and 20ms.
+        let start = Instant::now();
+        let (_, metrics) =
+            mock.provider().new_payload_v1_wait_with(payload_v1(0), &config).await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!(metrics.valid_after >= Duration::from_millis(50));
+        assert_eq!(metrics.poll_count, 4);
+    }
+
+    #[tokio::test]
+    async fn fork_choice_updated_build_returns_payload_id() {
+        let payload_id = PayloadId::new([1; 8]);
+        let valid = PayloadStatus::from_status(PayloadStatusEnum::Valid);
+        let mock = MockEngineApi::default();
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing));
+        mock.push_success(ForkchoiceUpdated::new(valid.clone()).with_payload_id(payload_id));
+        let head = B256::with_last_byte(1);
+        mock.push_success(ForkchoiceUpdated::new(valid).with_latest_valid_hash(head));
+
+        let provider = mock.provider();
+        let state = ForkchoiceState::default();
+        assert_eq!(provider.fork_choice_updated_v3_build(state, None).await.unwrap(), payload_id);
+
+        // the response is attached to the error
+        let err = provider.fork_choice_updated_v3_build(state, None).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("engine_forkchoiceUpdatedV3 did not return a payload id"));
+        let EngineWaitError::MissingPayloadId { method: "engine_forkchoiceUpdatedV3", response } =
+            err
+        else {
+            panic!("unexpected error: {err:?}")
+        };
+        assert_eq!(response.payload_status.latest_valid_hash, Some(head));
+        assert_eq!(mock.calls(), vec!["engine_forkchoiceUpdatedV3"; 3]);
+    }
+
+    #[tokio::test]
+    async fn fork_choice_updated_dispatches_by_version() {
+        let mock = MockEngineApi::default();
+        let provider = mock.provider();
+        for version in [
+            EngineApiMessageVersion::V1,
+            EngineApiMessageVersion::V2,
+            EngineApiMessageVersion::V3,
+            EngineApiMessageVersion::V4,
+        ] {
+            mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+            provider
+                .fork_choice_updated_wait(version, ForkchoiceState::default(), None)
+                .await
+                .unwrap();
         }
-        EngineApiMessageVersion::V2 => {
-            provider.fork_choice_updated_v2_wait(forkchoice_state, payload_attributes).await
+        assert_eq!(
+            mock.calls(),
+            vec![
+                "engine_forkchoiceUpdatedV1",
+                "engine_forkchoiceUpdatedV2",
+                "engine_forkchoiceUpdatedV3",
+                "engine_forkchoiceUpdatedV3",
+            ]
+        );
+    }
+
+    fn payload_v3(timestamp: u64) -> ExecutionPayloadV3 {
+        ExecutionPayloadV3 {
+            payload_inner: reth_rpc_types::ExecutionPayloadV2 {
+                payload_inner: payload_v1(timestamp),
+                withdrawals: Vec::new(),
+            },
+            blob_gas_used: 0,
+            excess_blob_gas: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn get_payload_polls_until_payload_has_transactions() {
+        use alloy_rpc_types_engine::BlobsBundleV1;
+
+        let envelope = |transactions: Vec<Bytes>| {
+            let mut execution_payload = payload_v3(0);
+            execution_payload.payload_inner.payload_inner.transactions = transactions;
+            ExecutionPayloadEnvelopeV3 {
+                execution_payload,
+                block_value: Default::default(),
+                blobs_bundle: BlobsBundleV1 {
+                    commitments: Vec::new(),
+                    proofs: Vec::new(),
+                    blobs: Vec::new(),
+                },
+                should_override_builder: false,
+            }
+        };
+        let payload_id = PayloadId::new([1; 8]);
+        let build = |mock: &MockEngineApi, config| {
+            mock.push_success(
+                ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid)
+                    .with_payload_id(payload_id),
+            );
+            let provider = mock.provider();
+            let attributes = PayloadAttributes {
+                timestamp: 0,
+                prev_randao: B256::ZERO,
+                suggested_fee_recipient: Default::default(),
+                withdrawals: Some(Vec::new()),
+                parent_beacon_block_root: Some(B256::ZERO),
+            };
+            async move {
+                provider.get_payload_v3_wait(ForkchoiceState::default(), attributes, &config).await
+            }
+        };
+
+        // the empty payload is polled again with the same id, without another forkchoice update
+        let mock = MockEngineApi::default();
+        let config = WaitConfig::default().with_max_attempts(3);
+        let fut = build(&mock, config);
+        mock.push_success(envelope(Vec::new()));
+        mock.push_success(envelope(vec![Bytes::from_static(&[1])]));
+        let built = fut.await.unwrap();
+        assert_eq!(built.payload_id, payload_id);
+        assert_eq!(built.poll_count, 2);
+        assert_eq!(built.payload.transaction_count(), 1);
+        assert_eq!(
+            mock.calls(),
+            ["engine_forkchoiceUpdatedV3", "engine_getPayloadV3", "engine_getPayloadV3"]
+        );
+
+        // once all attempts are made with only empty payloads, the build fails
+        let mock = MockEngineApi::default();
+        let fut = build(&mock, WaitConfig::default().with_max_attempts(2));
+        mock.push_success(envelope(Vec::new()));
+        mock.push_success(envelope(Vec::new()));
+        let err = fut.await.unwrap_err();
+        assert!(matches!(err, EngineWaitError::EmptyPayload { attempts: 2, .. }), "{err}");
+
+        // without a bound, empty payloads are still polled until the default deadline
+        let mock = MockEngineApi::default();
+        let fut = build(&mock, WaitConfig::default());
+        mock.push_success(envelope(Vec::new()));
+        mock.push_success(envelope(vec![Bytes::from_static(&[1])]));
+        assert_eq!(fut.await.unwrap().poll_count, 2);
+    }
+
+    #[tokio::test]
+    async fn import_block_forkchoice_update_after_accepted() {
+        let mock = MockEngineApi::default();
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Accepted));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Syncing));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+
+        let sidecar = CancunPayloadFields {
+            parent_beacon_block_root: B256::ZERO,
+            versioned_hashes: Vec::new(),
+        };
+        let imported = mock
+            .provider()
+            .import_block_v3(
+                payload_v3(0),
+                sidecar,
+                ForkchoiceState::default(),
+                &WaitConfig::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(imported.new_payload_status.status, PayloadStatusEnum::Accepted);
+        assert_eq!(imported.fcu_poll_count, 2);
+        assert_eq!(
+            mock.calls(),
+            vec![
+                "engine_forkchoiceUpdatedV3",
+                "engine_newPayloadV3",
+                "engine_forkchoiceUpdatedV3",
+                "engine_forkchoiceUpdatedV3",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn import_block_invalid_payload() {
+        let mock = MockEngineApi::default();
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+            validation_error: "bad state root".to_string(),
+        }));
+
+        let sidecar = CancunPayloadFields {
+            parent_beacon_block_root: B256::ZERO,
+            versioned_hashes: Vec::new(),
+        };
+        let err = mock
+            .provider()
+            .import_block_v3(
+                payload_v3(0),
+                sidecar,
+                ForkchoiceState::default(),
+                &WaitConfig::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineWaitError::Invalid { method: "engine_newPayloadV3", .. }));
+        assert_eq!(mock.calls(), vec!["engine_forkchoiceUpdatedV3", "engine_newPayloadV3"]);
+    }
+
+    #[tokio::test]
+    async fn import_block_updates_forkchoice_to_parent_first() {
+        let mock = MockEngineApi::default();
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+        mock.push_success(ForkchoiceUpdated::from_status(PayloadStatusEnum::Valid));
+
+        let parent = B256::with_last_byte(1);
+        let mut payload = payload_v3(0);
+        payload.payload_inner.payload_inner.parent_hash = parent;
+        payload.payload_inner.payload_inner.block_hash = B256::with_last_byte(2);
+        // the new block is also the safe and finalized block, which the engine does not know
+        // before the payload is imported
+        let state = ForkchoiceState {
+            head_block_hash: B256::with_last_byte(2),
+            safe_block_hash: B256::with_last_byte(2),
+            finalized_block_hash: B256::with_last_byte(2),
+        };
+        let sidecar = CancunPayloadFields {
+            parent_beacon_block_root: B256::ZERO,
+            versioned_hashes: Vec::new(),
+        };
+        mock.provider()
+            .import_block_v3(payload, sidecar, state, &WaitConfig::default())
+            .await
+            .unwrap();
+
+        let params = mock.params();
+        let parent_state = ForkchoiceState {
+            head_block_hash: parent,
+            safe_block_hash: parent,
+            finalized_block_hash: parent,
+        };
+        assert_eq!(params[0][0], serde_json::to_value(parent_state).unwrap());
+        assert_eq!(params[2][0], serde_json::to_value(state).unwrap());
+    }
+
+    #[tokio::test]
+    async fn deliver_payload_accepts_invalid() {
+        let mock = MockEngineApi::with_statuses([PayloadStatusEnum::Invalid {
+            validation_error: "bad state root".to_string(),
+        }]);
+        let sidecar = CancunPayloadFields {
+            parent_beacon_block_root: B256::ZERO,
+            versioned_hashes: Vec::new(),
+        };
+        let config = WaitConfig::default().with_accept_invalid();
+        let (status, _) =
+            mock.provider().deliver_payload_v3(payload_v3(0), sidecar, &config).await.unwrap();
+        assert!(status.status.is_invalid());
+    }
+
+    #[tokio::test]
+    async fn deliver_payload_verify_hashes() {
+        let payload = payload_v3(0);
+        let expected = payload.payload_inner.payload_inner.block_hash;
+        let mock = MockEngineApi::default();
+        mock.push_success(PayloadStatus::new(PayloadStatusEnum::Syncing, None));
+        mock.push_success(PayloadStatus::new(PayloadStatusEnum::Valid, Some(expected)));
+        mock.push_success(PayloadStatus::new(PayloadStatusEnum::Valid, Some(B256::repeat_byte(2))));
+        let provider = mock.provider();
+        let sidecar = CancunPayloadFields {
+            parent_beacon_block_root: B256::ZERO,
+            versioned_hashes: Vec::new(),
+        };
+        let config = WaitConfig::default().with_verify_hashes();
+
+        // a payload that is not executed yet has no latest valid hash to check
+        for _ in 0..2 {
+            provider.deliver_payload_v3(payload.clone(), sidecar.clone(), &config).await.unwrap();
         }
-        EngineApiMessageVersion::V1 => {
-            provider.fork_choice_updated_v1_wait(forkchoice_state, payload_attributes).await
+        let err = provider.deliver_payload_v3(payload, sidecar, &config).await.unwrap_err();
+        assert!(
+            matches!(
+                err,
+                EngineWaitError::HashMismatch {
+                    method: "engine_newPayloadV3",
+                    actual: Some(actual),
+                    ..
+                } if actual == B256::repeat_byte(2)
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn new_payload_requires_cancun_fields() {
+        let mock = MockEngineApi::default();
+        let err = mock
+            .provider()
+            .new_payload_wait(ExecutionPayload::V3(payload_v3(0)), ExecutionPayloadSidecar::none())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            EngineWaitError::MissingCancunFields { method: "engine_newPayloadV3" }
+        ));
+        assert!(mock.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn new_payload_sidecar_requests() {
+        use alloy_eips::{
+            eip6110::DepositRequest, eip7002::WithdrawalRequest, eip7251::ConsolidationRequest,
+        };
+
+        let payload = ExecutionPayloadV4 {
+            payload_inner: payload_v3(0),
+            deposit_requests: vec![DepositRequest::default()],
+            withdrawal_requests: Vec::new(),
+        };
+        let withdrawal = WithdrawalRequest { amount: 1, ..Default::default() };
+        let sidecar = ExecutionPayloadSidecar::v4(
+            vec![B256::with_last_byte(1)],
+            B256::with_last_byte(2),
+            vec![Request::WithdrawalRequest(withdrawal)],
+        );
+        assert_eq!(sidecar.cancun().unwrap().parent_beacon_block_root, B256::with_last_byte(2));
+
+        let mock = MockEngineApi::with_statuses([PayloadStatusEnum::Valid]);
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let captured = requests.clone();
+        let provider = RootProvider::<_, alloy_provider::network::Ethereum>::new(RpcClient::new(
+            tower::service_fn(move |req: RequestPacket| {
+                let RequestPacket::Single(single) = &req else { unreachable!() };
+                captured.lock().unwrap().push(single.params().unwrap().get().to_string());
+                let mut mock = mock.clone();
+                tower::Service::call(&mut mock, req)
+            }),
+            true,
+        ));
+        provider.new_payload_wait(ExecutionPayload::V4(payload.clone()), sidecar).await.unwrap();
+
+        let (sent, _, _): (ExecutionPayloadV4, Vec<B256>, B256) =
+            serde_json::from_str(&requests.lock().unwrap()[0]).unwrap();
+        assert!(sent.deposit_requests.is_empty());
+        assert_eq!(sent.withdrawal_requests, [withdrawal]);
+
+        let sidecar = ExecutionPayloadSidecar::v4(
+            Vec::new(),
+            B256::ZERO,
+            vec![Request::ConsolidationRequest(ConsolidationRequest::default())],
+        );
+        let err =
+            provider.new_payload_wait(ExecutionPayload::V4(payload), sidecar).await.unwrap_err();
+        assert_eq!(err.to_string(), "engine_newPayloadV4 does not support consolidation requests");
+    }
+
+    #[test]
+    fn sidecar_from_block() {
+        use alloy_eips::eip6110::DepositRequest;
+        use reth_chainspec::{ChainSpecBuilder, ForkCondition, Hardfork};
+        use reth_primitives::{Block, Requests, Signature, Transaction, TxEip1559, TxEip4844};
+
+        let tx = |transaction| {
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+        };
+        let blob_tx = |blob_versioned_hashes| {
+            tx(Transaction::Eip4844(TxEip4844 { blob_versioned_hashes, ..Default::default() }))
+        };
+        let deposit = Request::DepositRequest(DepositRequest::default());
+        let block = |timestamp, parent_beacon_block_root| {
+            Block {
+                header: Header { timestamp, parent_beacon_block_root, ..Default::default() },
+                body: vec![
+                    blob_tx(vec![B256::with_last_byte(2)]),
+                    tx(Transaction::Eip1559(TxEip1559::default())),
+                    blob_tx(vec![B256::with_last_byte(3), B256::with_last_byte(4)]),
+                ],
+                requests: Some(Requests(vec![deposit])),
+                ..Default::default()
+            }
+            .seal_slow()
+        };
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(10))
+            .with_fork(Hardfork::Prague, ForkCondition::Timestamp(20))
+            .build();
+        let root = B256::with_last_byte(1);
+        let hashes =
+            vec![B256::with_last_byte(2), B256::with_last_byte(3), B256::with_last_byte(4)];
+
+        let sidecar =
+            ExecutionPayloadSidecar::from_block(&block(9, Some(root)), &chain_spec).unwrap();
+        assert_eq!(sidecar, ExecutionPayloadSidecar::none());
+
+        let sidecar =
+            ExecutionPayloadSidecar::from_block(&block(10, Some(root)), &chain_spec).unwrap();
+        assert_eq!(sidecar, ExecutionPayloadSidecar::v3(hashes.clone(), root));
+
+        let sidecar =
+            ExecutionPayloadSidecar::from_block(&block(20, Some(root)), &chain_spec).unwrap();
+        assert_eq!(sidecar, ExecutionPayloadSidecar::v4(hashes, root, vec![deposit]));
+
+        // a block without requests has an empty list of requests after Prague
+        let mut no_requests = block(20, Some(root)).unseal();
+        no_requests.requests = None;
+        let sidecar =
+            ExecutionPayloadSidecar::from_block(&no_requests.seal_slow(), &chain_spec).unwrap();
+        assert_eq!(sidecar.requests(), Some(&[][..]));
+
+        let err = ExecutionPayloadSidecar::from_block(&block(20, None), &chain_spec).unwrap_err();
+        assert!(
+            matches!(err, EngineWaitError::MissingCancunFields { method: "engine_newPayloadV4" }),
+            "{err:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn new_payload_derives_versioned_hashes() {
+        use reth_primitives::{Block, Signature, Transaction, TxEip1559, TxEip4844};
+
+        let tx = |transaction| {
+            TransactionSigned::from_transaction_and_signature(transaction, Signature::default())
+        };
+        let blob_tx = |blob_versioned_hashes| {
+            tx(Transaction::Eip4844(TxEip4844 { blob_versioned_hashes, ..Default::default() }))
+        };
+        let block = Block {
+            header: Header {
+                parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                blob_gas_used: Some(0),
+                excess_blob_gas: Some(0),
+                ..Default::default()
+            },
+            body: vec![
+                blob_tx(vec![B256::with_last_byte(2), B256::with_last_byte(3)]),
+                tx(Transaction::Eip1559(TxEip1559::default())),
+                blob_tx(vec![B256::with_last_byte(4)]),
+            ],
+            withdrawals: Some(Default::default()),
+            ..Default::default()
         }
+        .seal_slow();
+        let (payload, versioned_hashes, _) = crate::replay::block_to_execution_payload_v3(&block);
+
+        assert_eq!(
+            payload_versioned_hashes(&payload.payload_inner.payload_inner).unwrap(),
+            versioned_hashes
+        );
+        assert_eq!(versioned_hashes.len(), 3);
+
+        let mock = MockEngineApi::with_statuses([PayloadStatusEnum::Valid]);
+        mock.provider().new_payload_v3_wait_auto(payload.clone(), &block.header).await.unwrap();
+        assert_eq!(mock.calls(), ["engine_newPayloadV3"]);
+
+        let err = mock
+            .provider()
+            .new_payload_v3_wait_auto(payload.clone(), &Header::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            EngineWaitError::MissingCancunFields { method: "engine_newPayloadV3" }
+        ));
+
+        let mut malformed = payload;
+        malformed.payload_inner.payload_inner.transactions[1] = vec![0x02, 0xc0].into();
+        let err =
+            mock.provider().new_payload_v3_wait_auto(malformed, &block.header).await.unwrap_err();
+        assert!(matches!(err, EngineWaitError::InvalidTransaction { index: 1, .. }), "{err:?}");
+        assert_eq!(mock.calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn payload_shape() {
+        use reth_primitives::{
+            constants::{EMPTY_TRANSACTIONS, EMPTY_WITHDRAWALS},
+            EMPTY_OMMER_ROOT_HASH,
+        };
+
+        let cancun = 1710338135;
+        let block = |timestamp| {
+            reth_primitives::Block {
+                header: Header {
+                    timestamp,
+                    base_fee_per_gas: Some(7),
+                    ommers_hash: EMPTY_OMMER_ROOT_HASH,
+                    transactions_root: EMPTY_TRANSACTIONS,
+                    withdrawals_root: Some(EMPTY_WITHDRAWALS),
+                    parent_beacon_block_root: Some(B256::with_last_byte(1)),
+                    blob_gas_used: Some(0),
+                    excess_blob_gas: Some(0),
+                    ..Default::default()
+                },
+                withdrawals: Some(Default::default()),
+                ..Default::default()
+            }
+            .seal_slow()
+        };
+        let (payload, versioned_hashes, root) =
+            crate::replay::block_to_execution_payload_v3(&block(cancun));
+        let shape = |payload: ExecutionPayloadV3, root| {
+            validate_payload_shape(&ExecutionPayload::V3(payload), root, &MAINNET)
+        };
+        shape(payload.clone(), Some(root)).unwrap();
+
+        let err = shape(payload.clone(), None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "engine_newPayloadV3 payload is malformed: cancun fields missing in post-cancun payload"
+        );
+
+        let mut wrong_hash = payload.clone();
+        wrong_hash.payload_inner.payload_inner.block_hash = B256::with_last_byte(2);
+        let err = shape(wrong_hash.clone(), Some(root)).unwrap_err();
+        assert!(matches!(
+            err,
+            EngineWaitError::MalformedPayload { error: PayloadError::BlockHash { .. }, .. }
+        ));
+
+        let (pre_cancun, _, _) = crate::replay::block_to_execution_payload_v3(&block(cancun - 1));
+        let err = shape(pre_cancun.clone(), Some(root)).unwrap_err();
+        assert!(matches!(
+            err,
+            EngineWaitError::MalformedPayload {
+                method: "engine_newPayloadV3",
+                error: PayloadError::PreCancunBlockWithBlobGasUsed,
+            }
+        ));
+        let err = validate_payload_shape(
+            &ExecutionPayload::V1(pre_cancun.payload_inner.payload_inner),
+            None,
+            &MAINNET,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            EngineWaitError::MalformedPayload {
+                method: "engine_newPayloadV1",
+                error: PayloadError::PostShanghaiBlockWithoutWitdrawals,
+            }
+        ));
+
+        // the payload is only checked if enabled
+        let mock = MockEngineApi::with_statuses([PayloadStatusEnum::Valid]);
+        let config = WaitConfig::default().with_payload_validation(MAINNET.clone());
+        let err = mock
+            .provider()
+            .new_payload_v3_wait_with(wrong_hash.clone(), versioned_hashes.clone(), root, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineWaitError::MalformedPayload { .. }));
+        assert!(mock.calls().is_empty());
+
+        let config = WaitConfig::default().with_chain_spec(MAINNET.clone());
+        mock.provider()
+            .new_payload_v3_wait_with(wrong_hash, versioned_hashes, root, &config)
+            .await
+            .unwrap();
+        assert_eq!(mock.calls(), ["engine_newPayloadV3"]);
+    }
+
+    #[test]
+    fn withdrawals_match_shanghai() {
+        let shanghai = 1681338455;
+        let payload = |timestamp, withdrawals| ExecutionPayloadInputV2 {
+            execution_payload: payload_v1(timestamp),
+            withdrawals,
+        };
+
+        validate_withdrawals(&MAINNET, &payload(shanghai - 1, None)).unwrap();
+        validate_withdrawals(&MAINNET, &payload(shanghai, Some(Vec::new()))).unwrap();
+
+        let err = validate_withdrawals(&MAINNET, &payload(shanghai, None)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "engine_newPayloadV2 payload with timestamp 1681338455 must contain withdrawals"
+        );
+        let err = validate_withdrawals(&MAINNET, &payload(shanghai - 1, Some(Vec::new())));
+        assert!(matches!(
+            err,
+            Err(EngineWaitError::WithdrawalsMismatch { shanghai_active: false, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn parent_beacon_block_root_after_cancun() {
+        let cancun = 1710338135;
+        let payload = |timestamp| ExecutionPayloadV3 {
+            payload_inner: reth_rpc_types::ExecutionPayloadV2 {
+                payload_inner: payload_v1(timestamp),
+                withdrawals: Vec::new(),
+            },
+            blob_gas_used: 0,
+            excess_blob_gas: 0,
+        };
+
+        validate_parent_beacon_block_root(&MAINNET, &payload(cancun - 1), B256::ZERO).unwrap();
+        validate_parent_beacon_block_root(&MAINNET, &payload(cancun), B256::with_last_byte(1))
+            .unwrap();
+        let err =
+            validate_parent_beacon_block_root(&MAINNET, &payload(cancun), B256::ZERO).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "engine_newPayloadV3 payload with timestamp 1710338135 must have a non-zero parent \
+             beacon block root"
+        );
+
+        // the root is checked before the payload is sent
+        let mock = MockEngineApi::with_statuses([]);
+        let config = WaitConfig::default().with_chain_spec(MAINNET.clone());
+        let err = mock
+            .provider()
+            .new_payload_v3_wait_with(payload(cancun), Vec::new(), B256::ZERO, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineWaitError::ZeroParentBeaconBlockRoot { .. }), "{err:?}");
+        assert!(mock.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn execution_requests_match_prague() {
+        use alloy_eips::{eip6110::DepositRequest, eip7002::WithdrawalRequest};
+        use reth_chainspec::{ChainSpecBuilder, ForkCondition, Hardfork};
+
+        let chain_spec = Arc::new(
+            ChainSpecBuilder::mainnet()
+                .with_fork(Hardfork::Prague, ForkCondition::Timestamp(20))
+                .build(),
+        );
+        let deposit = Request::DepositRequest(DepositRequest::default());
+        let withdrawal = Request::WithdrawalRequest(WithdrawalRequest::default());
+
+        // a payload after Prague without requests is valid
+        validate_execution_requests(&[], &chain_spec, 20).unwrap();
+        validate_execution_requests(&[], &chain_spec, 19).unwrap();
+        validate_execution_requests(&[deposit, deposit, withdrawal], &chain_spec, 20).unwrap();
+
+        let err = validate_execution_requests(&[deposit], &chain_spec, 19).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "engine_newPayloadV4 payload with timestamp 19 must not contain requests before Prague"
+        );
+        let err = validate_execution_requests(&[deposit, withdrawal, deposit], &chain_spec, 20)
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                EngineWaitError::UnorderedRequests {
+                    index: 2,
+                    request_type: 0,
+                    previous_type: 1,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+
+        // the requests are checked before the payload is sent
+        let payload = |timestamp| ExecutionPayloadV4 {
+            payload_inner: payload_v3(timestamp),
+            deposit_requests: Vec::new(),
+            withdrawal_requests: Vec::new(),
+        };
+        let mock = MockEngineApi::with_statuses([PayloadStatusEnum::Valid]);
+        let provider = mock.provider();
+        let config = WaitConfig::default().with_chain_spec(chain_spec);
+        let sidecar = |requests| ExecutionPayloadSidecar::v4(Vec::new(), B256::ZERO, requests);
+        let err = provider
+            .new_payload_wait_with(
+                ExecutionPayload::V4(payload(20)),
+                sidecar(vec![withdrawal, deposit]),
+                &config,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineWaitError::UnorderedRequests { index: 1, .. }), "{err:?}");
+        let err = provider
+            .new_payload_wait_with(
+                ExecutionPayload::V4(payload(19)),
+                sidecar(vec![deposit]),
+                &config,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, EngineWaitError::RequestsBeforePrague { .. }), "{err:?}");
+        assert!(mock.calls().is_empty());
+
+        provider
+            .new_payload_wait_with(ExecutionPayload::V4(payload(20)), sidecar(Vec::new()), &config)
+            .await
+            .unwrap();
+        assert_eq!(mock.calls(), ["engine_newPayloadV4"]);
+    }
+
+    #[tokio::test]
+    async fn new_payload_v4_falls_back_to_v3() {
+        use alloy_eips::eip6110::DepositRequest;
+        use reth_chainspec::{ChainSpecBuilder, ForkCondition, Hardfork};
+
+        let payload = |timestamp, deposit_requests| {
+            ExecutionPayload::V4(ExecutionPayloadV4 {
+                payload_inner: payload_v3(timestamp),
+                deposit_requests,
+                withdrawal_requests: Vec::new(),
+            })
+        };
+        let sidecar = || ExecutionPayloadSidecar::v3(Vec::new(), B256::with_last_byte(1));
+        let method_not_found = || {
+            let mock = MockEngineApi::default();
+            mock.push_error(METHOD_NOT_FOUND_CODE, "the method engine_newPayloadV4 does not exist");
+            mock.push_success(PayloadStatus::from_status(PayloadStatusEnum::Valid));
+            mock
+        };
+
+        // without the fallback, the error is returned as is
+        let mock = method_not_found();
+        let err = mock
+            .provider()
+            .new_payload_wait_with(payload(10, Vec::new()), sidecar(), &WaitConfig::default())
+            .await
+            .unwrap_err();
+        assert!(err.is_method_not_found(), "{err:?}");
+        assert_eq!(mock.calls(), ["engine_newPayloadV4"]);
+
+        let config = WaitConfig::default().with_version_fallback();
+        let mock = method_not_found();
+        let (status, _) = mock
+            .provider()
+            .new_payload_wait_with(payload(10, Vec::new()), sidecar(), &config)
+            .await
+            .unwrap();
+        assert_eq!(status.status, PayloadStatusEnum::Valid);
+        assert_eq!(mock.calls(), ["engine_newPayloadV4", "engine_newPayloadV3"]);
+
+        // payloads with requests or after Prague cannot be downgraded
+        let mock = method_not_found();
+        let err = mock
+            .provider()
+            .new_payload_wait_with(payload(10, vec![DepositRequest::default()]), sidecar(), &config)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(
+                err,
+                EngineWaitError::UnsupportedVersion { reason: "it contains requests", .. }
+            ),
+            "{err:?}"
+        );
+        assert_eq!(mock.calls(), ["engine_newPayloadV4"]);
+
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Prague, ForkCondition::Timestamp(20))
+            .build();
+        let config = config.with_chain_spec(Arc::new(chain_spec));
+        let mock = method_not_found();
+        let err = mock
+            .provider()
+            .new_payload_wait_with(payload(20, Vec::new()), sidecar(), &config)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "engine does not support engine_newPayloadV4, and block 0 cannot be sent with an \
+             earlier version because it is after Prague"
+        );
+    }
+
+    #[test]
+    fn invalid_error_extracts_validation_error() {
+        let status = PayloadStatus::from_status(PayloadStatusEnum::Invalid {
+            validation_error: "bad state root".to_string(),
+        });
+        let err = EngineWaitError::invalid("engine_newPayloadV3", status);
+        assert!(matches!(
+            &err,
+            EngineWaitError::Invalid { validation_error: Some(reason), .. } if reason == "bad state root"
+        ));
+        assert_eq!(err.to_string(), "engine_newPayloadV3 returned INVALID: bad state root");
+    }
+
+    #[test]
+    fn serialized_params_match_tuple() {
+        let state = ForkchoiceState::default();
+        let attributes: Option<PayloadAttributes> = None;
+        let params = SerializedParams::new((state, &attributes)).unwrap();
+        assert_eq!(
+            serde_json::to_string(&params).unwrap(),
+            serde_json::to_string(&(state, attributes)).unwrap()
+        );
     }
 }
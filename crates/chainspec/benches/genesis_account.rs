@@ -0,0 +1,45 @@
+#![allow(missing_docs)]
+use alloy_genesis::{Genesis, GenesisAccount};
+use alloy_primitives::{Address, U256};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use reth_chainspec::{Chain, ChainSpecBuilder};
+
+/// The number of prefunded accounts of the genesis, like a custom rollup with many accounts.
+const ACCOUNTS: u64 = 10_000;
+
+/// Benchmarks looking up accounts of a genesis with [`ACCOUNTS`] accounts, with the index of
+/// [`reth_chainspec::ChainSpec::genesis_account_index`] and with
+/// [`reth_chainspec::ChainSpec::genesis_account`], which looks up the alloc map of the genesis.
+pub fn genesis_account(c: &mut Criterion) {
+    let addresses =
+        (0..ACCOUNTS).map(|i| Address::from_word(U256::from(i).into())).collect::<Vec<_>>();
+    let alloc = addresses
+        .iter()
+        .map(|address| (*address, GenesisAccount::default().with_balance(U256::from(1))));
+    let spec = ChainSpecBuilder::default()
+        .chain(Chain::from_id(1337))
+        .genesis(Genesis::default().extend_accounts(alloc))
+        .build();
+    // build the index before measuring
+    let index = spec.genesis_account_index();
+
+    let mut group = c.benchmark_group("genesis_account");
+    group.bench_function("index", |b| {
+        b.iter(|| {
+            for address in &addresses {
+                black_box(index.get(black_box(address)));
+            }
+        })
+    });
+    group.bench_function("alloc", |b| {
+        b.iter(|| {
+            for address in &addresses {
+                black_box(spec.genesis_account(black_box(address)));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, genesis_account);
+criterion_main!(benches);
@@ -8,6 +8,14 @@ pub(crate) const MAINNET_DEPOSIT_CONTRACT: DepositContract = DepositContract::ne
     b256!("649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5"),
 );
 
+/// The target number of blobs per block from Prague, see
+/// [EIP-7691](https://eips.ethereum.org/EIPS/eip-7691).
+pub(crate) const PRAGUE_TARGET_BLOBS_PER_BLOCK: u64 = 6;
+
+/// The maximum number of blobs per block from Prague, see
+/// [EIP-7691](https://eips.ethereum.org/EIPS/eip-7691).
+pub(crate) const PRAGUE_MAX_BLOBS_PER_BLOCK: u64 = 9;
+
 #[cfg(feature = "optimism")]
 pub(crate) mod optimism;
 
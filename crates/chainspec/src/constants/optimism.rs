@@ -1,4 +1,7 @@
-use alloy_eips::eip1559::BaseFeeParams;
+use crate::{OpRollupGenesis, OpSystemConfig};
+use alloy_eips::{eip1559::BaseFeeParams, BlockNumHash};
+use alloy_primitives::{address, b256, Address};
+use reth_ethereum_forks::Hardfork;
 use reth_primitives_traits::constants::{
     BASE_SEPOLIA_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
     OP_MAINNET_EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR_CANYON,
@@ -6,45 +9,139 @@ use reth_primitives_traits::constants::{
     OP_MAINNET_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
     OP_SEPOLIA_EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR_CANYON,
     OP_SEPOLIA_EIP1559_DEFAULT_BASE_FEE_MAX_CHANGE_DENOMINATOR,
-    OP_SEPOLIA_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
+    OP_SEPOLIA_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER, OP_SYSTEM_TX_FROM_ADDR,
 };
 
-/// Get the base fee parameters for Base Sepolia.
-pub(crate) const BASE_SEPOLIA_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
+/// The chain id of OP mainnet.
+pub const OP_MAINNET_CHAIN_ID: u64 = 10;
+
+/// The chain id of OP Sepolia.
+pub const OP_SEPOLIA_CHAIN_ID: u64 = 11155420;
+
+/// The chain id of Base mainnet.
+pub const BASE_MAINNET_CHAIN_ID: u64 = 8453;
+
+/// The chain id of Base Sepolia.
+pub const BASE_SEPOLIA_CHAIN_ID: u64 = 84532;
+
+/// The address of the `L1Block` predeploy, which holds the attributes of the latest L1 block and
+/// is updated by the first transaction of every block.
+///
+/// The address is the same on all OP stack chains.
+pub const L1_BLOCK_CONTRACT: Address = address!("4200000000000000000000000000000000000015");
+
+/// The sender of the L1 attributes deposit transaction, which is the first transaction of every
+/// block and calls the [`L1_BLOCK_CONTRACT`].
+///
+/// The address is the same on all OP stack chains.
+pub const L1_ATTRIBUTES_DEPOSITOR: Address = OP_SYSTEM_TX_FROM_ADDR;
+
+/// The address of the `SequencerFeeVault` predeploy, which receives the priority fees of all
+/// transactions.
+///
+/// The vault already existed before Bedrock, which kept its address and made it a predeploy of
+/// all OP stack chains.
+pub const SEQUENCER_FEE_VAULT: Address = address!("4200000000000000000000000000000000000011");
+
+/// The address of the `BaseFeeVault` predeploy, which receives the base fees of all transactions
+/// instead of burning them.
+///
+/// The vault was introduced with [`Hardfork::Bedrock`], and is the same on all OP stack chains.
+pub const BASE_FEE_VAULT: Address = address!("4200000000000000000000000000000000000019");
+
+/// The address of the `L1FeeVault` predeploy, which receives the L1 data fees of all transactions.
+///
+/// The vault was introduced with [`Hardfork::Bedrock`], and is the same on all OP stack chains.
+pub const L1_FEE_VAULT: Address = address!("420000000000000000000000000000000000001a");
+
+/// The address of the [RIP-7212](https://github.com/ethereum/RIPs/blob/master/RIPS/rip-7212.md)
+/// `P256VERIFY` precompile, which is active from
+/// [`Hardfork::Fjord`](reth_ethereum_forks::Hardfork::Fjord).
+pub const P256VERIFY_PRECOMPILE: Address = address!("0000000000000000000000000000000000000100");
+
+/// The base fee parameters of Base Sepolia before [`Hardfork::Canyon`]: a max change
+/// denominator of 50 and an elasticity multiplier of 10.
+pub const BASE_SEPOLIA_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
     max_change_denominator: OP_SEPOLIA_EIP1559_DEFAULT_BASE_FEE_MAX_CHANGE_DENOMINATOR,
     elasticity_multiplier: BASE_SEPOLIA_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
 };
 
-/// Get the base fee parameters for Base Sepolia (post Canyon).
-pub(crate) const BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
+/// The base fee parameters of Base Sepolia from [`Hardfork::Canyon`]: the max change denominator
+/// is raised to 250, the elasticity multiplier stays at 10.
+pub const BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
     max_change_denominator: OP_SEPOLIA_EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR_CANYON,
     elasticity_multiplier: BASE_SEPOLIA_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
 };
 
-/// Get the base fee parameters for Optimism Sepolia.
-pub(crate) const OP_SEPOLIA_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
+/// The base fee parameters of OP Sepolia before [`Hardfork::Canyon`]: a max change denominator
+/// of 50 and an elasticity multiplier of 6.
+pub const OP_SEPOLIA_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
     max_change_denominator: OP_SEPOLIA_EIP1559_DEFAULT_BASE_FEE_MAX_CHANGE_DENOMINATOR,
     elasticity_multiplier: OP_SEPOLIA_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
 };
 
-/// Get the base fee parameters for Optimism Sepolia (post Canyon).
-pub(crate) const OP_SEPOLIA_CANYON_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
+/// The base fee parameters of OP Sepolia from [`Hardfork::Canyon`]: the max change denominator
+/// is raised to 250, the elasticity multiplier stays at 6.
+pub const OP_SEPOLIA_CANYON_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
     max_change_denominator: OP_SEPOLIA_EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR_CANYON,
     elasticity_multiplier: OP_SEPOLIA_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
 };
 
-/// Get the base fee parameters for Optimism Mainnet.
-pub(crate) const OP_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
+/// The base fee parameters of OP mainnet and Base mainnet before [`Hardfork::Canyon`]: a max
+/// change denominator of 50 and an elasticity multiplier of 6.
+///
+/// The base fee changes by at most 1/50 = 2% per block, and the gas target is a sixth of the gas
+/// limit.
+pub const OP_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
     max_change_denominator: OP_MAINNET_EIP1559_DEFAULT_BASE_FEE_MAX_CHANGE_DENOMINATOR,
     elasticity_multiplier: OP_MAINNET_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
 };
 
-/// Get the base fee parameters for Optimism Mainnet (post Canyon).
-pub(crate) const OP_CANYON_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
+/// The base fee parameters of OP mainnet and Base mainnet from [`Hardfork::Canyon`]: the max
+/// change denominator is raised to 250, the elasticity multiplier stays at 6.
+///
+/// This lowers the maximum base fee change per block from 2% to 1/250 = 0.4%, while the gas
+/// target stays a sixth of the gas limit.
+pub const OP_CANYON_BASE_FEE_PARAMS: BaseFeeParams = BaseFeeParams {
     max_change_denominator: OP_MAINNET_EIP1559_BASE_FEE_MAX_CHANGE_DENOMINATOR_CANYON,
     elasticity_multiplier: OP_MAINNET_EIP1559_DEFAULT_ELASTICITY_MULTIPLIER,
 };
 
+/// The base fee parameters introduced by each hardfork on Optimism Mainnet and Base Mainnet.
+pub(crate) const OP_FORK_BASE_FEE_PARAMS: [(Hardfork, BaseFeeParams); 2] =
+    [(Hardfork::London, OP_BASE_FEE_PARAMS), (Hardfork::Canyon, OP_CANYON_BASE_FEE_PARAMS)];
+
+/// The base fee parameters introduced by each hardfork on Optimism Sepolia.
+pub(crate) const OP_SEPOLIA_FORK_BASE_FEE_PARAMS: [(Hardfork, BaseFeeParams); 2] = [
+    (Hardfork::London, OP_SEPOLIA_BASE_FEE_PARAMS),
+    (Hardfork::Canyon, OP_SEPOLIA_CANYON_BASE_FEE_PARAMS),
+];
+
+/// The base fee parameters introduced by each hardfork on Base Sepolia.
+pub(crate) const BASE_SEPOLIA_FORK_BASE_FEE_PARAMS: [(Hardfork, BaseFeeParams); 2] = [
+    (Hardfork::London, BASE_SEPOLIA_BASE_FEE_PARAMS),
+    (Hardfork::Canyon, BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS),
+];
+
+/// The rollup genesis of Base mainnet, from its rollup config.
+pub(crate) const BASE_MAINNET_ROLLUP_GENESIS: OpRollupGenesis = OpRollupGenesis {
+    l1: BlockNumHash {
+        number: 17481768,
+        hash: b256!("5c13d307623a926cd31415036c8b7fa14572f9dac64528e857a470511fc30771"),
+    },
+    l2: BlockNumHash {
+        number: 0,
+        hash: b256!("f712aa9241cc24369b143cf6dce85f0902a9731e70d66818a3a5845b296c73dd"),
+    },
+    l2_time: 1686789347,
+    system_config: OpSystemConfig {
+        batcher_address: address!("5050f69a9786f081509234f1a7f4684b5e5b76c9"),
+        overhead: b256!("00000000000000000000000000000000000000000000000000000000000000bc"),
+        scalar: b256!("00000000000000000000000000000000000000000000000000000000000a6fe0"),
+        gas_limit: 30_000_000,
+    },
+};
+
 #[cfg(test)]
 mod tests {
     use super::*;
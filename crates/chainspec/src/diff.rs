@@ -0,0 +1,166 @@
+use crate::{BaseFeeParamsKind, ChainSpec};
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeSet, vec::Vec};
+use alloy_chains::Chain;
+use alloy_primitives::B256;
+use core::fmt;
+use reth_ethereum_forks::{ForkCondition, Hardfork};
+#[cfg(feature = "std")]
+use std::collections::BTreeSet;
+
+/// The differences between two chain specs, as returned by [`ChainSpec::diff`].
+///
+/// Every field holds the value of the first spec followed by the value of the second spec, and is
+/// only set if the values differ.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainSpecDiff {
+    /// The chain ids.
+    pub chain: Option<(Chain, Chain)>,
+    /// The genesis hashes.
+    pub genesis_hash: Option<(B256, B256)>,
+    /// The hardforks whose activation condition differs, in hardfork order.
+    pub hardforks: Vec<HardforkDiff>,
+    /// The base fee params.
+    pub base_fee_params: Option<(BaseFeeParamsKind, BaseFeeParamsKind)>,
+}
+
+impl ChainSpecDiff {
+    /// Compares the two chain specs.
+    pub fn new(old: &ChainSpec, new: &ChainSpec) -> Self {
+        let chain = (old.chain != new.chain).then_some((old.chain, new.chain));
+
+        let (old_hash, new_hash) = (old.genesis_hash(), new.genesis_hash());
+        let genesis_hash = (old_hash != new_hash).then_some((old_hash, new_hash));
+
+        let forks = old.hardforks.keys().chain(new.hardforks.keys()).collect::<BTreeSet<_>>();
+        let hardforks = forks
+            .into_iter()
+            .filter_map(|fork| {
+                let diff = HardforkDiff {
+                    fork: *fork,
+                    old: old.hardforks.get(fork).copied(),
+                    new: new.hardforks.get(fork).copied(),
+                };
+                (diff.old != diff.new).then_some(diff)
+            })
+            .collect();
+
+        let base_fee_params = (old.base_fee_params != new.base_fee_params)
+            .then(|| (old.base_fee_params.clone(), new.base_fee_params.clone()));
+
+        Self { chain, genesis_hash, hardforks, base_fee_params }
+    }
+
+    /// Returns `true` if the two chain specs do not differ.
+    pub fn is_empty(&self) -> bool {
+        self.chain.is_none() &&
+            self.genesis_hash.is_none() &&
+            self.hardforks.is_empty() &&
+            self.base_fee_params.is_none()
+    }
+}
+
+impl fmt::Display for ChainSpecDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No differences")
+        }
+        if let Some((old, new)) = &self.chain {
+            writeln!(f, "Chain: {old} -> {new}")?;
+        }
+        if let Some((old, new)) = &self.genesis_hash {
+            writeln!(f, "Genesis hash: {old} -> {new}")?;
+        }
+        if !self.hardforks.is_empty() {
+            writeln!(f, "Hardforks:")?;
+            for diff in &self.hardforks {
+                writeln!(f, "- {diff}")?;
+            }
+        }
+        if let Some((old, new)) = &self.base_fee_params {
+            writeln!(f, "Base fee params: {old:?} -> {new:?}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A hardfork whose activation condition differs between two chain specs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HardforkDiff {
+    /// The hardfork.
+    pub fork: Hardfork,
+    /// The activation condition in the first spec, or `None` if the fork is not configured.
+    pub old: Option<ForkCondition>,
+    /// The activation condition in the second spec, or `None` if the fork is not configured.
+    pub new: Option<ForkCondition>,
+}
+
+impl fmt::Display for HardforkDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}: ", self.fork)?;
+        match self.old {
+            Some(condition) => write!(f, "{condition:?}")?,
+            None => write!(f, "none")?,
+        }
+        match self.new {
+            Some(condition) => write!(f, " -> {condition:?}"),
+            None => write!(f, " -> none"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BaseFeeParams, ChainSpecBuilder, MAINNET, SEPOLIA};
+
+    #[test]
+    fn diff_identical() {
+        let diff = MAINNET.diff(&MAINNET);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "No differences\n");
+    }
+
+    #[test]
+    fn diff_hardforks_and_base_fee_params() {
+        let old = ChainSpecBuilder::mainnet().build();
+        let new = ChainSpecBuilder::from(&*MAINNET)
+            .with_fork(Hardfork::Prague, ForkCondition::Timestamp(100))
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(50))
+            .base_fee_params(BaseFeeParams::new(250, 6))
+            .build();
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.chain, None);
+        assert_eq!(diff.genesis_hash, None);
+        assert_eq!(
+            diff.hardforks,
+            vec![
+                HardforkDiff {
+                    fork: Hardfork::Cancun,
+                    old: old.hardforks.get(&Hardfork::Cancun).copied(),
+                    new: Some(ForkCondition::Timestamp(50)),
+                },
+                HardforkDiff {
+                    fork: Hardfork::Prague,
+                    old: None,
+                    new: Some(ForkCondition::Timestamp(100)),
+                },
+            ]
+        );
+        assert_eq!(
+            diff.base_fee_params,
+            Some((BaseFeeParams::ethereum().into(), BaseFeeParams::new(250, 6).into()))
+        );
+        assert_eq!(diff.hardforks[1].to_string(), "Prague: none -> Timestamp(100)");
+    }
+
+    #[test]
+    fn diff_chains() {
+        let diff = MAINNET.diff(&SEPOLIA);
+        assert_eq!(diff.chain, Some((MAINNET.chain, SEPOLIA.chain)));
+        assert_eq!(diff.genesis_hash, Some((MAINNET.genesis_hash(), SEPOLIA.genesis_hash())));
+        assert!(!diff.hardforks.is_empty());
+        assert!(diff.to_string().starts_with("Chain: mainnet -> sepolia\n"));
+    }
+}
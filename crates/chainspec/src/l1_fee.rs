@@ -0,0 +1,271 @@
+use crate::ChainSpec;
+use alloy_primitives::U256;
+
+/// The cost of a zero byte of L1 calldata.
+const ZERO_BYTE_COST: u64 = 4;
+
+/// The cost of a non-zero byte of L1 calldata.
+const NON_ZERO_BYTE_COST: u64 = 16;
+
+/// The EIP-2718 type of deposit transactions, which do not pay an L1 data fee.
+const DEPOSIT_TX_TYPE: u8 = 0x7e;
+
+/// The L1 fee parameters of a block, as set by its L1 attributes deposit transaction in the
+/// `L1Block` predeploy, see [`ChainSpec::l1_data_fee`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct L1FeeParams {
+    /// The base fee of the L1 origin block.
+    pub l1_base_fee: U256,
+    /// The fixed overhead added to the calldata gas of every transaction, only used before
+    /// Ecotone.
+    pub l1_fee_overhead: U256,
+    /// The scalar applied to the L1 base fee, in millionths.
+    pub l1_base_fee_scalar: U256,
+    /// The blob base fee of the L1 origin block, only used from Ecotone.
+    pub l1_blob_base_fee: U256,
+    /// The scalar applied to the L1 blob base fee, in millionths, only used from Ecotone.
+    pub l1_blob_base_fee_scalar: U256,
+}
+
+impl L1FeeParams {
+    /// Returns the L1 fee per byte of calldata introduced in Ecotone, scaled by `16 * 1e6`.
+    fn ecotone_fee_scaled(&self) -> U256 {
+        let calldata_cost_per_byte = self
+            .l1_base_fee
+            .saturating_mul(U256::from(NON_ZERO_BYTE_COST))
+            .saturating_mul(self.l1_base_fee_scalar);
+        let blob_cost_per_byte = self.l1_blob_base_fee.saturating_mul(self.l1_blob_base_fee_scalar);
+        calldata_cost_per_byte.saturating_add(blob_cost_per_byte)
+    }
+}
+
+impl ChainSpec {
+    /// Returns the L1 data fee that the given EIP-2718 encoded transaction pays on top of its L2
+    /// execution fee, if it is included in a block with the given timestamp and L1 fee parameters.
+    ///
+    /// The formula depends on the hardfork active at the timestamp:
+    /// - Before Ecotone, the calldata gas of the transaction plus the fixed overhead is priced at
+    ///   the scaled L1 base fee. Before Regolith, the calldata gas includes the gas of a 68 byte
+    ///   signature.
+    /// - From Ecotone, the calldata gas is priced at the scaled L1 base and blob base fees.
+    /// - From Fjord, the calldata gas is estimated from the `FastLZ` compressed size of the
+    ///   transaction instead of its byte count.
+    ///
+    /// Deposit transactions do not pay an L1 data fee.
+    pub fn l1_data_fee(&self, tx: &[u8], params: &L1FeeParams, timestamp: u64) -> U256 {
+        if tx.is_empty() || tx[0] == DEPOSIT_TX_TYPE {
+            return U256::ZERO
+        }
+
+        if self.is_fjord_active_at_timestamp(timestamp) {
+            let estimated_size = fjord_estimated_size(tx);
+            return estimated_size
+                .saturating_mul(params.ecotone_fee_scaled())
+                .wrapping_div(U256::from(1_000_000_000_000u64))
+        }
+
+        let calldata_gas = U256::from(calldata_gas(tx));
+        if self.is_ecotone_active_at_timestamp(timestamp) {
+            return calldata_gas
+                .saturating_mul(params.ecotone_fee_scaled())
+                .wrapping_div(U256::from(1_000_000 * NON_ZERO_BYTE_COST))
+        }
+
+        let calldata_gas = if self.is_regolith_active_at_timestamp(timestamp) {
+            calldata_gas
+        } else {
+            calldata_gas + U256::from(68 * NON_ZERO_BYTE_COST)
+        };
+        calldata_gas
+            .saturating_add(params.l1_fee_overhead)
+            .saturating_mul(params.l1_base_fee)
+            .saturating_mul(params.l1_base_fee_scalar)
+            .wrapping_div(U256::from(1_000_000))
+    }
+}
+
+/// Returns the L1 calldata gas of the given bytes.
+fn calldata_gas(input: &[u8]) -> u64 {
+    input.iter().map(|byte| if *byte == 0 { ZERO_BYTE_COST } else { NON_ZERO_BYTE_COST }).sum()
+}
+
+/// Returns the estimated compressed size of the given transaction in bytes, scaled by `1e6`, as
+/// introduced in Fjord.
+fn fjord_estimated_size(tx: &[u8]) -> U256 {
+    U256::from(flz_compress_len(tx))
+        .saturating_mul(U256::from(836_500))
+        .saturating_sub(U256::from(42_585_600))
+        .max(U256::from(100_000_000))
+}
+
+/// Returns the length of the given bytes after `FastLZ` compression, as computed by the
+/// `GasPriceOracle` predeploy.
+fn flz_compress_len(input: &[u8]) -> u32 {
+    let mut idx: u32 = 2;
+    let idx_limit: u32 = if input.len() < 13 { 0 } else { input.len() as u32 - 13 };
+    let mut anchor = 0;
+    let mut size = 0;
+    let mut htab = [0; 8192];
+
+    while idx < idx_limit {
+        let mut r: u32;
+        let mut distance: u32;
+
+        loop {
+            let seq = u24(input, idx);
+            let hash = flz_hash(seq);
+            r = htab[hash as usize];
+            htab[hash as usize] = idx;
+            distance = idx - r;
+            if idx >= idx_limit {
+                break
+            }
+            idx += 1;
+            if distance < 8192 && seq == u24(input, r) {
+                break
+            }
+        }
+
+        if idx >= idx_limit {
+            break
+        }
+
+        idx -= 1;
+        if idx > anchor {
+            size = flz_literals(idx - anchor, size);
+        }
+
+        let len = flz_cmp(input, r + 3, idx + 3, idx_limit + 9);
+        size = flz_match(len, size);
+
+        idx = flz_set_next_hash(&mut htab, input, idx + len);
+        idx = flz_set_next_hash(&mut htab, input, idx);
+        anchor = idx;
+    }
+
+    flz_literals(input.len() as u32 - anchor, size)
+}
+
+const fn flz_literals(r: u32, size: u32) -> u32 {
+    let size = size + 0x21 * (r / 0x20);
+    let r = r % 0x20;
+    if r != 0 {
+        size + r + 1
+    } else {
+        size
+    }
+}
+
+const fn flz_cmp(input: &[u8], p: u32, q: u32, r: u32) -> u32 {
+    let mut l = 0;
+    let mut r = r - q;
+    while l < r {
+        if input[(p + l) as usize] != input[(q + l) as usize] {
+            r = 0;
+        }
+        l += 1;
+    }
+    l
+}
+
+const fn flz_match(l: u32, size: u32) -> u32 {
+    let l = l - 1;
+    let size = size + (3 * (l / 262));
+    if l % 262 >= 6 {
+        size + 3
+    } else {
+        size + 2
+    }
+}
+
+fn flz_set_next_hash(htab: &mut [u32; 8192], input: &[u8], idx: u32) -> u32 {
+    htab[flz_hash(u24(input, idx)) as usize] = idx;
+    idx + 1
+}
+
+const fn flz_hash(v: u32) -> u16 {
+    let hash = (v as u64 * 2654435769) >> 19;
+    hash as u16 & 0x1fff
+}
+
+fn u24(input: &[u8], idx: u32) -> u32 {
+    u32::from(input[idx as usize]) +
+        (u32::from(input[(idx + 1) as usize]) << 8) +
+        (u32::from(input[(idx + 2) as usize]) << 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Chain, ChainSpecBuilder, ForkCondition, Hardfork};
+    use alloy_primitives::{b256, hex, keccak256};
+
+    #[test]
+    fn l1_data_fee_by_fork() {
+        let spec = ChainSpecBuilder::default()
+            .chain(Chain::from_id(10))
+            .genesis(Default::default())
+            .with_fork(Hardfork::Bedrock, ForkCondition::Block(0))
+            .with_fork(Hardfork::Regolith, ForkCondition::Timestamp(10))
+            .with_fork(Hardfork::Ecotone, ForkCondition::Timestamp(20))
+            .with_fork(Hardfork::Fjord, ForkCondition::Timestamp(30))
+            .build();
+        let params = L1FeeParams {
+            l1_base_fee: U256::from(1_000),
+            l1_fee_overhead: U256::from(1_000),
+            l1_base_fee_scalar: U256::from(1_000),
+            l1_blob_base_fee: U256::from(1_000),
+            l1_blob_base_fee_scalar: U256::from(1_000),
+        };
+        let fee = |tx: &[u8], timestamp| spec.l1_data_fee(tx, &params, timestamp).to::<u64>();
+
+        // the reference values of the `GasPriceOracle` predeploy
+        let tx = hex!("facade");
+        assert_eq!(fee(&tx, 0), 2136);
+        assert_eq!(fee(&tx, 10), 1048);
+        assert_eq!(fee(&tx, 20), 51);
+        assert_eq!(fee(&tx, 30), 1700);
+
+        // a transaction with a FastLZ compressed size of 202 bytes
+        let tx = hex!("02f901550a758302df1483be21b88304743f94f80e51afb613d764fa61751affd3313c190a86bb870151bd62fd12adb8e41ef24f3f000000000000000000000000000000000000000000000000000000000000006e000000000000000000000000af88d065e77c8cc2239327c5edb3a432268e5831000000000000000000000000000000000000000000000000000000000003c1e5000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a000000000000000000000000000000000000000000000000000000000000000148c89ed219d02f1a5be012c689b4f5b731827bebe000000000000000000000000c001a033fd89cb37c31b2cba46b6466e040c61fc9b2a3675a7f5f493ebd5ad77c497f8a07cdf65680e238392693019b4092f610222e71b7cec06449cb922b93b6a12744e");
+        assert_eq!(flz_compress_len(&tx), 202);
+        assert_eq!(fee(&tx, 30), 2148);
+
+        // the transaction 0x5dadeb52979f29fc7a7494c43fdabc5be1d8ff404f3aafe93d729fa8e5d00769 of
+        // Base mainnet
+        let tx = hex!("02f904788221050883036ee48409c6c87383037f6f941195cf65f83b3a5768f3c496d3a05ad6412c64b78644364c5bb000b90404d123b4d80000000000000000000000000000000000000000000000000000000000000040000000000000000000000000000000000000000000000000000000000000038000000000000000000000000000000000f6476f90447748c19248ccaa31e6b8bfda4eb9d830f5f47df7f0998f7c2123d9e6137761b75d3184efb0f788e3b14516000000000000000000000000000000000000000000000000000044364c5bb000000000000000000000000000f38e53bd45c8225a7c94b513beadaa7afe5d222d0000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000024000000000000000000000000000000000000000000000000000000000000002a000000000000000000000000000000000000000000000000000000000000002c000000000000000000000000000000000000000000000000000000000000002e0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000030000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000084d6574614d61736b0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000035697066733a2f2f516d656852577a743347745961776343347564745657557233454c587261436746434259416b66507331696f48610000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000001000000000000000000000000cd0d83d9e840f8e27d5c2e365fd365ff1c05b2480000000000000000000000000000000000000000000000000000000000000ce40000000000000000000000000000000000000000000000000000000000000041e4480d358dbae20880960a0a464d63b06565a0c9f9b1b37aa94b522247b23ce149c81359bf4239d1a879eeb41047ec710c15f5c0f67453da59a383e6abd742971c00000000000000000000000000000000000000000000000000000000000000c001a0b57f0ff8516ea29cb26a44ac5055a5420847d1e16a8e7b03b70f0c02291ff2d5a00ad3771e5f39ccacfff0faa8c5d25ef7a1c179f79e66e828ffddcb994c8b512e");
+        assert_eq!(
+            keccak256(tx),
+            b256!("5dadeb52979f29fc7a7494c43fdabc5be1d8ff404f3aafe93d729fa8e5d00769")
+        );
+        assert_eq!(flz_compress_len(&tx), 468);
+
+        // the L1 fee params and `l1Fee` of the receipt of the transaction
+        // 0x2bc7cb4648e847712e39abd42178e35214a70bb15c568d604687661b9539b4c2 in the Ecotone block
+        // 121258977 of OP mainnet, whose calldata gas (`l1GasUsed`) is 2048
+        let params = L1FeeParams {
+            l1_base_fee: U256::from(0x5d749a07eu64),
+            l1_base_fee_scalar: U256::from(0x558),
+            l1_blob_base_fee: U256::from(1),
+            l1_blob_base_fee_scalar: U256::from(0xc5fc5),
+            ..Default::default()
+        };
+        let calldata = [1; 128];
+        assert_eq!(calldata_gas(&calldata), 0x800);
+        assert_eq!(spec.l1_data_fee(&calldata, &params, 20), U256::from(0x105d4b2024u64));
+
+        // deposits and empty transactions pay no L1 data fee
+        for timestamp in [0, 10, 20, 30] {
+            assert_eq!(fee(&[], timestamp), 0);
+            assert_eq!(fee(&hex!("7efacade"), timestamp), 0);
+        }
+    }
+
+    #[test]
+    fn flz_compress_len_reference() {
+        assert_eq!(flz_compress_len(&[]), 0);
+        assert_eq!(flz_compress_len(&[0; 1000]), 21);
+        assert_eq!(flz_compress_len(&[42; 1000]), 21);
+        assert_eq!(flz_compress_len(&hex!("facade")), 4);
+    }
+}
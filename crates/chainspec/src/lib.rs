@@ -10,13 +10,34 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 pub use alloy_chains::{Chain, ChainKind, NamedChain};
+#[cfg(feature = "optimism")]
+pub use constants::optimism::{
+    BASE_FEE_VAULT, BASE_MAINNET_CHAIN_ID, BASE_SEPOLIA_BASE_FEE_PARAMS,
+    BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS, BASE_SEPOLIA_CHAIN_ID, L1_ATTRIBUTES_DEPOSITOR,
+    L1_BLOCK_CONTRACT, L1_FEE_VAULT, OP_BASE_FEE_PARAMS, OP_CANYON_BASE_FEE_PARAMS,
+    OP_MAINNET_CHAIN_ID, OP_SEPOLIA_BASE_FEE_PARAMS, OP_SEPOLIA_CANYON_BASE_FEE_PARAMS,
+    OP_SEPOLIA_CHAIN_ID, P256VERIFY_PRECOMPILE, SEQUENCER_FEE_VAULT,
+};
+pub use diff::{ChainSpecDiff, HardforkDiff};
 pub use info::ChainInfo;
+#[cfg(feature = "optimism")]
+pub use l1_fee::L1FeeParams;
+#[cfg(feature = "std")]
+pub use load::{ChainSpecFileError, LoadedChainSpecs};
+#[cfg(feature = "optimism")]
 pub use spec::{
-    BaseFeeParams, BaseFeeParamsKind, ChainSpec, ChainSpecBuilder, DepositContract,
-    ForkBaseFeeParams, DEV, GOERLI, HOLESKY, MAINNET, SEPOLIA,
+    op_chain_spec_by_genesis_hash, op_chain_spec_by_id, HoloceneExtraDataError, OpGenesisConfig,
+    BASE_MAINNET, BASE_SEPOLIA, OP_MAINNET, OP_SEPOLIA,
+};
+pub use spec::{
+    BaseFeeParams, BaseFeeParamsKind, BaseFeeParamsOverride, ChainSpec, ChainSpecBuilder,
+    DepositContract, ForkBaseFeeParams, GenesisAccountIndex, GenesisHashMismatch, DEV, GOERLI,
+    HOLESKY, MAINNET, SEPOLIA,
 };
 #[cfg(feature = "optimism")]
-pub use spec::{BASE_MAINNET, BASE_SEPOLIA, OP_MAINNET, OP_SEPOLIA};
+pub use superchain::SuperchainConfigError;
+#[cfg(feature = "optimism")]
+pub use system_config::{OpRollupGenesis, OpSystemConfig};
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
@@ -32,6 +53,25 @@ pub mod net;
 /// The chain spec module.
 mod spec;
 
+/// Chain spec comparison.
+mod diff;
+
+/// The L1 data fee of OP stack transactions.
+#[cfg(feature = "optimism")]
+mod l1_fee;
+
+/// The rollup genesis and system config of OP stack chains.
+#[cfg(feature = "optimism")]
+mod system_config;
+
+/// Chain specs from Superchain registry configs.
+#[cfg(feature = "optimism")]
+mod superchain;
+
+/// Loading chain specs from genesis files.
+#[cfg(feature = "std")]
+mod load;
+
 /// Chain specific constants
 pub(crate) mod constants;
 
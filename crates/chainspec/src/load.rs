@@ -0,0 +1,121 @@
+use crate::ChainSpec;
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The chain specs loaded by [`ChainSpec::load_all_from_dir`], together with the files that could
+/// not be loaded.
+#[derive(Debug, Default)]
+pub struct LoadedChainSpecs {
+    /// The chain specs of all files that were loaded, in file name order.
+    pub specs: Vec<Arc<ChainSpec>>,
+    /// The files that were skipped because they could not be read or parsed, in file name order.
+    pub errors: Vec<(PathBuf, ChainSpecFileError)>,
+}
+
+impl LoadedChainSpecs {
+    /// Loads every `*.json` file in the given directory with [`ChainSpec::from_genesis_file`].
+    pub(crate) fn from_dir(dir: &Path) -> io::Result<Self> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == "json") && path.is_file() {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut loaded = Self::default();
+        for path in paths {
+            match ChainSpec::from_genesis_file(&path) {
+                Ok(spec) => loaded.specs.push(Arc::new(spec)),
+                Err(err) => loaded.errors.push((path, err)),
+            }
+        }
+        Ok(loaded)
+    }
+}
+
+/// Error returned by [`ChainSpec::from_genesis_file`].
+#[derive(Debug)]
+pub enum ChainSpecFileError {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is not a valid genesis json document.
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for ChainSpecFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read genesis file: {err}"),
+            Self::Json(err) => write!(f, "failed to parse genesis file: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ChainSpecFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+        }
+    }
+}
+
+impl ChainSpec {
+    /// Reads the genesis json file at the given path and parses it with
+    /// [`Self::from_genesis_json`].
+    pub fn from_genesis_file(path: impl AsRef<Path>) -> Result<Self, ChainSpecFileError> {
+        let json = fs::read_to_string(path).map_err(ChainSpecFileError::Io)?;
+        Self::from_genesis_json(&json).map_err(ChainSpecFileError::Json)
+    }
+
+    /// Loads the chain spec of every `*.json` genesis file in the given directory with
+    /// [`Self::from_genesis_file`], e.g. to register all chains of a node running several chains
+    /// at once.
+    ///
+    /// Files that fail to load are skipped and reported in [`LoadedChainSpecs::errors`], so one
+    /// malformed file does not prevent loading the others. Only failing to read the directory
+    /// itself is an error.
+    pub fn load_all_from_dir(dir: impl AsRef<Path>) -> io::Result<LoadedChainSpecs> {
+        LoadedChainSpecs::from_dir(dir.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HOLESKY, SEPOLIA};
+    use alloy_chains::Chain;
+
+    #[test]
+    fn load_all_from_dir() {
+        let dir = std::env::temp_dir().join(format!("reth-chainspec-load-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b-sepolia.json"), SEPOLIA.to_genesis_json().to_string()).unwrap();
+        fs::write(dir.join("a-holesky.json"), HOLESKY.to_genesis_json().to_string()).unwrap();
+        fs::write(dir.join("c-broken.json"), "{\"config\":").unwrap();
+        fs::write(dir.join("notes.txt"), "not a genesis").unwrap();
+        fs::create_dir_all(dir.join("nested.json")).unwrap();
+
+        let loaded = ChainSpec::load_all_from_dir(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let loaded = loaded.unwrap();
+
+        let chains = loaded.specs.iter().map(|spec| spec.chain).collect::<Vec<_>>();
+        assert_eq!(chains, [Chain::holesky(), Chain::sepolia()]);
+        assert_eq!(loaded.errors.len(), 1);
+        let (path, err) = &loaded.errors[0];
+        assert_eq!(path, &dir.join("c-broken.json"));
+        assert!(matches!(err, ChainSpecFileError::Json(_)), "{err}");
+
+        assert!(ChainSpec::load_all_from_dir(&dir).is_err());
+        assert!(matches!(
+            ChainSpec::from_genesis_file(dir.join("a-holesky.json")),
+            Err(ChainSpecFileError::Io(_))
+        ));
+    }
+}
@@ -1,4 +1,9 @@
-use crate::constants::MAINNET_DEPOSIT_CONTRACT;
+use crate::{
+    constants::{
+        MAINNET_DEPOSIT_CONTRACT, PRAGUE_MAX_BLOBS_PER_BLOCK, PRAGUE_TARGET_BLOBS_PER_BLOCK,
+    },
+    ChainSpecDiff,
+};
 #[cfg(not(feature = "std"))]
 use alloc::{
     collections::BTreeMap,
@@ -8,9 +13,10 @@ use alloc::{
     vec::Vec,
 };
 use alloy_chains::{Chain, ChainKind, NamedChain};
-use alloy_genesis::Genesis;
+use alloy_genesis::{Genesis, GenesisAccount};
 use alloy_primitives::{address, b256, Address, BlockNumber, B256, U256};
 use alloy_trie::EMPTY_ROOT_HASH;
+use core::hash::{Hash, Hasher};
 use derive_more::From;
 use once_cell::sync::Lazy;
 use reth_ethereum_forks::{
@@ -30,11 +36,16 @@ use reth_trie_common::root::state_root_ref_unhashed;
 use std::{collections::BTreeMap, sync::Arc};
 
 #[cfg(feature = "optimism")]
-use crate::constants::optimism::{
-    BASE_SEPOLIA_BASE_FEE_PARAMS, BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS, OP_BASE_FEE_PARAMS,
-    OP_CANYON_BASE_FEE_PARAMS, OP_SEPOLIA_BASE_FEE_PARAMS, OP_SEPOLIA_CANYON_BASE_FEE_PARAMS,
+use crate::{
+    constants::optimism::{
+        BASE_MAINNET_ROLLUP_GENESIS, BASE_SEPOLIA_FORK_BASE_FEE_PARAMS, OP_FORK_BASE_FEE_PARAMS,
+        OP_SEPOLIA_FORK_BASE_FEE_PARAMS,
+    },
+    system_config::ROLLUP_GENESIS_KEY,
+    OpRollupGenesis,
 };
 pub use alloy_eips::eip1559::BaseFeeParams;
+use alloy_eips::eip4844::{MAX_BLOBS_PER_BLOCK, TARGET_BLOBS_PER_BLOCK};
 #[cfg(feature = "optimism")]
 use reth_ethereum_forks::chains::optimism::*;
 
@@ -65,6 +76,10 @@ pub static MAINNET: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
         )),
         base_fee_params: BaseFeeParamsKind::Constant(BaseFeeParams::ethereum()),
         prune_delete_limit: 3500,
+        #[cfg(feature = "optimism")]
+        op_rollup_genesis: None,
+        #[cfg(feature = "optimism")]
+        op_genesis_config: None,
     }
     .into()
 });
@@ -89,6 +104,10 @@ pub static GOERLI: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
         )),
         base_fee_params: BaseFeeParamsKind::Constant(BaseFeeParams::ethereum()),
         prune_delete_limit: 1700,
+        #[cfg(feature = "optimism")]
+        op_rollup_genesis: None,
+        #[cfg(feature = "optimism")]
+        op_genesis_config: None,
     }
     .into()
 });
@@ -113,6 +132,10 @@ pub static SEPOLIA: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
         )),
         base_fee_params: BaseFeeParamsKind::Constant(BaseFeeParams::ethereum()),
         prune_delete_limit: 1700,
+        #[cfg(feature = "optimism")]
+        op_rollup_genesis: None,
+        #[cfg(feature = "optimism")]
+        op_genesis_config: None,
     }
     .into()
 });
@@ -135,6 +158,10 @@ pub static HOLESKY: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
         )),
         base_fee_params: BaseFeeParamsKind::Constant(BaseFeeParams::ethereum()),
         prune_delete_limit: 1700,
+        #[cfg(feature = "optimism")]
+        op_rollup_genesis: None,
+        #[cfg(feature = "optimism")]
+        op_genesis_config: None,
     }
     .into()
 });
@@ -188,105 +215,98 @@ pub static DEV: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
 /// The Optimism Mainnet spec
 #[cfg(feature = "optimism")]
 pub static OP_MAINNET: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
-    ChainSpec {
-        chain: Chain::optimism_mainnet(),
-        // genesis contains empty alloc field because state at first bedrock block is imported
-        // manually from trusted source
-        genesis: serde_json::from_str(include_str!("../res/genesis/optimism.json"))
-            .expect("Can't deserialize Optimism Mainnet genesis json"),
-        genesis_hash: Some(b256!(
-            "7ca38a1916c42007829c55e69d3e9a73265554b586a499015373241b8a3fa48b"
-        )),
-        paris_block_and_final_difficulty: Some((0, U256::from(0))),
-        hardforks: OP_MAINNET_HARDFORKS.into(),
-        base_fee_params: BaseFeeParamsKind::Variable(
-            vec![
-                (Hardfork::London, OP_BASE_FEE_PARAMS),
-                (Hardfork::Canyon, OP_CANYON_BASE_FEE_PARAMS),
-            ]
-            .into(),
-        ),
-        prune_delete_limit: 1700,
-        ..Default::default()
-    }
-    .into()
+    // genesis contains empty alloc field because state at first bedrock block is imported
+    // manually from trusted source
+    ChainSpecBuilder::default()
+        .chain(Chain::optimism_mainnet())
+        .genesis_json(include_str!("../res/genesis/optimism.json"))
+        .genesis_hash(b256!("7ca38a1916c42007829c55e69d3e9a73265554b586a499015373241b8a3fa48b"))
+        .op_hardforks(OP_MAINNET_HARDFORKS)
+        .op_base_fee_params(&OP_FORK_BASE_FEE_PARAMS)
+        .op_defaults()
+        .build()
+        .into()
 });
 
 /// The OP Sepolia spec
 #[cfg(feature = "optimism")]
 pub static OP_SEPOLIA: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
-    ChainSpec {
-        chain: Chain::from_named(NamedChain::OptimismSepolia),
-        genesis: serde_json::from_str(include_str!("../res/genesis/sepolia_op.json"))
-            .expect("Can't deserialize OP Sepolia genesis json"),
-        genesis_hash: Some(b256!(
-            "102de6ffb001480cc9b8b548fd05c34cd4f46ae4aa91759393db90ea0409887d"
-        )),
-        paris_block_and_final_difficulty: Some((0, U256::from(0))),
-        hardforks: OP_SEPOLIA_HARDFORKS.into(),
-        base_fee_params: BaseFeeParamsKind::Variable(
-            vec![
-                (Hardfork::London, OP_SEPOLIA_BASE_FEE_PARAMS),
-                (Hardfork::Canyon, OP_SEPOLIA_CANYON_BASE_FEE_PARAMS),
-            ]
-            .into(),
-        ),
-        prune_delete_limit: 1700,
-        ..Default::default()
-    }
-    .into()
+    ChainSpecBuilder::default()
+        .chain(Chain::from_named(NamedChain::OptimismSepolia))
+        .genesis_json(include_str!("../res/genesis/sepolia_op.json"))
+        .genesis_hash(b256!("102de6ffb001480cc9b8b548fd05c34cd4f46ae4aa91759393db90ea0409887d"))
+        .op_hardforks(OP_SEPOLIA_HARDFORKS)
+        .op_base_fee_params(&OP_SEPOLIA_FORK_BASE_FEE_PARAMS)
+        .op_defaults()
+        .build()
+        .into()
 });
 
 /// The Base Sepolia spec
 #[cfg(feature = "optimism")]
 pub static BASE_SEPOLIA: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
-    ChainSpec {
-        chain: Chain::base_sepolia(),
-        genesis: serde_json::from_str(include_str!("../res/genesis/sepolia_base.json"))
-            .expect("Can't deserialize Base Sepolia genesis json"),
-        genesis_hash: Some(b256!(
-            "0dcc9e089e30b90ddfc55be9a37dd15bc551aeee999d2e2b51414c54eaf934e4"
-        )),
-        paris_block_and_final_difficulty: Some((0, U256::from(0))),
-        hardforks: BASE_SEPOLIA_HARDFORKS.into(),
-        base_fee_params: BaseFeeParamsKind::Variable(
-            vec![
-                (Hardfork::London, BASE_SEPOLIA_BASE_FEE_PARAMS),
-                (Hardfork::Canyon, BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS),
-            ]
-            .into(),
-        ),
-        prune_delete_limit: 1700,
-        ..Default::default()
-    }
-    .into()
+    ChainSpecBuilder::default()
+        .chain(Chain::base_sepolia())
+        .genesis_json(include_str!("../res/genesis/sepolia_base.json"))
+        .genesis_hash(b256!("0dcc9e089e30b90ddfc55be9a37dd15bc551aeee999d2e2b51414c54eaf934e4"))
+        .op_hardforks(BASE_SEPOLIA_HARDFORKS)
+        .op_base_fee_params(&BASE_SEPOLIA_FORK_BASE_FEE_PARAMS)
+        .op_defaults()
+        .build()
+        .into()
 });
 
 /// The Base mainnet spec
 #[cfg(feature = "optimism")]
 pub static BASE_MAINNET: Lazy<Arc<ChainSpec>> = Lazy::new(|| {
-    ChainSpec {
-        chain: Chain::base_mainnet(),
-        genesis: serde_json::from_str(include_str!("../res/genesis/base.json"))
-            .expect("Can't deserialize Base genesis json"),
-        genesis_hash: Some(b256!(
-            "f712aa9241cc24369b143cf6dce85f0902a9731e70d66818a3a5845b296c73dd"
-        )),
-        paris_block_and_final_difficulty: Some((0, U256::from(0))),
-        hardforks: BASE_MAINNET_HARDFORKS.into(),
-        base_fee_params: BaseFeeParamsKind::Variable(
-            vec![
-                (Hardfork::London, OP_BASE_FEE_PARAMS),
-                (Hardfork::Canyon, OP_CANYON_BASE_FEE_PARAMS),
-            ]
-            .into(),
-        ),
-        prune_delete_limit: 1700,
-        ..Default::default()
-    }
-    .into()
+    ChainSpecBuilder::default()
+        .chain(Chain::base_mainnet())
+        .genesis_json(include_str!("../res/genesis/base.json"))
+        .genesis_hash(b256!("f712aa9241cc24369b143cf6dce85f0902a9731e70d66818a3a5845b296c73dd"))
+        .op_hardforks(BASE_MAINNET_HARDFORKS)
+        .op_base_fee_params(&OP_FORK_BASE_FEE_PARAMS)
+        .op_rollup_genesis(BASE_MAINNET_ROLLUP_GENESIS)
+        .op_defaults()
+        .build()
+        .into()
 });
 
+/// The built-in OP stack chain specs by chain, see [`op_chain_spec_by_id`] and
+/// [`op_chain_spec_by_genesis_hash`].
+///
+/// New built-in OP stack chains must be registered here, which makes them available to both
+/// lookups.
+#[cfg(feature = "optimism")]
+static OP_CHAIN_SPECS: [(NamedChain, &Lazy<Arc<ChainSpec>>); 4] = [
+    (NamedChain::Optimism, &OP_MAINNET),
+    (NamedChain::OptimismSepolia, &OP_SEPOLIA),
+    (NamedChain::Base, &BASE_MAINNET),
+    (NamedChain::BaseSepolia, &BASE_SEPOLIA),
+];
+
+/// Returns the built-in OP stack chain spec with the given chain id, or `None` if there is no
+/// built-in spec for the chain.
+#[cfg(feature = "optimism")]
+pub fn op_chain_spec_by_id(chain_id: u64) -> Option<Arc<ChainSpec>> {
+    OP_CHAIN_SPECS
+        .iter()
+        .find(|(chain, _)| *chain as u64 == chain_id)
+        .map(|(_, spec)| Arc::clone(spec))
+}
+
+/// Returns the built-in OP stack chain spec with the given genesis hash, or `None` if there is no
+/// built-in spec for the chain, e.g. to identify the chain of a peer from its status message.
+///
+/// The hash is matched against the pinned genesis hash of every built-in spec, so the genesis
+/// blocks are never hashed, but all built-in specs are initialized on the first lookup.
+#[cfg(feature = "optimism")]
+pub fn op_chain_spec_by_genesis_hash(genesis_hash: B256) -> Option<Arc<ChainSpec>> {
+    OP_CHAIN_SPECS
+        .iter()
+        .find(|(_, spec)| spec.genesis_hash == Some(genesis_hash))
+        .map(|(_, spec)| Arc::clone(spec))
+}
+
 /// A wrapper around [`BaseFeeParams`] that allows for specifying constant or dynamic EIP-1559
 /// parameters based on the active [Hardfork].
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -298,6 +318,88 @@ pub enum BaseFeeParamsKind {
     Variable(ForkBaseFeeParams),
 }
 
+impl BaseFeeParamsKind {
+    /// Derives the [`BaseFeeParamsKind::Variable`] schedule of an OP stack chain from its
+    /// hardforks.
+    ///
+    /// `fork_params` lists the [`BaseFeeParams`] introduced by each hardfork, only the hardforks
+    /// that are part of `hardforks` are included in the schedule, in activation order.
+    #[cfg(feature = "optimism")]
+    pub fn from_op_hardforks(
+        hardforks: &BTreeMap<Hardfork, ForkCondition>,
+        fork_params: &[(Hardfork, BaseFeeParams)],
+    ) -> Self {
+        let mut params = fork_params
+            .iter()
+            .filter(|(fork, _)| hardforks.contains_key(fork))
+            .copied()
+            .collect::<Vec<_>>();
+        params.sort_by_key(|(fork, _)| *fork);
+        Self::Variable(params.into())
+    }
+
+    /// Applies the given overrides to the params.
+    ///
+    /// The overrides only replace the fields that are set, for every entry of a
+    /// [`BaseFeeParamsKind::Variable`] schedule. If a Canyon denominator is set, it replaces the
+    /// denominator of the entries of Canyon and the later OP stack hardforks instead of the
+    /// general one, and a [`BaseFeeParamsKind::Constant`] value is turned into a London and Canyon
+    /// schedule.
+    pub fn with_override(self, overrides: &BaseFeeParamsOverride) -> Self {
+        match self {
+            Self::Constant(params) => {
+                #[cfg(feature = "optimism")]
+                if overrides.canyon_max_change_denominator.is_some() {
+                    let schedule = vec![(Hardfork::London, params), (Hardfork::Canyon, params)];
+                    return Self::Variable(schedule.into()).with_override(overrides)
+                }
+                Self::Constant(overrides.apply(Hardfork::London, params))
+            }
+            Self::Variable(ForkBaseFeeParams(schedule)) => Self::Variable(
+                schedule
+                    .into_iter()
+                    .map(|(fork, params)| (fork, overrides.apply(fork, params)))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+        }
+    }
+}
+
+/// Overrides for the [`BaseFeeParams`] of a chain, for example parsed from CLI arguments.
+///
+/// Fields that are `None` keep the value of the chain spec.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BaseFeeParamsOverride {
+    /// The base fee max change denominator.
+    pub max_change_denominator: Option<u128>,
+    /// The elasticity multiplier.
+    pub elasticity_multiplier: Option<u128>,
+    /// The base fee max change denominator of [`Hardfork::Canyon`] and the later OP stack
+    /// hardforks, which falls back to [`Self::max_change_denominator`] if it is not set.
+    #[cfg(feature = "optimism")]
+    pub canyon_max_change_denominator: Option<u128>,
+}
+
+impl BaseFeeParamsOverride {
+    /// Applies the overrides to the params of the given fork.
+    #[allow(unused_variables)]
+    fn apply(&self, fork: Hardfork, params: BaseFeeParams) -> BaseFeeParams {
+        let max_change_denominator = self.max_change_denominator;
+        // Ethereum hardforks like Cancun are declared after Canyon, but do not follow it
+        #[cfg(feature = "optimism")]
+        let max_change_denominator = match self.canyon_max_change_denominator {
+            Some(canyon) if fork.is_optimism() && fork >= Hardfork::Canyon => Some(canyon),
+            _ => max_change_denominator,
+        };
+
+        BaseFeeParams::new(
+            max_change_denominator.unwrap_or(params.max_change_denominator),
+            self.elasticity_multiplier.unwrap_or(params.elasticity_multiplier),
+        )
+    }
+}
+
 impl Default for BaseFeeParamsKind {
     fn default() -> Self {
         BaseFeeParams::ethereum().into()
@@ -321,6 +423,41 @@ impl From<ForkBaseFeeParams> for BaseFeeParamsKind {
 #[derive(Clone, Debug, PartialEq, Eq, From)]
 pub struct ForkBaseFeeParams(Vec<(Hardfork, BaseFeeParams)>);
 
+/// The genesis accounts of a chain spec by address.
+#[cfg(feature = "std")]
+type GenesisAccounts<'a> = std::collections::HashMap<&'a Address, &'a GenesisAccount>;
+#[cfg(not(feature = "std"))]
+type GenesisAccounts<'a> = BTreeMap<&'a Address, &'a GenesisAccount>;
+
+/// An index of the genesis alloc of a [`ChainSpec`] by address, see
+/// [`ChainSpec::genesis_account_index`].
+///
+/// The index borrows the accounts of the alloc, so building it does not clone them.
+#[derive(Debug, Clone)]
+pub struct GenesisAccountIndex<'a>(GenesisAccounts<'a>);
+
+impl<'a> GenesisAccountIndex<'a> {
+    /// Builds the index of the given genesis alloc.
+    fn new(alloc: &'a BTreeMap<Address, GenesisAccount>) -> Self {
+        Self(alloc.iter().collect())
+    }
+
+    /// Returns the account with the given address, if any.
+    pub fn get(&self, address: &Address) -> Option<&'a GenesisAccount> {
+        self.0.get(address).copied()
+    }
+
+    /// Returns the number of indexed accounts.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the genesis alloc has no accounts.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 /// An Ethereum chain specification.
 ///
 /// A chain specification describes:
@@ -359,6 +496,15 @@ pub struct ChainSpec {
     /// the amount of blocks between pruner runs to account for the difference in amount of new
     /// data coming in.
     pub prune_delete_limit: usize,
+
+    /// The genesis of the rollup, for OP stack chains, see [`ChainSpec::op_rollup_genesis`].
+    #[cfg(feature = "optimism")]
+    pub op_rollup_genesis: Option<OpRollupGenesis>,
+
+    /// The `optimism` section of the genesis config, for OP stack chains, see
+    /// [`ChainSpec::op_genesis_config`].
+    #[cfg(feature = "optimism")]
+    pub op_genesis_config: Option<OpGenesisConfig>,
 }
 
 impl Default for ChainSpec {
@@ -372,10 +518,28 @@ impl Default for ChainSpec {
             deposit_contract: Default::default(),
             base_fee_params: BaseFeeParamsKind::Constant(BaseFeeParams::ethereum()),
             prune_delete_limit: MAINNET.prune_delete_limit,
+            #[cfg(feature = "optimism")]
+            op_rollup_genesis: None,
+            #[cfg(feature = "optimism")]
+            op_genesis_config: None,
         }
     }
 }
 
+/// Only the chain id, the cached [`ChainSpec::genesis_hash`] and the hardforks are hashed, so
+/// hashing a chain spec is cheap regardless of the size of its genesis alloc.
+///
+/// This is consistent with [`Eq`]: equal chain specs have equal hashes, while chain specs that
+/// only differ in other fields, like the base fee params, collide and are told apart by the full
+/// comparison. This makes a chain spec usable as the key of a cache of derived data.
+impl Hash for ChainSpec {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.chain.hash(state);
+        self.genesis_hash.hash(state);
+        self.hardforks.hash(state);
+    }
+}
+
 impl ChainSpec {
     /// Get information about the chain itself
     pub const fn chain(&self) -> Chain {
@@ -427,6 +591,37 @@ impl ChainSpec {
         &self.genesis
     }
 
+    /// Get the genesis allocation of the given account, if any.
+    ///
+    /// Callers looking up many accounts should use [`Self::genesis_account_index`] instead.
+    pub fn genesis_account(&self, address: &Address) -> Option<&GenesisAccount> {
+        self.genesis.alloc.get(address)
+    }
+
+    /// Builds an index of the genesis alloc by address, so that lookups take constant time
+    /// regardless of the number of genesis accounts.
+    pub fn genesis_account_index(&self) -> GenesisAccountIndex<'_> {
+        GenesisAccountIndex::new(&self.genesis.alloc)
+    }
+
+    /// Get the OP stack specific `optimism` section of the genesis config, if any.
+    ///
+    /// The section is parsed once when the chain spec is built from its genesis.
+    #[cfg(feature = "optimism")]
+    pub const fn op_genesis_config(&self) -> Option<&OpGenesisConfig> {
+        self.op_genesis_config.as_ref()
+    }
+
+    /// Get the sum of the balances of all accounts in the genesis allocation.
+    ///
+    /// The sum saturates at [`U256::MAX`].
+    pub fn genesis_total_allocated(&self) -> U256 {
+        self.genesis
+            .alloc
+            .values()
+            .fold(U256::ZERO, |total, account| total.saturating_add(account.balance))
+    }
+
     /// Get the header for the genesis block.
     pub fn genesis_header(&self) -> Header {
         // If London is activated at genesis, we set the initial base fee as per EIP-1559.
@@ -500,6 +695,11 @@ impl ChainSpec {
     }
 
     /// Get the [`BaseFeeParams`] for the chain at the given timestamp.
+    ///
+    /// For [`BaseFeeParamsKind::Variable`] params, this returns the params of the last fork in the
+    /// schedule that is active at the timestamp. Block activated forks like London are never
+    /// active at a timestamp, so the first entry of the schedule is returned if no later fork is
+    /// active yet, or [`BaseFeeParams::ethereum`] if the schedule is empty.
     pub fn base_fee_params_at_timestamp(&self, timestamp: u64) -> BaseFeeParams {
         match self.base_fee_params {
             BaseFeeParamsKind::Constant(bf_params) => bf_params,
@@ -537,11 +737,256 @@ impl ChainSpec {
         }
     }
 
+    /// Returns the [`BaseFeeParams`] in effect right before [`Hardfork::Canyon`] and the ones
+    /// introduced by it, or `None` if the base fee params of the chain do not change at Canyon.
+    ///
+    /// Canyon raises the max change denominator of OP mainnet and Base mainnet from 50 to 250, see
+    /// [`OP_BASE_FEE_PARAMS`](crate::OP_BASE_FEE_PARAMS) and
+    /// [`OP_CANYON_BASE_FEE_PARAMS`](crate::OP_CANYON_BASE_FEE_PARAMS), and leaves the elasticity
+    /// multiplier unchanged.
+    #[cfg(feature = "optimism")]
+    pub fn canyon_base_fee_params_change(&self) -> Option<(BaseFeeParams, BaseFeeParams)> {
+        let BaseFeeParamsKind::Variable(ForkBaseFeeParams(ref schedule)) = self.base_fee_params
+        else {
+            return None
+        };
+        let canyon = schedule.iter().position(|(fork, _)| *fork == Hardfork::Canyon)?;
+        let (_, before) = schedule.get(canyon.checked_sub(1)?)?;
+        Some((*before, schedule[canyon].1))
+    }
+
+    /// Returns the maximum number of blobs a block with the given timestamp may contain.
+    ///
+    /// This is zero before Cancun and on OP stack chains, which do not accept blob transactions in
+    /// their blocks, six from Cancun and nine from Prague.
+    pub fn max_blob_count_at_timestamp(&self, timestamp: u64) -> u64 {
+        self.blob_counts_at_timestamp(timestamp).1
+    }
+
+    /// Returns the target number of blobs per block at the given timestamp, which the blob base
+    /// fee adjusts towards.
+    ///
+    /// This is zero before Cancun and on OP stack chains, three from Cancun and six from Prague.
+    pub fn target_blob_count_at_timestamp(&self, timestamp: u64) -> u64 {
+        self.blob_counts_at_timestamp(timestamp).0
+    }
+
+    /// Returns the target and maximum number of blobs per block at the given timestamp.
+    fn blob_counts_at_timestamp(&self, timestamp: u64) -> (u64, u64) {
+        if self.is_optimism() || !self.is_cancun_active_at_timestamp(timestamp) {
+            (0, 0)
+        } else if self.is_prague_active_at_timestamp(timestamp) {
+            (PRAGUE_TARGET_BLOBS_PER_BLOCK, PRAGUE_MAX_BLOBS_PER_BLOCK)
+        } else {
+            (TARGET_BLOBS_PER_BLOCK, MAX_BLOBS_PER_BLOCK as u64)
+        }
+    }
+
+    /// Returns the base fee of the child of the given parent header, which has the given
+    /// timestamp.
+    ///
+    /// The [`BaseFeeParams`] are selected by the timestamp of the child, not of the parent, so
+    /// the first block after a fork that changes the params, like Canyon on OP stack chains,
+    /// already uses the new params. If the parent has no base fee, the child is the London fork
+    /// block and [`EIP1559_INITIAL_BASE_FEE`] is returned.
+    ///
+    /// On OP stack chains the params of a parent from [`Hardfork::Holocene`] on are decoded from
+    /// its header, see [`Self::base_fee_params_from_header`], and `None` is returned if they are
+    /// invalid.
+    pub fn next_block_base_fee(&self, parent: &Header, timestamp: u64) -> Option<u64> {
+        #[cfg(feature = "optimism")]
+        let base_fee_params = self.base_fee_params_from_header(parent, timestamp).ok()?;
+        #[cfg(not(feature = "optimism"))]
+        let base_fee_params = self.base_fee_params_at_timestamp(timestamp);
+        Some(parent.next_block_base_fee(base_fee_params).unwrap_or(EIP1559_INITIAL_BASE_FEE))
+    }
+
+    /// Asserts that the forks of a [`BaseFeeParamsKind::Variable`] schedule are listed in the
+    /// order they activate in.
+    ///
+    /// The base fee params lookups walk the schedule in reverse and return the params of the first
+    /// active fork, so a schedule that is out of order silently returns the params of the wrong
+    /// fork. Forks activated by block are expected before forks activated by timestamp.
+    ///
+    /// # Panics
+    ///
+    /// If a fork in the schedule activates before the fork listed before it.
+    pub fn assert_fee_schedule_sorted(&self) {
+        let BaseFeeParamsKind::Variable(ForkBaseFeeParams(ref bf_params)) = self.base_fee_params
+        else {
+            return
+        };
+        for window in bf_params.windows(2) {
+            let (previous, fork) = (window[0].0, window[1].0);
+            assert!(
+                activation_order(self.fork(previous)) <= activation_order(self.fork(fork)),
+                "base fee params of {fork:?} are listed after {previous:?}, which activates later"
+            );
+        }
+    }
+
+    /// Returns the chain spec with the given [`BaseFeeParamsKind`].
+    pub fn with_base_fee_params(mut self, base_fee_params: BaseFeeParamsKind) -> Self {
+        self.base_fee_params = base_fee_params;
+        self
+    }
+
+    /// Returns the chain spec with the given overrides applied to its [`BaseFeeParamsKind`], see
+    /// [`BaseFeeParamsKind::with_override`].
+    pub fn with_base_fee_params_override(self, overrides: &BaseFeeParamsOverride) -> Self {
+        let base_fee_params = self.base_fee_params.clone().with_override(overrides);
+        self.with_base_fee_params(base_fee_params)
+    }
+
     /// Get the hash of the genesis block.
     pub fn genesis_hash(&self) -> B256 {
         self.genesis_hash.unwrap_or_else(|| self.genesis_header().hash_slow())
     }
 
+    /// Recomputes the genesis block hash from [`Self::genesis`] and checks that it matches the
+    /// pinned [`Self::genesis_hash`], if there is one.
+    ///
+    /// Note: this fails for chains whose genesis state is imported instead of being derived from
+    /// the genesis alloc, like OP mainnet.
+    pub fn verify_genesis_hash(&self) -> Result<(), GenesisHashMismatch> {
+        let Some(expected) = self.genesis_hash else { return Ok(()) };
+        let computed = self.genesis_header().hash_slow();
+        if computed != expected {
+            return Err(GenesisHashMismatch { expected, computed })
+        }
+        Ok(())
+    }
+
+    /// Returns the differences in chain id, genesis hash, hardfork activation conditions and base
+    /// fee params between this chain spec and the given one.
+    pub fn diff(&self, other: &Self) -> ChainSpecDiff {
+        ChainSpecDiff::new(self, other)
+    }
+
+    /// Parses a genesis json file, including its `config` block, into a chain spec.
+    ///
+    /// The hardforks are derived from the activation blocks and timestamps in the `config`, and
+    /// the genesis hash is computed from the genesis instead of being pinned. The base fee params
+    /// are read from the `optimism` section of the `config`, if any. If the `config` activates
+    /// Bedrock, the prune delete limit of the built-in OP chain specs is used.
+    pub fn from_genesis_json(json: &str) -> Result<Self, serde_json::Error> {
+        let genesis: Genesis = serde_json::from_str(json)?;
+        #[allow(unused_mut)]
+        let mut spec = Self::from(genesis);
+
+        #[cfg(feature = "optimism")]
+        if spec.hardforks.contains_key(&Hardfork::Bedrock) {
+            spec.prune_delete_limit = 1700;
+        }
+
+        Ok(spec)
+    }
+
+    /// Serializes the chain spec into a genesis json document, which can be parsed again with
+    /// [`Self::from_genesis_json`].
+    ///
+    /// The activation blocks and timestamps in the `config` are derived from the hardforks of the
+    /// spec, except for [`Hardfork::Frontier`] which has no field in the `config`. For OP stack
+    /// chains, the base fee params are written to the `optimism` section of the `config`, and the
+    /// rollup genesis to its `rollupGenesis` field.
+    pub fn to_genesis_json(&self) -> serde_json::Value {
+        let block = |fork| match self.hardforks.get(&fork) {
+            Some(ForkCondition::Block(block)) => Some(*block),
+            _ => None,
+        };
+        let timestamp = |fork| self.hardforks.get(&fork).and_then(ForkCondition::as_timestamp);
+
+        let mut genesis = self.genesis.clone();
+        let config = &mut genesis.config;
+        config.chain_id = self.chain.id();
+
+        // Block-based hardforks
+        config.homestead_block = block(Hardfork::Homestead);
+        config.dao_fork_block = block(Hardfork::Dao);
+        config.eip150_block = block(Hardfork::Tangerine);
+        config.eip155_block = block(Hardfork::SpuriousDragon);
+        config.byzantium_block = block(Hardfork::Byzantium);
+        config.constantinople_block = block(Hardfork::Constantinople);
+        config.petersburg_block = block(Hardfork::Petersburg);
+        config.istanbul_block = block(Hardfork::Istanbul);
+        config.muir_glacier_block = block(Hardfork::MuirGlacier);
+        config.berlin_block = block(Hardfork::Berlin);
+        config.london_block = block(Hardfork::London);
+        config.arrow_glacier_block = block(Hardfork::ArrowGlacier);
+        config.gray_glacier_block = block(Hardfork::GrayGlacier);
+
+        // Paris
+        (config.terminal_total_difficulty, config.merge_netsplit_block) =
+            match self.hardforks.get(&Hardfork::Paris) {
+                Some(ForkCondition::TTD { fork_block, total_difficulty }) => {
+                    (Some(*total_difficulty), *fork_block)
+                }
+                _ => (None, None),
+            };
+
+        // Time-based hardforks
+        config.shanghai_time = timestamp(Hardfork::Shanghai);
+        config.cancun_time = timestamp(Hardfork::Cancun);
+        config.prague_time = timestamp(Hardfork::Prague);
+
+        #[cfg(feature = "optimism")]
+        {
+            let fields = &mut config.extra_fields;
+            for (key, value) in [
+                ("bedrockBlock", block(Hardfork::Bedrock)),
+                ("regolithTime", timestamp(Hardfork::Regolith)),
+                ("canyonTime", timestamp(Hardfork::Canyon)),
+                ("ecotoneTime", timestamp(Hardfork::Ecotone)),
+                ("fjordTime", timestamp(Hardfork::Fjord)),
+                ("graniteTime", timestamp(Hardfork::Granite)),
+                ("holoceneTime", timestamp(Hardfork::Holocene)),
+                ("isthmusTime", timestamp(Hardfork::Isthmus)),
+            ] {
+                match value {
+                    Some(value) => fields.insert(key.to_string(), value.into()),
+                    None => fields.remove(key),
+                };
+            }
+
+            let op_genesis_config = OpGenesisConfig::from_base_fee_params(&self.base_fee_params)
+                .map(|config| OpGenesisConfig {
+                    l1_fee_recipient: self
+                        .op_genesis_config
+                        .and_then(|config| config.l1_fee_recipient),
+                    ..config
+                });
+            if let Some(op_genesis_config) = op_genesis_config.filter(|_| self.is_optimism()) {
+                let section = fields
+                    .entry("optimism".to_string())
+                    .or_insert_with(|| serde_json::Value::Object(Default::default()));
+                if let (Some(section), serde_json::Value::Object(params)) = (
+                    section.as_object_mut(),
+                    serde_json::to_value(op_genesis_config).expect("failed to serialize config"),
+                ) {
+                    for key in [
+                        "eip1559Elasticity",
+                        "eip1559Denominator",
+                        "eip1559DenominatorCanyon",
+                        "l1FeeRecipient",
+                    ] {
+                        section.remove(key);
+                    }
+                    section.extend(params);
+                }
+            }
+
+            match self.op_rollup_genesis {
+                Some(rollup_genesis) => fields.insert(
+                    ROLLUP_GENESIS_KEY.to_string(),
+                    serde_json::to_value(rollup_genesis).expect("failed to serialize genesis"),
+                ),
+                None => fields.remove(ROLLUP_GENESIS_KEY),
+            };
+        }
+
+        serde_json::to_value(genesis).expect("failed to serialize genesis")
+    }
+
     /// Get the timestamp of the genesis block.
     pub const fn genesis_timestamp(&self) -> u64 {
         self.genesis.timestamp
@@ -622,6 +1067,164 @@ impl ChainSpec {
         self.hardforks.iter().map(|(f, b)| (*f, *b))
     }
 
+    /// Returns all hardforks of this spec that ever activate, in the order of their activation.
+    ///
+    /// Block based forks come first, ordered by block. The merge is ordered by its fork block if
+    /// it is known, and placed after all block based forks otherwise. Timestamp based forks
+    /// follow, ordered by timestamp. Forks that activate together are in the order of
+    /// [`Hardfork`].
+    pub fn fork_timeline(&self) -> Vec<(Hardfork, ForkCondition)> {
+        let mut timeline = self
+            .forks_iter()
+            .filter(|(_, condition)| *condition != ForkCondition::Never)
+            .collect::<Vec<_>>();
+        timeline.sort_by_key(|(_, condition)| match *condition {
+            ForkCondition::Block(block) | ForkCondition::TTD { fork_block: Some(block), .. } => {
+                (0, block)
+            }
+            ForkCondition::TTD { fork_block: None, .. } => (1, 0),
+            ForkCondition::Timestamp(timestamp) => (2, timestamp),
+            ForkCondition::Never => unreachable!("never activating forks are filtered out"),
+        });
+        timeline
+    }
+
+    /// Returns a copy of the hardfork schedule of this spec, with the activation conditions of the
+    /// given forks replaced.
+    ///
+    /// Forks that are not part of this spec are added. This allows reusing the schedule of a known
+    /// chain for a custom chain that activates some forks at different times, e.g. with
+    /// [`ChainSpecBuilder::op_hardforks`].
+    pub fn hardforks_with_overrides(
+        &self,
+        overrides: &[(Hardfork, ForkCondition)],
+    ) -> BTreeMap<Hardfork, ForkCondition> {
+        let mut hardforks = self.hardforks.clone();
+        hardforks.extend(overrides.iter().copied());
+        hardforks
+    }
+
+    /// Get an iterator of all OP stack hardforks with their respective activation conditions.
+    #[cfg(feature = "optimism")]
+    pub fn op_hardforks_iter(&self) -> impl Iterator<Item = (Hardfork, ForkCondition)> + '_ {
+        self.forks_iter().filter(|(fork, _)| fork.is_optimism())
+    }
+
+    /// Returns the first OP stack hardfork that activates after the given timestamp, together
+    /// with its activation timestamp.
+    #[cfg(feature = "optimism")]
+    pub fn next_op_fork_after(&self, timestamp: u64) -> Option<(Hardfork, u64)> {
+        self.op_hardforks_iter()
+            .filter_map(|(fork, condition)| Some((fork, condition.as_timestamp()?)))
+            .filter(|(_, activation)| *activation > timestamp)
+            .min_by_key(|(_, activation)| *activation)
+    }
+
+    /// Returns the address of the `L1Block` predeploy, see [`L1_BLOCK_CONTRACT`].
+    ///
+    /// [`L1_BLOCK_CONTRACT`]: crate::L1_BLOCK_CONTRACT
+    #[cfg(feature = "optimism")]
+    pub const fn l1_block_info_address(&self) -> Address {
+        crate::constants::optimism::L1_BLOCK_CONTRACT
+    }
+
+    /// Returns the sender of the L1 attributes deposit transaction, see
+    /// [`L1_ATTRIBUTES_DEPOSITOR`].
+    ///
+    /// The first transaction of a block is the L1 attributes deposit transaction if it is a
+    /// deposit sent from this address to [`Self::l1_block_info_address`].
+    ///
+    /// [`L1_ATTRIBUTES_DEPOSITOR`]: crate::L1_ATTRIBUTES_DEPOSITOR
+    #[cfg(feature = "optimism")]
+    pub const fn l1_attributes_depositor_address(&self) -> Address {
+        crate::constants::optimism::L1_ATTRIBUTES_DEPOSITOR
+    }
+
+    /// Returns the address of the `SequencerFeeVault` predeploy, which receives the priority fees,
+    /// see [`SEQUENCER_FEE_VAULT`].
+    ///
+    /// [`SEQUENCER_FEE_VAULT`]: crate::SEQUENCER_FEE_VAULT
+    #[cfg(feature = "optimism")]
+    pub const fn sequencer_fee_vault_address(&self) -> Address {
+        crate::constants::optimism::SEQUENCER_FEE_VAULT
+    }
+
+    /// Returns the address of the `BaseFeeVault` predeploy, which receives the base fees, see
+    /// [`BASE_FEE_VAULT`].
+    ///
+    /// [`BASE_FEE_VAULT`]: crate::BASE_FEE_VAULT
+    #[cfg(feature = "optimism")]
+    pub const fn base_fee_vault_address(&self) -> Address {
+        crate::constants::optimism::BASE_FEE_VAULT
+    }
+
+    /// Returns the address of the `L1FeeVault` predeploy, which receives the L1 data fees, see
+    /// [`L1_FEE_VAULT`].
+    ///
+    /// [`L1_FEE_VAULT`]: crate::L1_FEE_VAULT
+    #[cfg(feature = "optimism")]
+    pub const fn l1_fee_vault_address(&self) -> Address {
+        crate::constants::optimism::L1_FEE_VAULT
+    }
+
+    /// Returns the addresses of the precompiles that are active at the given timestamp, in
+    /// ascending order.
+    ///
+    /// The timestamp is assumed to be after Bedrock, which activates all precompiles up to
+    /// Istanbul (`0x01` to `0x09`) on every OP stack chain. On top of these, Cancun activates the
+    /// KZG point evaluation precompile (`0x0a`) and Fjord activates the
+    /// [`P256VERIFY_PRECOMPILE`](crate::P256VERIFY_PRECOMPILE).
+    #[cfg(feature = "optimism")]
+    pub fn active_precompiles_at_timestamp(&self, timestamp: u64) -> Vec<Address> {
+        let mut precompiles = (1..=9).map(Address::with_last_byte).collect::<Vec<_>>();
+        if self.is_cancun_active_at_timestamp(timestamp) {
+            precompiles.push(Address::with_last_byte(0x0a));
+        }
+        if self.is_fork_active_at_timestamp(Hardfork::Fjord, timestamp) {
+            precompiles.push(crate::constants::optimism::P256VERIFY_PRECOMPILE);
+        }
+        precompiles
+    }
+
+    /// Returns the numbers of the EIPs that are active at the given block and timestamp, in
+    /// ascending order, derived from the hardforks of the chain.
+    ///
+    /// Block based forks are checked against the block, timestamp based forks against the
+    /// timestamp, and Paris against the Paris block of the chain if it is known. EIPs that a later
+    /// fork removed again, like EIP-1283 with Petersburg, are left out. On OP stack chains, the
+    /// EIPs of the forks specific to the OP stack are included as well, e.g. the P256VERIFY
+    /// precompile of RIP-7212 with Fjord, while the EIPs of the Ethereum forks are only included
+    /// if the OP stack fork that activates them is scheduled.
+    pub fn active_eips_at(&self, block: u64, timestamp: u64) -> Vec<u32> {
+        let head = Head { number: block, timestamp, ..Default::default() };
+        let is_active = |fork| match fork {
+            Hardfork::Paris => {
+                self.fork(fork).active_at_head(&head) ||
+                    self.is_paris_active_at_block(block).unwrap_or_default()
+            }
+            fork => self.fork(fork).active_at_head(&head),
+        };
+
+        #[cfg(not(feature = "optimism"))]
+        let hardfork_eips = HARDFORK_EIPS.iter();
+        #[cfg(feature = "optimism")]
+        let hardfork_eips = HARDFORK_EIPS.iter().chain(OP_HARDFORK_EIPS);
+        let mut eips = hardfork_eips
+            .filter(|(fork, _)| is_active(*fork))
+            .flat_map(|(_, eips)| eips.iter().copied())
+            .collect::<Vec<_>>();
+        if is_active(Hardfork::Petersburg) {
+            eips.retain(|eip| *eip != 1283);
+        }
+        #[cfg(feature = "optimism")]
+        if self.is_optimism() {
+            eips.retain(|eip| !OP_EXCLUDED_EIPS.contains(eip));
+        }
+        eips.sort_unstable();
+        eips.dedup();
+        eips
+    }
+
     /// Convenience method to check if a fork is active at a given timestamp.
     #[inline]
     pub fn is_fork_active_at_timestamp(&self, fork: Hardfork, timestamp: u64) -> bool {
@@ -671,6 +1274,16 @@ impl ChainSpec {
         self.fork(Hardfork::Homestead).active_at_block(block_number)
     }
 
+    /// Returns `true` if the chain activates [`Hardfork::Dao`] after genesis, i.e. if the block
+    /// of the fork has to be executed with the irregular state change of the DAO fork.
+    ///
+    /// This is only the case for Ethereum mainnet. Chains that activate the fork at genesis have
+    /// no DAO accounts to drain, and OP stack chains do not schedule it at all.
+    #[inline]
+    pub fn requires_dao_hardfork(&self) -> bool {
+        matches!(self.fork(Hardfork::Dao), ForkCondition::Block(block) if block > 0)
+    }
+
     /// The Paris hardfork (merge) is activated via block number. If we have knowledge of the block,
     /// this function will return true if the block number is greater than or equal to the Paris
     /// (merge) block.
@@ -685,6 +1298,135 @@ impl ChainSpec {
         self.fork(Hardfork::Bedrock).active_at_block(block_number)
     }
 
+    /// Convenience method to check if [`Hardfork::Regolith`] is active at a given timestamp.
+    ///
+    /// Regolith is scheduled by block number instead of timestamp on some chains. Such a
+    /// condition is only considered active if Regolith is active since genesis, because the block
+    /// of a timestamp is not known here. Use [`Self::is_fork_active_at_block`] for these chains.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn is_regolith_active_at_timestamp(&self, timestamp: u64) -> bool {
+        match self.fork(Hardfork::Regolith) {
+            ForkCondition::Block(block) => block == 0,
+            condition => condition.active_at_timestamp(timestamp),
+        }
+    }
+
+    /// Convenience method to check if [`Hardfork::Canyon`] is active at a given timestamp.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn is_canyon_active_at_timestamp(&self, timestamp: u64) -> bool {
+        self.is_fork_active_at_timestamp(Hardfork::Canyon, timestamp)
+    }
+
+    /// Convenience method to check if [`Hardfork::Ecotone`] is active at a given timestamp.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn is_ecotone_active_at_timestamp(&self, timestamp: u64) -> bool {
+        self.is_fork_active_at_timestamp(Hardfork::Ecotone, timestamp)
+    }
+
+    /// Convenience method to check if [`Hardfork::Fjord`] is active at a given timestamp.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn is_fjord_active_at_timestamp(&self, timestamp: u64) -> bool {
+        self.is_fork_active_at_timestamp(Hardfork::Fjord, timestamp)
+    }
+
+    /// Convenience method to check if [`Hardfork::Holocene`] is active at a given timestamp.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn is_holocene_active_at_timestamp(&self, timestamp: u64) -> bool {
+        self.is_fork_active_at_timestamp(Hardfork::Holocene, timestamp)
+    }
+
+    /// Convenience method to check if [`Hardfork::Isthmus`] is active at a given timestamp.
+    #[cfg(feature = "optimism")]
+    #[inline]
+    pub fn is_isthmus_active_at_timestamp(&self, timestamp: u64) -> bool {
+        self.is_fork_active_at_timestamp(Hardfork::Isthmus, timestamp)
+    }
+
+    /// Returns the [`BaseFeeParams`] of the block following the given parent block.
+    ///
+    /// From [`Hardfork::Holocene`] on, the EIP-1559 params are set by the system config of the
+    /// chain and encoded in the `extra_data` of every block: a version byte `0`, followed by the
+    /// big-endian `u32` base fee max change denominator and elasticity multiplier. `extra_data`
+    /// that does not encode them, including a zero denominator, is invalid.
+    ///
+    /// Before Holocene the params of the chain at the timestamp of the parent are returned, see
+    /// [`Self::base_fee_params_at_timestamp`]. The child of the last block before a fork that
+    /// changes the params already uses the new params, so prefer
+    /// [`Self::base_fee_params_from_header`] if the timestamp of the child is known.
+    #[cfg(feature = "optimism")]
+    pub fn next_block_base_fee_params(
+        &self,
+        parent_extra_data: &[u8],
+        parent_timestamp: u64,
+    ) -> Result<BaseFeeParams, HoloceneExtraDataError> {
+        if !self.is_holocene_active_at_timestamp(parent_timestamp) {
+            return Ok(self.base_fee_params_at_timestamp(parent_timestamp))
+        }
+        let (denominator, elasticity) = decode_holocene_extra_data(parent_extra_data)?;
+        Ok(BaseFeeParams::new(denominator as u128, elasticity as u128))
+    }
+
+    /// Returns the [`BaseFeeParams`] of the child of the given parent header, which has the given
+    /// timestamp.
+    ///
+    /// If [`Hardfork::Holocene`] is active at the parent, the params are decoded from the
+    /// `extra_data` of the parent, see [`Self::next_block_base_fee_params`]. Otherwise the params
+    /// of the [`BaseFeeParamsKind::Variable`] schedule at the timestamp of the child are returned,
+    /// like [`Self::base_fee_params_at_timestamp`].
+    #[cfg(feature = "optimism")]
+    pub fn base_fee_params_from_header(
+        &self,
+        header: &Header,
+        timestamp: u64,
+    ) -> Result<BaseFeeParams, HoloceneExtraDataError> {
+        if !self.is_holocene_active_at_timestamp(header.timestamp) {
+            return Ok(self.base_fee_params_at_timestamp(timestamp))
+        }
+        self.next_block_base_fee_params(&header.extra_data, header.timestamp)
+    }
+
+    /// Returns `true` if transactions of the given [EIP-2718] type are valid at the given
+    /// timestamp.
+    ///
+    /// Blob transactions are valid from Cancun, except on Optimism chains, which don't support
+    /// them, set code transactions from Prague, and deposit transactions only on Optimism chains.
+    /// Unknown types are never valid.
+    ///
+    /// The access list and dynamic fee transactions are introduced by forks that are scheduled by
+    /// block number, and the block of a timestamp is not known here. Such a fork is considered
+    /// active if it is active since genesis, or if Shanghai, which follows all block based forks,
+    /// is active at the timestamp.
+    ///
+    /// [EIP-2718]: https://eips.ethereum.org/EIPS/eip-2718
+    pub fn is_tx_type_supported(&self, tx_type: u8, timestamp: u64) -> bool {
+        let is_active = |fork| match self.fork(fork) {
+            ForkCondition::Block(block) => {
+                block == 0 || self.is_shanghai_active_at_timestamp(timestamp)
+            }
+            condition => condition.active_at_timestamp(timestamp),
+        };
+        match tx_type {
+            // legacy
+            0x00 => true,
+            // EIP-2930
+            0x01 => is_active(Hardfork::Berlin),
+            // EIP-1559
+            0x02 => is_active(Hardfork::London),
+            // EIP-4844
+            0x03 => !self.is_optimism() && self.is_cancun_active_at_timestamp(timestamp),
+            // EIP-7702
+            0x04 => self.is_prague_active_at_timestamp(timestamp),
+            // Optimism deposit
+            0x7e => self.is_optimism(),
+            _ => false,
+        }
+    }
+
     /// Creates a [`ForkFilter`] for the block described by [Head].
     pub fn fork_filter(&self, head: Head) -> ForkFilter {
         let forks = self.forks_iter().filter_map(|(_, condition)| {
@@ -835,7 +1577,12 @@ impl ChainSpec {
 impl From<Genesis> for ChainSpec {
     fn from(genesis: Genesis) -> Self {
         #[cfg(feature = "optimism")]
-        let optimism_genesis_info = OptimismGenesisInfo::extract_from(&genesis);
+        let op_genesis_config = OpGenesisConfig::extract_from(&genesis);
+        #[cfg(feature = "optimism")]
+        let optimism_genesis_info =
+            OptimismGenesisInfo::extract_from(&genesis, op_genesis_config.as_ref());
+        #[cfg(feature = "optimism")]
+        let op_rollup_genesis = OpRollupGenesis::extract_from(&genesis);
 
         // Block-based hardforks
         let hardfork_opts = [
@@ -861,20 +1608,15 @@ impl From<Genesis> for ChainSpec {
             .collect::<BTreeMap<_, _>>();
 
         // Paris
-        let paris_block_and_final_difficulty =
-            if let Some(ttd) = genesis.config.terminal_total_difficulty {
-                hardforks.insert(
-                    Hardfork::Paris,
-                    ForkCondition::TTD {
-                        total_difficulty: ttd,
-                        fork_block: genesis.config.merge_netsplit_block,
-                    },
-                );
-
-                genesis.config.merge_netsplit_block.map(|block| (block, ttd))
-            } else {
-                None
-            };
+        if let Some(ttd) = genesis.config.terminal_total_difficulty {
+            hardforks.insert(
+                Hardfork::Paris,
+                ForkCondition::TTD {
+                    total_difficulty: ttd,
+                    fork_block: genesis.config.merge_netsplit_block,
+                },
+            );
+        }
 
         // Time-based hardforks
         let time_hardfork_opts = [
@@ -889,6 +1631,12 @@ impl From<Genesis> for ChainSpec {
             (Hardfork::Ecotone, optimism_genesis_info.ecotone_time),
             #[cfg(feature = "optimism")]
             (Hardfork::Fjord, optimism_genesis_info.fjord_time),
+            #[cfg(feature = "optimism")]
+            (Hardfork::Granite, optimism_genesis_info.granite_time),
+            #[cfg(feature = "optimism")]
+            (Hardfork::Holocene, optimism_genesis_info.holocene_time),
+            #[cfg(feature = "optimism")]
+            (Hardfork::Isthmus, optimism_genesis_info.isthmus_time),
         ];
 
         let time_hardforks = time_hardfork_opts
@@ -900,6 +1648,8 @@ impl From<Genesis> for ChainSpec {
 
         hardforks.extend(time_hardforks);
 
+        let paris_block_and_final_difficulty = paris_block_and_final_difficulty(&hardforks);
+
         // NOTE: in full node, we prune all receipts except the deposit contract's. We do not
         // have the deployment block in the genesis file, so we use block zero. We use the same
         // deposit topic as the mainnet contract if we have the deposit contract address in the
@@ -917,17 +1667,169 @@ impl From<Genesis> for ChainSpec {
             deposit_contract,
             #[cfg(feature = "optimism")]
             base_fee_params: optimism_genesis_info.base_fee_params,
+            #[cfg(feature = "optimism")]
+            op_rollup_genesis,
+            #[cfg(feature = "optimism")]
+            op_genesis_config,
             ..Default::default()
         }
     }
 }
 
+/// The EIPs activated by each Ethereum hardfork, see [`ChainSpec::active_eips_at`].
+///
+/// EIP-1283 is activated by Constantinople and removed again by Petersburg.
+const HARDFORK_EIPS: &[(Hardfork, &[u32])] = &[
+    (Hardfork::Homestead, &[2, 7]),
+    (Hardfork::Dao, &[779]),
+    (Hardfork::Tangerine, &[150]),
+    (Hardfork::SpuriousDragon, &[155, 160, 161, 170]),
+    (Hardfork::Byzantium, &[100, 140, 196, 197, 198, 211, 214, 649, 658]),
+    (Hardfork::Constantinople, &[145, 1014, 1052, 1234, 1283]),
+    (Hardfork::Istanbul, &[152, 1108, 1344, 1884, 2028, 2200]),
+    (Hardfork::MuirGlacier, &[2384]),
+    (Hardfork::Berlin, &[2565, 2718, 2929, 2930]),
+    (Hardfork::London, &[1559, 3198, 3529, 3541, 3554]),
+    (Hardfork::ArrowGlacier, &[4345]),
+    (Hardfork::GrayGlacier, &[5133]),
+    (Hardfork::Paris, &[3675, 4399]),
+    (Hardfork::Shanghai, &[3651, 3855, 3860, 4895]),
+    (Hardfork::Cancun, &[1153, 4788, 4844, 5656, 6780, 7516]),
+    (Hardfork::Prague, &[2537, 2935, 6110, 7002, 7251, 7685, 7691, 7702]),
+];
+
+/// The EIPs activated by the OP stack hardforks on top of [`HARDFORK_EIPS`], see
+/// [`ChainSpec::active_eips_at`].
+///
+/// Canyon, Ecotone and Isthmus activate Shanghai, Cancun and Prague, which are scheduled next to
+/// them, so only the forks that are not paired with an Ethereum fork are listed. Fjord activates
+/// the P256VERIFY precompile of RIP-7212.
+#[cfg(feature = "optimism")]
+const OP_HARDFORK_EIPS: &[(Hardfork, &[u32])] = &[(Hardfork::Fjord, &[7212])];
+
+/// The EIPs of the Ethereum hardforks that are not activated on OP stack chains, like the deposit
+/// requests of EIP-6110 with Prague, see [`ChainSpec::active_eips_at`].
+#[cfg(feature = "optimism")]
+const OP_EXCLUDED_EIPS: &[u32] = &[6110];
+
+/// Derives the Paris block and the final total difficulty from the given hardforks.
+///
+/// OP stack chains are post-merge from genesis, so if Bedrock is configured, Paris is considered
+/// active at block zero with the terminal total difficulty of the Paris fork, or zero.
+fn paris_block_and_final_difficulty(
+    hardforks: &BTreeMap<Hardfork, ForkCondition>,
+) -> Option<(u64, U256)> {
+    #[cfg(feature = "optimism")]
+    if hardforks.contains_key(&Hardfork::Bedrock) {
+        let ttd = hardforks.get(&Hardfork::Paris).and_then(ForkCondition::ttd);
+        return Some((0, ttd.unwrap_or(U256::ZERO)))
+    }
+
+    match hardforks.get(&Hardfork::Paris) {
+        Some(ForkCondition::TTD { fork_block: Some(fork_block), total_difficulty }) => {
+            Some((*fork_block, *total_difficulty))
+        }
+        _ => None,
+    }
+}
+
+/// Returns a key that orders fork conditions by activation: forks activated by block, then by
+/// total difficulty only, then by timestamp, then forks that never activate.
+const fn activation_order(condition: ForkCondition) -> (u8, u64) {
+    match condition {
+        ForkCondition::Block(block) | ForkCondition::TTD { fork_block: Some(block), .. } => {
+            (0, block)
+        }
+        ForkCondition::TTD { fork_block: None, .. } => (1, 0),
+        ForkCondition::Timestamp(timestamp) => (2, timestamp),
+        ForkCondition::Never => (3, 0),
+    }
+}
+
+/// Error returned by [`ChainSpec::verify_genesis_hash`] if the pinned genesis hash does not match
+/// the hash of the genesis block computed from the genesis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenesisHashMismatch {
+    /// The pinned genesis hash.
+    pub expected: B256,
+    /// The hash of the genesis block computed from the genesis.
+    pub computed: B256,
+}
+
+impl core::fmt::Display for GenesisHashMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "genesis hash mismatch: expected {}, computed {}", self.expected, self.computed)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GenesisHashMismatch {}
+
+/// Error returned by [`ChainSpec::next_block_base_fee_params`] if the `extra_data` of a block from
+/// [`Hardfork::Holocene`] on does not encode its EIP-1559 params.
+#[cfg(feature = "optimism")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoloceneExtraDataError {
+    /// The `extra_data` is not exactly 9 bytes long.
+    InvalidLength(usize),
+    /// The version byte is not `0`.
+    InvalidVersion(u8),
+    /// The denominator is zero.
+    ZeroDenominator,
+}
+
+#[cfg(feature = "optimism")]
+impl core::fmt::Display for HoloceneExtraDataError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength(len) => {
+                write!(f, "holocene extra data must be 9 bytes long, got {len}")
+            }
+            Self::InvalidVersion(version) => {
+                write!(f, "unsupported holocene extra data version {version}")
+            }
+            Self::ZeroDenominator => {
+                write!(f, "holocene base fee max change denominator must not be zero")
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "optimism", feature = "std"))]
+impl std::error::Error for HoloceneExtraDataError {}
+
+/// Decodes the base fee max change denominator and elasticity multiplier from the `extra_data` of
+/// a block from [`Hardfork::Holocene`] on.
+#[cfg(feature = "optimism")]
+fn decode_holocene_extra_data(extra_data: &[u8]) -> Result<(u32, u32), HoloceneExtraDataError> {
+    let extra_data: &[u8; 9] = extra_data
+        .try_into()
+        .map_err(|_| HoloceneExtraDataError::InvalidLength(extra_data.len()))?;
+    if extra_data[0] != 0 {
+        return Err(HoloceneExtraDataError::InvalidVersion(extra_data[0]))
+    }
+    let denominator = u32::from_be_bytes(extra_data[1..5].try_into().unwrap());
+    let elasticity = u32::from_be_bytes(extra_data[5..9].try_into().unwrap());
+    if denominator == 0 {
+        return Err(HoloceneExtraDataError::ZeroDenominator)
+    }
+    Ok((denominator, elasticity))
+}
+
 /// A helper to build custom chain specs
 #[derive(Debug, Default, Clone)]
 pub struct ChainSpecBuilder {
     chain: Option<Chain>,
     genesis: Option<Genesis>,
+    genesis_hash: Option<B256>,
     hardforks: BTreeMap<Hardfork, ForkCondition>,
+    paris_block_and_final_difficulty: Option<(u64, U256)>,
+    base_fee_params: Option<BaseFeeParamsKind>,
+    prune_delete_limit: Option<usize>,
+    #[cfg(feature = "optimism")]
+    op_rollup_genesis: Option<OpRollupGenesis>,
+    #[cfg(feature = "optimism")]
+    op_genesis_config: Option<OpGenesisConfig>,
 }
 
 impl ChainSpecBuilder {
@@ -937,6 +1839,7 @@ impl ChainSpecBuilder {
             chain: Some(MAINNET.chain),
             genesis: Some(MAINNET.genesis.clone()),
             hardforks: MAINNET.hardforks.clone(),
+            ..Default::default()
         }
     }
 
@@ -952,6 +1855,91 @@ impl ChainSpecBuilder {
         self
     }
 
+    /// Set the genesis block from the given genesis json.
+    ///
+    /// # Panics
+    ///
+    /// If the json can't be deserialized into a [`Genesis`].
+    pub fn genesis_json(self, json: &str) -> Self {
+        self.genesis(serde_json::from_str(json).expect("Can't deserialize genesis json"))
+    }
+
+    /// Set the known hash of the genesis block, instead of computing it from the genesis.
+    pub const fn genesis_hash(mut self, genesis_hash: B256) -> Self {
+        self.genesis_hash = Some(genesis_hash);
+        self
+    }
+
+    /// Set the parameters that configure how a block's base fee is computed.
+    pub fn base_fee_params(mut self, base_fee_params: impl Into<BaseFeeParamsKind>) -> Self {
+        self.base_fee_params = Some(base_fee_params.into());
+        self
+    }
+
+    /// Set the delete limit for the pruner, per block.
+    pub const fn prune_delete_limit(mut self, prune_delete_limit: usize) -> Self {
+        self.prune_delete_limit = Some(prune_delete_limit);
+        self
+    }
+
+    /// Set the block the Paris hardfork activated at and the final total difficulty, instead of
+    /// deriving them from the Paris fork condition.
+    ///
+    /// This is required for chains like mainnet, whose Paris fork condition does not know the fork
+    /// block.
+    pub const fn paris_block_and_final_difficulty(
+        mut self,
+        paris_block: u64,
+        final_difficulty: U256,
+    ) -> Self {
+        self.paris_block_and_final_difficulty = Some((paris_block, final_difficulty));
+        self
+    }
+
+    /// Replace all forks of the spec with the given OP stack hardfork schedule.
+    #[cfg(feature = "optimism")]
+    pub fn op_hardforks(
+        mut self,
+        hardforks: impl IntoIterator<Item = (Hardfork, ForkCondition)>,
+    ) -> Self {
+        self.hardforks = hardforks.into_iter().collect();
+        self
+    }
+
+    /// Set the variable base fee params of an OP stack chain, derived from the forks that are
+    /// currently part of the spec, see [`BaseFeeParamsKind::from_op_hardforks`].
+    ///
+    /// This should be called after [`Self::op_hardforks`].
+    #[cfg(feature = "optimism")]
+    pub fn op_base_fee_params(self, fork_params: &[(Hardfork, BaseFeeParams)]) -> Self {
+        let base_fee_params = BaseFeeParamsKind::from_op_hardforks(&self.hardforks, fork_params);
+        self.base_fee_params(base_fee_params)
+    }
+
+    /// Set the genesis of the rollup, see [`ChainSpec::op_rollup_genesis`].
+    #[cfg(feature = "optimism")]
+    pub const fn op_rollup_genesis(mut self, op_rollup_genesis: OpRollupGenesis) -> Self {
+        self.op_rollup_genesis = Some(op_rollup_genesis);
+        self
+    }
+
+    /// Set the `optimism` section of the genesis config, see [`ChainSpec::op_genesis_config`].
+    ///
+    /// If this is not set, the section is parsed from the genesis.
+    #[cfg(feature = "optimism")]
+    pub const fn op_genesis_config(mut self, op_genesis_config: OpGenesisConfig) -> Self {
+        self.op_genesis_config = Some(op_genesis_config);
+        self
+    }
+
+    /// Set the defaults shared by all OP stack chains: the pruner delete limit is lowered to
+    /// account for the higher block frequency.
+    #[cfg(feature = "optimism")]
+    pub const fn op_defaults(mut self) -> Self {
+        self.prune_delete_limit = Some(1700);
+        self
+    }
+
     /// Add the given fork with the given activation condition to the spec.
     pub fn with_fork(mut self, fork: Hardfork, condition: ForkCondition) -> Self {
         self.hardforks.insert(fork, condition);
@@ -1110,6 +2098,32 @@ impl ChainSpecBuilder {
         self
     }
 
+    /// Enable Granite at genesis
+    #[cfg(feature = "optimism")]
+    pub fn granite_activated(mut self) -> Self {
+        self = self.fjord_activated();
+        self.hardforks.insert(Hardfork::Granite, ForkCondition::Timestamp(0));
+        self
+    }
+
+    /// Enable Holocene at genesis
+    #[cfg(feature = "optimism")]
+    pub fn holocene_activated(mut self) -> Self {
+        self = self.granite_activated();
+        self.hardforks.insert(Hardfork::Holocene, ForkCondition::Timestamp(0));
+        self
+    }
+
+    /// Enable Isthmus at genesis
+    #[cfg(feature = "optimism")]
+    pub fn isthmus_activated(mut self) -> Self {
+        self = self.holocene_activated();
+        // Isthmus also activates changes from L1's Prague hardfork
+        self.hardforks.insert(Hardfork::Prague, ForkCondition::Timestamp(0));
+        self.hardforks.insert(Hardfork::Isthmus, ForkCondition::Timestamp(0));
+        self
+    }
+
     /// Build the resulting [`ChainSpec`].
     ///
     /// # Panics
@@ -1117,23 +2131,27 @@ impl ChainSpecBuilder {
     /// This function panics if the chain ID and genesis is not set ([`Self::chain`] and
     /// [`Self::genesis`])
     pub fn build(self) -> ChainSpec {
-        let paris_block_and_final_difficulty = {
-            self.hardforks.get(&Hardfork::Paris).and_then(|cond| {
-                if let ForkCondition::TTD { fork_block, total_difficulty } = cond {
-                    fork_block.map(|fork_block| (fork_block, *total_difficulty))
-                } else {
-                    None
-                }
-            })
-        };
+        let paris_block_and_final_difficulty = self
+            .paris_block_and_final_difficulty
+            .or_else(|| paris_block_and_final_difficulty(&self.hardforks));
+        let defaults = ChainSpec::default();
+        let genesis = self.genesis.expect("The genesis is required");
+        #[cfg(feature = "optimism")]
+        let op_genesis_config =
+            self.op_genesis_config.or_else(|| OpGenesisConfig::extract_from(&genesis));
         ChainSpec {
             chain: self.chain.expect("The chain is required"),
-            genesis: self.genesis.expect("The genesis is required"),
-            genesis_hash: None,
+            genesis,
+            genesis_hash: self.genesis_hash,
             hardforks: self.hardforks,
             paris_block_and_final_difficulty,
             deposit_contract: None,
-            ..Default::default()
+            base_fee_params: self.base_fee_params.unwrap_or(defaults.base_fee_params),
+            prune_delete_limit: self.prune_delete_limit.unwrap_or(defaults.prune_delete_limit),
+            #[cfg(feature = "optimism")]
+            op_rollup_genesis: self.op_rollup_genesis,
+            #[cfg(feature = "optimism")]
+            op_genesis_config,
         }
     }
 }
@@ -1144,6 +2162,7 @@ impl From<&Arc<ChainSpec>> for ChainSpecBuilder {
             chain: Some(value.chain),
             genesis: Some(value.genesis.clone()),
             hardforks: value.hardforks.clone(),
+            ..Default::default()
         }
     }
 }
@@ -1176,54 +2195,90 @@ struct OptimismGenesisInfo {
     canyon_time: Option<u64>,
     ecotone_time: Option<u64>,
     fjord_time: Option<u64>,
+    granite_time: Option<u64>,
+    holocene_time: Option<u64>,
+    isthmus_time: Option<u64>,
     #[serde(skip)]
     base_fee_params: BaseFeeParamsKind,
 }
 
+/// The OP stack specific `optimism` section of the genesis `config`.
 #[cfg(feature = "optimism")]
-#[derive(Debug, Eq, PartialEq, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct OptimismBaseFeeInfo {
-    eip1559_elasticity: Option<u64>,
-    eip1559_denominator: Option<u64>,
-    eip1559_denominator_canyon: Option<u64>,
+pub struct OpGenesisConfig {
+    /// The EIP-1559 elasticity multiplier.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eip1559_elasticity: Option<u64>,
+    /// The EIP-1559 base fee max change denominator.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eip1559_denominator: Option<u64>,
+    /// The EIP-1559 base fee max change denominator from [`Hardfork::Canyon`] on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub eip1559_denominator_canyon: Option<u64>,
+    /// The recipient of the L1 data fees of all transactions, which is the
+    /// [`L1_FEE_VAULT`](crate::L1_FEE_VAULT) on chains that do not override it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub l1_fee_recipient: Option<Address>,
+}
+
+#[cfg(feature = "optimism")]
+impl OpGenesisConfig {
+    /// Extracts the `optimism` section from the given genesis, if it is present and valid.
+    pub fn extract_from(genesis: &Genesis) -> Option<Self> {
+        genesis.config.extra_fields.get_deserialized::<Self>("optimism")?.ok()
+    }
+
+    /// Returns the base fee params configured by the section, or `None` if it does not set both
+    /// the elasticity and the denominator.
+    ///
+    /// If the Canyon denominator is set, the params change at [`Hardfork::Canyon`].
+    pub(crate) fn base_fee_params(&self) -> Option<BaseFeeParamsKind> {
+        let elasticity = self.eip1559_elasticity? as u128;
+        let denominator = self.eip1559_denominator? as u128;
+        let london = BaseFeeParams::new(denominator, elasticity);
+        Some(match self.eip1559_denominator_canyon {
+            Some(canyon_denominator) => BaseFeeParamsKind::Variable(
+                vec![
+                    (Hardfork::London, london),
+                    (Hardfork::Canyon, BaseFeeParams::new(canyon_denominator as u128, elasticity)),
+                ]
+                .into(),
+            ),
+            None => london.into(),
+        })
+    }
+
+    /// Derives the section from the given base fee params, the inverse of the base fee params
+    /// derived from the section in [`ChainSpec::from_genesis_json`].
+    ///
+    /// Returns `None` if the params are variable but do not configure [`Hardfork::London`].
+    fn from_base_fee_params(base_fee_params: &BaseFeeParamsKind) -> Option<Self> {
+        let (london, canyon) = match base_fee_params {
+            BaseFeeParamsKind::Constant(params) => (*params, None),
+            BaseFeeParamsKind::Variable(ForkBaseFeeParams(params)) => {
+                let params_at = |fork| params.iter().find(|(f, _)| *f == fork).map(|(_, p)| *p);
+                (params_at(Hardfork::London)?, params_at(Hardfork::Canyon))
+            }
+        };
+        Some(Self {
+            eip1559_elasticity: Some(london.elasticity_multiplier as u64),
+            eip1559_denominator: Some(london.max_change_denominator as u64),
+            eip1559_denominator_canyon: canyon.map(|params| params.max_change_denominator as u64),
+            l1_fee_recipient: None,
+        })
+    }
 }
 
 #[cfg(feature = "optimism")]
 impl OptimismGenesisInfo {
-    fn extract_from(genesis: &Genesis) -> Self {
+    fn extract_from(genesis: &Genesis, op_genesis_config: Option<&OpGenesisConfig>) -> Self {
         let mut optimism_genesis_info: Self =
             genesis.config.extra_fields.deserialize_as().unwrap_or_default();
 
-        if let Some(Ok(optimism_base_fee_info)) =
-            genesis.config.extra_fields.get_deserialized::<OptimismBaseFeeInfo>("optimism")
+        if let Some(base_fee_params) = op_genesis_config.and_then(OpGenesisConfig::base_fee_params)
         {
-            if let (Some(elasticity), Some(denominator)) = (
-                optimism_base_fee_info.eip1559_elasticity,
-                optimism_base_fee_info.eip1559_denominator,
-            ) {
-                let base_fee_params = if let Some(canyon_denominator) =
-                    optimism_base_fee_info.eip1559_denominator_canyon
-                {
-                    BaseFeeParamsKind::Variable(
-                        vec![
-                            (
-                                Hardfork::London,
-                                BaseFeeParams::new(denominator as u128, elasticity as u128),
-                            ),
-                            (
-                                Hardfork::Canyon,
-                                BaseFeeParams::new(canyon_denominator as u128, elasticity as u128),
-                            ),
-                        ]
-                        .into(),
-                    )
-                } else {
-                    BaseFeeParams::new(denominator as u128, elasticity as u128).into()
-                };
-
-                optimism_genesis_info.base_fee_params = base_fee_params;
-            }
+            optimism_genesis_info.base_fee_params = base_fee_params;
         }
 
         optimism_genesis_info
@@ -1234,7 +2289,7 @@ impl OptimismGenesisInfo {
 mod tests {
     use super::*;
     use alloy_chains::Chain;
-    use alloy_genesis::{ChainConfig, GenesisAccount};
+    use alloy_genesis::ChainConfig;
     use alloy_primitives::{b256, hex};
     use reth_ethereum_forks::{ForkCondition, ForkHash, ForkId, Head};
     use reth_trie_common::TrieAccount;
@@ -1888,7 +2943,23 @@ Post-merge hard forks (timestamp based):
                 ),
                 (
                     Head { number: 0, timestamp: 1720627201, ..Default::default() },
-                    ForkId { hash: ForkHash([0xe4, 0x01, 0x0e, 0xb9]), next: 0 },
+                    ForkId { hash: ForkHash([0xe4, 0x01, 0x0e, 0xb9]), next: 1726070401 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1726070400, ..Default::default() },
+                    ForkId { hash: ForkHash([0xe4, 0x01, 0x0e, 0xb9]), next: 1726070401 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1726070401, ..Default::default() },
+                    ForkId { hash: ForkHash([0xbc, 0x38, 0xf9, 0xca]), next: 1736445601 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1736445601, ..Default::default() },
+                    ForkId { hash: ForkHash([0x3a, 0x2a, 0xf1, 0x83]), next: 1746806401 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1746806401, ..Default::default() },
+                    ForkId { hash: ForkHash([0x86, 0x72, 0x8b, 0x4e]), next: 0 },
                 ),
             ],
         );
@@ -1926,7 +2997,23 @@ Post-merge hard forks (timestamp based):
                 ),
                 (
                     Head { number: 0, timestamp: 1716998400, ..Default::default() },
-                    ForkId { hash: ForkHash([0x54, 0x0a, 0x8c, 0x5d]), next: 0 },
+                    ForkId { hash: ForkHash([0x54, 0x0a, 0x8c, 0x5d]), next: 1723478400 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1723478399, ..Default::default() },
+                    ForkId { hash: ForkHash([0x54, 0x0a, 0x8c, 0x5d]), next: 1723478400 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1723478400, ..Default::default() },
+                    ForkId { hash: ForkHash([0x75, 0xde, 0xa4, 0x1e]), next: 1732633200 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1732633200, ..Default::default() },
+                    ForkId { hash: ForkHash([0x4a, 0x1c, 0x79, 0x2e]), next: 1744905600 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1744905600, ..Default::default() },
+                    ForkId { hash: ForkHash([0x6c, 0x62, 0x5e, 0xe1]), next: 0 },
                 ),
             ],
         );
@@ -1983,7 +3070,23 @@ Post-merge hard forks (timestamp based):
                 ),
                 (
                     Head { number: 0, timestamp: 1716998400, ..Default::default() },
-                    ForkId { hash: ForkHash([0x4e, 0x45, 0x7a, 0x49]), next: 0 },
+                    ForkId { hash: ForkHash([0x4e, 0x45, 0x7a, 0x49]), next: 1723478400 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1723478399, ..Default::default() },
+                    ForkId { hash: ForkHash([0x4e, 0x45, 0x7a, 0x49]), next: 1723478400 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1723478400, ..Default::default() },
+                    ForkId { hash: ForkHash([0x5e, 0xdf, 0xa3, 0xb6]), next: 1732633200 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1732633200, ..Default::default() },
+                    ForkId { hash: ForkHash([0x8b, 0x5e, 0x76, 0x29]), next: 1744905600 },
+                ),
+                (
+                    Head { number: 0, timestamp: 1744905600, ..Default::default() },
+                    ForkId { hash: ForkHash([0x06, 0x0a, 0x4d, 0x1d]), next: 0 },
                 ),
             ],
         );
@@ -2639,6 +3742,15 @@ Post-merge hard forks (timestamp based):
             .paris_activated()
             .build();
         assert_eq!(paris_chainspec.paris_block_and_final_difficulty, Some((0, U256::ZERO)));
+
+        // the Paris fork condition of mainnet does not know the fork block
+        let builder = ChainSpecBuilder::mainnet();
+        assert_eq!(builder.clone().build().paris_block_and_final_difficulty, None);
+        let final_difficulty = MAINNET.get_final_paris_total_difficulty().unwrap();
+        let spec = builder.paris_block_and_final_difficulty(15537394, final_difficulty).build();
+        assert_eq!(spec.paris_block_and_final_difficulty, MAINNET.paris_block_and_final_difficulty);
+        assert_eq!(spec.final_paris_total_difficulty(15537393), None);
+        assert_eq!(spec.final_paris_total_difficulty(15537394), Some(final_difficulty));
     }
 
     #[test]
@@ -2684,6 +3796,15 @@ Post-merge hard forks (timestamp based):
             .active_at_ttd(HOLESKY.genesis.difficulty, HOLESKY.genesis.difficulty));
     }
 
+    #[test]
+    fn requires_dao_hardfork() {
+        assert!(MAINNET.requires_dao_hardfork());
+        assert!(!SEPOLIA.requires_dao_hardfork());
+        assert!(!DEV.requires_dao_hardfork());
+        #[cfg(feature = "optimism")]
+        assert!(!crate::OP_MAINNET.requires_dao_hardfork());
+    }
+
     #[test]
     fn test_genesis_format_deserialization() {
         // custom genesis with chain config
@@ -2775,47 +3896,568 @@ Post-merge hard forks (timestamp based):
 
     #[test]
     #[cfg(feature = "optimism")]
-    fn base_sepolia_genesis() {
-        let genesis = BASE_SEPOLIA.genesis_header();
-        assert_eq!(
-            genesis.hash_slow(),
-            b256!("0dcc9e089e30b90ddfc55be9a37dd15bc551aeee999d2e2b51414c54eaf934e4")
-        );
-        let base_fee = genesis
-            .next_block_base_fee(BASE_SEPOLIA.base_fee_params_at_timestamp(genesis.timestamp))
-            .unwrap();
-        // <https://base-sepolia.blockscout.com/block/1>
-        assert_eq!(base_fee, 980000000);
+    fn base_fee_params_at_canyon_transition() {
+        let london = BaseFeeParams::new(50, 6);
+        let canyon = BaseFeeParams::new(250, 6);
+        let canyon_time = 1704992401;
+
+        for spec in [&*OP_MAINNET, &*BASE_MAINNET] {
+            assert_eq!(spec.base_fee_params_at_timestamp(0), london);
+            assert_eq!(spec.base_fee_params_at_timestamp(canyon_time - 1), london);
+            assert_eq!(spec.base_fee_params_at_timestamp(canyon_time), canyon);
+            assert_eq!(spec.base_fee_params_at_timestamp(u64::MAX), canyon);
+        }
+
+        let spec = ChainSpec {
+            base_fee_params: BaseFeeParamsKind::Variable(Vec::new().into()),
+            ..Default::default()
+        };
+        assert_eq!(spec.base_fee_params_at_timestamp(0), BaseFeeParams::ethereum());
     }
 
     #[test]
     #[cfg(feature = "optimism")]
-    fn op_sepolia_genesis() {
-        let genesis = OP_SEPOLIA.genesis_header();
-        assert_eq!(
-            genesis.hash_slow(),
-            b256!("102de6ffb001480cc9b8b548fd05c34cd4f46ae4aa91759393db90ea0409887d")
-        );
-        let base_fee = genesis
-            .next_block_base_fee(OP_SEPOLIA.base_fee_params_at_timestamp(genesis.timestamp))
-            .unwrap();
-        // <https://optimism-sepolia.blockscout.com/block/1>
-        assert_eq!(base_fee, 980000000);
-    }
+    fn canyon_base_fee_params_change() {
+        use crate::constants::optimism::{
+            BASE_SEPOLIA_BASE_FEE_PARAMS, BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS, OP_BASE_FEE_PARAMS,
+            OP_CANYON_BASE_FEE_PARAMS, OP_SEPOLIA_BASE_FEE_PARAMS,
+            OP_SEPOLIA_CANYON_BASE_FEE_PARAMS,
+        };
 
-    #[test]
-    fn latest_eth_mainnet_fork_id() {
-        assert_eq!(
-            ForkId { hash: ForkHash([0x9f, 0x3d, 0x22, 0x54]), next: 0 },
-            MAINNET.latest_fork_id()
-        )
+        // Canyon only raises the denominator, the elasticity is unchanged
+        assert_eq!(OP_BASE_FEE_PARAMS, BaseFeeParams::new(50, 6));
+        assert_eq!(OP_CANYON_BASE_FEE_PARAMS, BaseFeeParams::new(250, 6));
+        assert_eq!(OP_SEPOLIA_BASE_FEE_PARAMS, BaseFeeParams::new(50, 6));
+        assert_eq!(OP_SEPOLIA_CANYON_BASE_FEE_PARAMS, BaseFeeParams::new(250, 6));
+        assert_eq!(BASE_SEPOLIA_BASE_FEE_PARAMS, BaseFeeParams::new(50, 10));
+        assert_eq!(BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS, BaseFeeParams::new(250, 10));
+
+        for (spec, before, after) in [
+            (&*OP_MAINNET, OP_BASE_FEE_PARAMS, OP_CANYON_BASE_FEE_PARAMS),
+            (&*BASE_MAINNET, OP_BASE_FEE_PARAMS, OP_CANYON_BASE_FEE_PARAMS),
+            (&*OP_SEPOLIA, OP_SEPOLIA_BASE_FEE_PARAMS, OP_SEPOLIA_CANYON_BASE_FEE_PARAMS),
+            (&*BASE_SEPOLIA, BASE_SEPOLIA_BASE_FEE_PARAMS, BASE_SEPOLIA_CANYON_BASE_FEE_PARAMS),
+        ] {
+            assert_eq!(spec.canyon_base_fee_params_change(), Some((before, after)));
+        }
+        assert_eq!(MAINNET.canyon_base_fee_params_change(), None);
     }
 
-    #[cfg(feature = "optimism")]
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn op_hardforks_iter() {
+        let canyon = ForkCondition::Timestamp(1704992401);
+        let ecotone = ForkCondition::Timestamp(1710374401);
+        let fjord = ForkCondition::Timestamp(1720627201);
+
+        for spec in [&*OP_MAINNET, &*BASE_MAINNET] {
+            let forks = spec.op_hardforks_iter().collect::<Vec<_>>();
+            assert!(forks.iter().all(|(fork, _)| fork.is_optimism()));
+            assert!(forks.contains(&(Hardfork::Canyon, canyon)));
+            assert!(forks.contains(&(Hardfork::Ecotone, ecotone)));
+            assert!(forks.contains(&(Hardfork::Fjord, fjord)));
+
+            // Regolith is active at genesis
+            assert_eq!(spec.next_op_fork_after(0), Some((Hardfork::Canyon, 1704992401)));
+            assert_eq!(spec.next_op_fork_after(1704992400), Some((Hardfork::Canyon, 1704992401)));
+            assert_eq!(spec.next_op_fork_after(1704992401), Some((Hardfork::Ecotone, 1710374401)));
+            assert_eq!(spec.next_op_fork_after(1720627201), Some((Hardfork::Granite, 1726070401)));
+            assert_eq!(spec.next_op_fork_after(1726070401), Some((Hardfork::Holocene, 1736445601)));
+            assert_eq!(spec.next_op_fork_after(1746806401), None);
+        }
+
+        assert_eq!(MAINNET.op_hardforks_iter().count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn holocene_isthmus_timeline() {
+        for (spec, granite, holocene, isthmus) in [
+            (&*OP_MAINNET, 1726070401, 1736445601, 1746806401),
+            (&*BASE_MAINNET, 1726070401, 1736445601, 1746806401),
+            (&*OP_SEPOLIA, 1723478400, 1732633200, 1744905600),
+            (&*BASE_SEPOLIA, 1723478400, 1732633200, 1744905600),
+        ] {
+            let forks = spec.fork_timeline().into_iter().map(|(fork, _)| fork).collect::<Vec<_>>();
+            let position = |fork| forks.iter().position(|f| *f == fork).unwrap();
+            assert_eq!(position(Hardfork::Granite), position(Hardfork::Fjord) + 1);
+            assert_eq!(position(Hardfork::Holocene), position(Hardfork::Granite) + 1);
+            assert_eq!(position(Hardfork::Prague), position(Hardfork::Holocene) + 1);
+            assert_eq!(position(Hardfork::Isthmus), position(Hardfork::Prague) + 1);
+
+            assert!(!spec.is_fork_active_at_timestamp(Hardfork::Granite, granite - 1));
+            assert!(spec.is_fork_active_at_timestamp(Hardfork::Granite, granite));
+            assert!(!spec.is_holocene_active_at_timestamp(holocene - 1));
+            assert!(spec.is_holocene_active_at_timestamp(holocene));
+            assert!(!spec.is_isthmus_active_at_timestamp(isthmus - 1));
+            assert!(spec.is_isthmus_active_at_timestamp(isthmus));
+            assert_eq!(spec.next_op_fork_after(holocene), Some((Hardfork::Isthmus, isthmus)));
+
+            // Isthmus activates L1's Prague changes, like EIP-7702 transactions
+            assert!(!spec.is_prague_active_at_timestamp(isthmus - 1));
+            assert!(spec.is_prague_active_at_timestamp(isthmus));
+            assert!(!spec.is_tx_type_supported(0x04, isthmus - 1));
+            assert!(spec.is_tx_type_supported(0x04, isthmus));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn holocene_base_fee_params() {
+        let spec = ChainSpecBuilder::default()
+            .chain(Chain::base_mainnet())
+            .genesis(Genesis::default())
+            .fjord_activated()
+            .with_fork(Hardfork::Holocene, ForkCondition::Timestamp(10))
+            .op_base_fee_params(&OP_FORK_BASE_FEE_PARAMS)
+            .build();
+        let canyon = crate::constants::optimism::OP_CANYON_BASE_FEE_PARAMS;
+        let extra_data = |denominator: u32, elasticity: u32| {
+            [&[0][..], &denominator.to_be_bytes(), &elasticity.to_be_bytes()].concat()
+        };
+
+        // before Holocene the extra data is ignored
+        assert_eq!(spec.next_block_base_fee_params(&[1, 2, 3], 9), Ok(canyon));
+        assert_eq!(
+            spec.next_block_base_fee_params(&extra_data(8, 2), 10),
+            Ok(BaseFeeParams::new(8, 2))
+        );
+        assert_eq!(
+            spec.next_block_base_fee_params(&extra_data(0, 0), 10),
+            Err(HoloceneExtraDataError::ZeroDenominator)
+        );
+
+        assert_eq!(
+            spec.next_block_base_fee_params(&[], 10),
+            Err(HoloceneExtraDataError::InvalidLength(0))
+        );
+        let mut versioned = extra_data(8, 2);
+        versioned[0] = 1;
+        assert_eq!(
+            spec.next_block_base_fee_params(&versioned, 10),
+            Err(HoloceneExtraDataError::InvalidVersion(1))
+        );
+        assert_eq!(
+            spec.next_block_base_fee_params(&extra_data(0, 2), 10),
+            Err(HoloceneExtraDataError::ZeroDenominator)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_fee_params_from_header() {
+        let spec = ChainSpecBuilder::default()
+            .chain(Chain::base_mainnet())
+            .genesis(Genesis::default())
+            .fjord_activated()
+            .with_fork(Hardfork::Holocene, ForkCondition::Timestamp(10))
+            .op_base_fee_params(&OP_FORK_BASE_FEE_PARAMS)
+            .build();
+        let canyon = crate::constants::optimism::OP_CANYON_BASE_FEE_PARAMS;
+        let header = |timestamp, extra_data: &[u8]| Header {
+            timestamp,
+            extra_data: extra_data.to_vec().into(),
+            base_fee_per_gas: Some(1_000_000_000),
+            gas_limit: 30_000_000,
+            gas_used: 30_000_000,
+            ..Default::default()
+        };
+        let params = [0, 0, 0, 0, 100, 0, 0, 0, 4];
+
+        // the parent is before Holocene, so its extra data is not decoded
+        assert_eq!(spec.base_fee_params_from_header(&header(9, b"reth"), 10), Ok(canyon));
+        assert_eq!(
+            spec.base_fee_params_from_header(&header(10, &params), 11),
+            Ok(BaseFeeParams::new(100, 4))
+        );
+        // zero and malformed params are invalid from Holocene on
+        assert_eq!(
+            spec.base_fee_params_from_header(&header(10, &[0; 9]), 11),
+            Err(HoloceneExtraDataError::ZeroDenominator)
+        );
+        assert_eq!(
+            spec.base_fee_params_from_header(&header(10, b"reth"), 11),
+            Err(HoloceneExtraDataError::InvalidLength(4))
+        );
+
+        // a full block raises the base fee by the elasticity minus one over the denominator
+        assert_eq!(spec.next_block_base_fee(&header(10, &params), 11), Some(1_030_000_000));
+        assert_eq!(spec.next_block_base_fee(&header(9, &params), 10), Some(1_020_000_000));
+        assert_eq!(spec.next_block_base_fee(&header(10, &[0; 9]), 11), None);
+    }
+
+    #[test]
+    fn fork_timeline() {
+        let timeline = MAINNET.fork_timeline();
+        let forks = timeline.iter().map(|(fork, _)| *fork).collect::<Vec<_>>();
+        assert_eq!(forks.len(), MAINNET.hardforks.len());
+        let position = |fork| forks.iter().position(|f| *f == fork).unwrap();
+        assert_eq!(position(Hardfork::Paris), position(Hardfork::GrayGlacier) + 1);
+        assert_eq!(position(Hardfork::Shanghai), position(Hardfork::Paris) + 1);
+
+        // timestamp forks are ordered by activation, and never activating forks are skipped
+        let spec = ChainSpecBuilder::mainnet()
+            .with_fork(
+                Hardfork::Paris,
+                ForkCondition::TTD { fork_block: None, total_difficulty: U256::ZERO },
+            )
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(20))
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(10))
+            .with_fork(Hardfork::Prague, ForkCondition::Never)
+            .build();
+        let timeline = spec.fork_timeline();
+        let tail = timeline.iter().rev().take(3).map(|(fork, _)| *fork).collect::<Vec<_>>();
+        assert_eq!(tail, [Hardfork::Shanghai, Hardfork::Cancun, Hardfork::Paris]);
+        assert!(timeline.iter().all(|(fork, _)| *fork != Hardfork::Prague));
+
+        #[cfg(feature = "optimism")]
+        assert_eq!(BASE_MAINNET.fork_timeline(), BASE_MAINNET_HARDFORKS);
+    }
+
+    #[test]
+    fn tx_type_supported() {
+        let cancun = 1710338135;
+        assert!(MAINNET.is_tx_type_supported(0x00, 0));
+        assert!(!MAINNET.is_tx_type_supported(0x02, 0));
+        assert!(MAINNET.is_tx_type_supported(0x02, cancun));
+        assert!(!MAINNET.is_tx_type_supported(0x03, cancun - 1));
+        assert!(MAINNET.is_tx_type_supported(0x03, cancun));
+        assert!(!MAINNET.is_tx_type_supported(0x04, u64::MAX));
+        assert!(!MAINNET.is_tx_type_supported(0x7e, cancun));
+        assert!(!MAINNET.is_tx_type_supported(0x05, cancun));
+
+        // forks scheduled at genesis are active at any timestamp
+        let spec = ChainSpecBuilder::mainnet()
+            .london_activated()
+            .with_fork(Hardfork::Prague, ForkCondition::Timestamp(10))
+            .build();
+        assert!(spec.is_tx_type_supported(0x01, 0));
+        assert!(spec.is_tx_type_supported(0x02, 0));
+        assert!(!spec.is_tx_type_supported(0x04, 9));
+        assert!(spec.is_tx_type_supported(0x04, 10));
+
+        #[cfg(feature = "optimism")]
+        {
+            assert!(BASE_MAINNET.is_tx_type_supported(0x7e, 0));
+            assert!(BASE_MAINNET.is_tx_type_supported(0x02, 0));
+            assert!(!BASE_MAINNET.is_tx_type_supported(0x03, u64::MAX));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_mainnet_schedule_parity() {
+        // forks that are intentionally scheduled on only one of the chains
+        const OP_MAINNET_ONLY: &[Hardfork] = &[];
+        const BASE_MAINNET_ONLY: &[Hardfork] = &[];
+
+        let op = OP_MAINNET.fork_timeline();
+        let base = BASE_MAINNET.fork_timeline();
+        let missing = |timeline: &[(Hardfork, ForkCondition)],
+                       other: &[(Hardfork, ForkCondition)],
+                       allowed: &[Hardfork]| {
+            timeline
+                .iter()
+                .map(|(fork, _)| *fork)
+                .filter(|fork| !allowed.contains(fork))
+                .filter(|fork| other.iter().all(|(other, _)| other != fork))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(missing(&op, &base, OP_MAINNET_ONLY), [], "forks missing on Base mainnet");
+        assert_eq!(missing(&base, &op, BASE_MAINNET_ONLY), [], "forks missing on OP mainnet");
+
+        // the forks both chains schedule activate in the same order
+        let common = |timeline: &[(Hardfork, ForkCondition)]| {
+            timeline
+                .iter()
+                .map(|(fork, _)| *fork)
+                .filter(|fork| !OP_MAINNET_ONLY.contains(fork) && !BASE_MAINNET_ONLY.contains(fork))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(common(&op), common(&base));
+
+        // The activation times are not compared, because they may genuinely differ. Currently
+        // OP mainnet carries the legacy pre-Bedrock history, so Berlin and the forks activated
+        // with Bedrock are scheduled at later blocks, while Base mainnet started at Bedrock and
+        // activates them at genesis. The timestamp forks activate at the same time on both chains.
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn optimism_chain_ids() {
+        use crate::{
+            BASE_MAINNET_CHAIN_ID, BASE_SEPOLIA_CHAIN_ID, OP_MAINNET_CHAIN_ID, OP_SEPOLIA_CHAIN_ID,
+        };
+
+        assert_eq!(OP_MAINNET.chain.id(), OP_MAINNET_CHAIN_ID);
+        assert_eq!(OP_SEPOLIA.chain.id(), OP_SEPOLIA_CHAIN_ID);
+        assert_eq!(BASE_MAINNET.chain.id(), BASE_MAINNET_CHAIN_ID);
+        assert_eq!(BASE_SEPOLIA.chain.id(), BASE_SEPOLIA_CHAIN_ID);
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn l1_block_info_address() {
+        let l1_block = address!("4200000000000000000000000000000000000015");
+        assert_eq!(OP_MAINNET.l1_block_info_address(), l1_block);
+        assert_eq!(BASE_MAINNET.l1_block_info_address(), l1_block);
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_mainnet_fee_vaults() {
+        let vaults = [
+            (
+                BASE_MAINNET.sequencer_fee_vault_address(),
+                "4200000000000000000000000000000000000011",
+            ),
+            (BASE_MAINNET.base_fee_vault_address(), "4200000000000000000000000000000000000019"),
+            (BASE_MAINNET.l1_fee_vault_address(), "420000000000000000000000000000000000001a"),
+        ];
+        for (vault, expected) in vaults {
+            assert_eq!(vault, Address::from_str(expected).unwrap());
+            // the vaults are predeploys, so their proxies are part of the genesis of the chain
+            let account = BASE_MAINNET.genesis.alloc.get(&vault).unwrap();
+            assert!(account.code.as_ref().is_some_and(|code| !code.is_empty()), "{vault}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn l1_attributes_deposit_addresses() {
+        use alloy_rlp::Decodable;
+
+        // the L1 attributes deposit transaction of an Ecotone block
+        let tx = hex!("7ef8f8a0b84fa363879a2159e341c50a32da3ea0d21765b7bd43db37f2e5e04e8848b1ee94deaddeaddeaddeaddeaddeaddeaddeaddead00019442000000000000000000000000000000000000158080830f424080b8a4440a5e20000f42400000000000000000000000040000000065c41f680000000000a03f6b00000000000000000000000000000000000000000000000000000000000000080000000000000000000000000000000000000000000000000000000535f4d983dea59eac60478a64ecfdcde8571e611404295350de7ed4ccb404296c1a84ab7a00000000000000000000000073b4168cc87f35cc239200a20eb841cded23493b");
+        assert_eq!(tx[0], 0x7e);
+        let mut fields = &tx[1..];
+        alloy_rlp::Header::decode(&mut fields).unwrap();
+        let _source_hash = B256::decode(&mut fields).unwrap();
+        let from = Address::decode(&mut fields).unwrap();
+        let to = Address::decode(&mut fields).unwrap();
+
+        for spec in [&*OP_MAINNET, &*BASE_MAINNET] {
+            assert_eq!(spec.l1_attributes_depositor_address(), from);
+            assert_eq!(spec.l1_block_info_address(), to);
+        }
+    }
+
+    #[test]
+    fn blob_counts() {
+        let cancun = 1710338135;
+        let counts = |spec: &ChainSpec, timestamp| {
+            (
+                spec.target_blob_count_at_timestamp(timestamp),
+                spec.max_blob_count_at_timestamp(timestamp),
+            )
+        };
+        assert_eq!(counts(&MAINNET, cancun - 1), (0, 0));
+        assert_eq!(counts(&MAINNET, cancun), (3, 6));
+
+        let prague = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Prague, ForkCondition::Timestamp(cancun + 100))
+            .build();
+        assert_eq!(counts(&prague, cancun + 99), (3, 6));
+        assert_eq!(counts(&prague, cancun + 100), (6, 9));
+
+        #[cfg(feature = "optimism")]
+        assert_eq!(counts(&BASE_MAINNET, u64::MAX), (0, 0));
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn op_chain_specs_by_id() {
+        for (chain, spec) in &OP_CHAIN_SPECS {
+            let chain_id = Chain::from_named(*chain).id();
+            assert_eq!(spec.chain.id(), chain_id);
+            assert!(Arc::ptr_eq(&op_chain_spec_by_id(chain_id).unwrap(), spec));
+        }
+        assert_eq!(op_chain_spec_by_id(8453).unwrap().genesis_hash(), BASE_MAINNET.genesis_hash());
+        assert!(op_chain_spec_by_id(MAINNET.chain.id()).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn op_chain_specs_by_genesis_hash() {
+        for (chain, spec) in &OP_CHAIN_SPECS {
+            let genesis_hash = spec.genesis_hash.expect("built-in specs pin their genesis hash");
+            let by_hash = op_chain_spec_by_genesis_hash(genesis_hash).unwrap();
+            assert!(Arc::ptr_eq(&by_hash, spec), "{chain}");
+            let by_id = op_chain_spec_by_id(Chain::from_named(*chain).id()).unwrap();
+            assert!(Arc::ptr_eq(&by_hash, &by_id), "{chain}");
+        }
+        let base = b256!("f712aa9241cc24369b143cf6dce85f0902a9731e70d66818a3a5845b296c73dd");
+        assert_eq!(op_chain_spec_by_genesis_hash(base).unwrap().chain, Chain::base_mainnet());
+        assert!(op_chain_spec_by_genesis_hash(MAINNET.genesis_hash()).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn regolith_active_at_timestamp() {
+        assert!(BASE_MAINNET.is_regolith_active_at_timestamp(0));
+
+        let regolith = |condition| {
+            ChainSpecBuilder::default()
+                .chain(BASE_MAINNET.chain)
+                .genesis(Genesis::default())
+                .with_fork(Hardfork::Regolith, condition)
+                .build()
+        };
+        let timestamp = regolith(ForkCondition::Timestamp(10));
+        assert!(!timestamp.is_regolith_active_at_timestamp(9));
+        assert!(timestamp.is_regolith_active_at_timestamp(10));
+        assert!(regolith(ForkCondition::Block(0)).is_regolith_active_at_timestamp(0));
+        assert!(!regolith(ForkCondition::Block(10)).is_regolith_active_at_timestamp(u64::MAX));
+        assert!(!regolith(ForkCondition::Never).is_regolith_active_at_timestamp(u64::MAX));
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_mainnet_next_block_base_fee() {
+        let canyon = 1704992401;
+        let parent = Header {
+            timestamp: canyon - 2,
+            gas_limit: 30_000_000,
+            gas_used: 30_000_000,
+            base_fee_per_gas: Some(1_000_000_000),
+            ..Default::default()
+        };
+
+        // the base fee rises by (30M - 5M) / 5M / max_change_denominator, which is 50 before
+        // Canyon and 250 after it
+        assert_eq!(BASE_MAINNET.next_block_base_fee(&parent, canyon - 1), Some(1_100_000_000));
+        assert_eq!(BASE_MAINNET.next_block_base_fee(&parent, canyon), Some(1_020_000_000));
+
+        let pre_london = Header { base_fee_per_gas: None, ..parent };
+        assert_eq!(
+            BASE_MAINNET.next_block_base_fee(&pre_london, canyon),
+            Some(EIP1559_INITIAL_BASE_FEE)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_mainnet_active_precompiles() {
+        let (ecotone, fjord) = (1710374401, 1720627201);
+        let p256 = address!("0000000000000000000000000000000000000100");
+
+        let precompiles = BASE_MAINNET.active_precompiles_at_timestamp(ecotone - 1);
+        assert_eq!(precompiles, (1..=9).map(Address::with_last_byte).collect::<Vec<_>>());
+
+        let precompiles = BASE_MAINNET.active_precompiles_at_timestamp(ecotone);
+        assert_eq!(precompiles.last(), Some(&Address::with_last_byte(0x0a)));
+        assert!(!precompiles.contains(&p256));
+        assert!(!BASE_MAINNET.active_precompiles_at_timestamp(fjord - 1).contains(&p256));
+
+        let precompiles = BASE_MAINNET.active_precompiles_at_timestamp(fjord);
+        assert_eq!(precompiles.len(), 11);
+        assert_eq!(precompiles.last(), Some(&p256));
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_mainnet_active_eips() {
+        let (ecotone, fjord) = (1710374401, 1720627201);
+        let block = 12_000_000;
+
+        let eips = BASE_MAINNET.active_eips_at(block, ecotone - 1);
+        assert!(eips.contains(&1559) && eips.contains(&3855));
+        assert!(!eips.contains(&4844));
+
+        let eips = BASE_MAINNET.active_eips_at(block, ecotone);
+        assert!(eips.contains(&4844) && eips.contains(&1153));
+        assert!(!eips.contains(&7212));
+        assert!(!eips.contains(&779));
+        assert!(BASE_MAINNET.active_eips_at(block, fjord).contains(&7212));
+
+        let isthmus = 1746806401;
+        assert!(!BASE_MAINNET.active_eips_at(block, isthmus - 1).contains(&7702));
+        let eips = BASE_MAINNET.active_eips_at(block, isthmus);
+        assert!(eips.contains(&2537) && eips.contains(&7702));
+        assert!(!eips.contains(&6110));
+
+        // the Prague EIPs are not active if Isthmus is not scheduled
+        let spec = ChainSpecBuilder::default()
+            .chain(BASE_MAINNET.chain)
+            .genesis(BASE_MAINNET.genesis.clone())
+            .holocene_activated()
+            .build();
+        let eips = spec.active_eips_at(block, isthmus);
+        assert!(eips.contains(&7212) && !eips.contains(&7702));
+    }
+
+    #[test]
+    fn mainnet_active_eips() {
+        assert_eq!(MAINNET.active_eips_at(0, 0), Vec::<u32>::new());
+        assert_eq!(MAINNET.active_eips_at(1_150_000, 0), [2, 7]);
+
+        let eips = MAINNET.active_eips_at(15_537_393, 0);
+        assert!(eips.contains(&1559) && !eips.contains(&1283) && !eips.contains(&3675));
+        assert!(eips.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(MAINNET.active_eips_at(15_537_394, 0).contains(&3675));
+
+        let eips = MAINNET.active_eips_at(19_426_587, 1710338135);
+        assert!(eips.contains(&4844) && !eips.contains(&7702));
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_mainnet_op_forks_active_at_timestamp() {
+        // Ecotone activated on Base mainnet on Mar 14 2024 00:00:01 UTC
+        let ecotone = 1710374401;
+        assert!(!BASE_MAINNET.is_ecotone_active_at_timestamp(ecotone - 1));
+        assert!(BASE_MAINNET.is_ecotone_active_at_timestamp(ecotone));
+
+        assert!(BASE_MAINNET.is_regolith_active_at_timestamp(0));
+        assert!(!BASE_MAINNET.is_canyon_active_at_timestamp(1704992400));
+        assert!(BASE_MAINNET.is_canyon_active_at_timestamp(1704992401));
+        assert!(!BASE_MAINNET.is_fjord_active_at_timestamp(1720627200));
+        assert!(BASE_MAINNET.is_fjord_active_at_timestamp(1720627201));
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn base_sepolia_genesis() {
+        let genesis = BASE_SEPOLIA.genesis_header();
+        assert_eq!(
+            genesis.hash_slow(),
+            b256!("0dcc9e089e30b90ddfc55be9a37dd15bc551aeee999d2e2b51414c54eaf934e4")
+        );
+        let base_fee = genesis
+            .next_block_base_fee(BASE_SEPOLIA.base_fee_params_at_timestamp(genesis.timestamp))
+            .unwrap();
+        // <https://base-sepolia.blockscout.com/block/1>
+        assert_eq!(base_fee, 980000000);
+    }
+
+    #[test]
+    #[cfg(feature = "optimism")]
+    fn op_sepolia_genesis() {
+        let genesis = OP_SEPOLIA.genesis_header();
+        assert_eq!(
+            genesis.hash_slow(),
+            b256!("102de6ffb001480cc9b8b548fd05c34cd4f46ae4aa91759393db90ea0409887d")
+        );
+        let base_fee = genesis
+            .next_block_base_fee(OP_SEPOLIA.base_fee_params_at_timestamp(genesis.timestamp))
+            .unwrap();
+        // <https://optimism-sepolia.blockscout.com/block/1>
+        assert_eq!(base_fee, 980000000);
+    }
+
+    #[test]
+    fn latest_eth_mainnet_fork_id() {
+        assert_eq!(
+            ForkId { hash: ForkHash([0x9f, 0x3d, 0x22, 0x54]), next: 0 },
+            MAINNET.latest_fork_id()
+        )
+    }
+
+    #[cfg(feature = "optimism")]
     #[test]
     fn latest_base_mainnet_fork_id() {
         assert_eq!(
-            ForkId { hash: ForkHash([0xe4, 0x01, 0x0e, 0xb9]), next: 0 },
+            ForkId { hash: ForkHash([0x86, 0x72, 0x8b, 0x4e]), next: 0 },
             BASE_MAINNET.latest_fork_id()
         )
     }
@@ -3003,14 +4645,15 @@ Post-merge hard forks (timestamp based):
 
         let optimism_object = genesis.config.extra_fields.get("optimism").unwrap();
         let optimism_base_fee_info =
-            serde_json::from_value::<OptimismBaseFeeInfo>(optimism_object.clone()).unwrap();
+            serde_json::from_value::<OpGenesisConfig>(optimism_object.clone()).unwrap();
 
         assert_eq!(
             optimism_base_fee_info,
-            OptimismBaseFeeInfo {
+            OpGenesisConfig {
                 eip1559_elasticity: Some(6),
                 eip1559_denominator: Some(50),
                 eip1559_denominator_canyon: None,
+                l1_fee_recipient: None,
             }
         );
         assert_eq!(
@@ -3025,4 +4668,427 @@ Post-merge hard forks (timestamp based):
 
         assert!(chainspec.is_fork_active_at_timestamp(Hardfork::Regolith, 20));
     }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn optimism_from_genesis_json() {
+        let spec =
+            ChainSpec::from_genesis_json(include_str!("../res/genesis/sepolia_base.json")).unwrap();
+
+        assert_eq!(spec.genesis_hash, None);
+        assert_eq!(spec.genesis_hash(), BASE_SEPOLIA.genesis_hash());
+        assert_eq!(spec.chain, Chain::base_sepolia());
+        assert!(spec.is_optimism());
+        assert!(spec.is_fork_active_at_block(Hardfork::Bedrock, 0));
+        assert!(spec.is_fork_active_at_timestamp(Hardfork::Regolith, 0));
+        assert_eq!(spec.paris_block_and_final_difficulty, Some((0, U256::ZERO)));
+        assert_eq!(spec.prune_delete_limit, 1700);
+        assert_eq!(spec.base_fee_params, BaseFeeParams::new(50, 10).into());
+
+        assert!(ChainSpec::from_genesis_json("{").is_err());
+    }
+
+    #[test]
+    fn base_fee_params_override() {
+        let overrides =
+            BaseFeeParamsOverride { elasticity_multiplier: Some(4), ..Default::default() };
+        let spec = ChainSpecBuilder::mainnet().build().with_base_fee_params_override(&overrides);
+        assert_eq!(spec.base_fee_params, BaseFeeParams::new(8, 4).into());
+
+        let spec = spec.with_base_fee_params(BaseFeeParams::new(50, 6).into());
+        assert_eq!(spec.base_fee_params_at_timestamp(0), BaseFeeParams::new(50, 6));
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn optimism_base_fee_params_override() {
+        // only change the post-Canyon params
+        let overrides = BaseFeeParamsOverride {
+            canyon_max_change_denominator: Some(100),
+            ..Default::default()
+        };
+        let spec = (**BASE_MAINNET).clone().with_base_fee_params_override(&overrides);
+        assert_eq!(
+            spec.base_fee_params,
+            BaseFeeParamsKind::Variable(
+                vec![
+                    (Hardfork::London, BaseFeeParams::new(50, 6)),
+                    (Hardfork::Canyon, BaseFeeParams::new(100, 6)),
+                ]
+                .into()
+            )
+        );
+
+        // a constant value is turned into a schedule
+        let overrides = BaseFeeParamsOverride {
+            max_change_denominator: Some(10),
+            canyon_max_change_denominator: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(
+            BaseFeeParamsKind::from(BaseFeeParams::new(50, 6)).with_override(&overrides),
+            BaseFeeParamsKind::Variable(
+                vec![
+                    (Hardfork::London, BaseFeeParams::new(10, 6)),
+                    (Hardfork::Canyon, BaseFeeParams::new(20, 6)),
+                ]
+                .into()
+            )
+        );
+
+        // Ethereum hardforks are not post-Canyon, and the general denominator applies to all
+        // entries if no Canyon denominator is set
+        let schedule = BaseFeeParamsKind::Variable(
+            vec![
+                (Hardfork::London, BaseFeeParams::new(50, 6)),
+                (Hardfork::Canyon, BaseFeeParams::new(250, 6)),
+                (Hardfork::Cancun, BaseFeeParams::new(250, 6)),
+                (Hardfork::Holocene, BaseFeeParams::new(250, 6)),
+            ]
+            .into(),
+        );
+        assert_eq!(
+            schedule.clone().with_override(&overrides),
+            BaseFeeParamsKind::Variable(
+                vec![
+                    (Hardfork::London, BaseFeeParams::new(10, 6)),
+                    (Hardfork::Canyon, BaseFeeParams::new(20, 6)),
+                    (Hardfork::Cancun, BaseFeeParams::new(10, 6)),
+                    (Hardfork::Holocene, BaseFeeParams::new(20, 6)),
+                ]
+                .into()
+            )
+        );
+        let overrides =
+            BaseFeeParamsOverride { max_change_denominator: Some(10), ..Default::default() };
+        assert_eq!(
+            schedule.with_override(&overrides),
+            BaseFeeParamsKind::Variable(
+                vec![
+                    (Hardfork::London, BaseFeeParams::new(10, 6)),
+                    (Hardfork::Canyon, BaseFeeParams::new(10, 6)),
+                    (Hardfork::Cancun, BaseFeeParams::new(10, 6)),
+                    (Hardfork::Holocene, BaseFeeParams::new(10, 6)),
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn dev_genesis_account() {
+        let account = DEV.genesis_account(&address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"));
+        assert!(account.is_some_and(|account| !account.balance.is_zero()));
+        assert_eq!(DEV.genesis_account(&Address::ZERO), None);
+    }
+
+    #[test]
+    fn genesis_account_index() {
+        let index = DEV.genesis_account_index();
+        assert_eq!(index.len(), DEV.genesis.alloc.len());
+        for (address, account) in &DEV.genesis.alloc {
+            assert_eq!(index.get(address), Some(account));
+        }
+        assert_eq!(index.get(&Address::ZERO), None);
+    }
+
+    #[test]
+    fn genesis_total_allocated() {
+        assert_eq!(
+            MAINNET.genesis_total_allocated(),
+            U256::from(72_009_990_499_480_000_000_000_000u128)
+        );
+        assert_eq!(
+            DEV.genesis_total_allocated(),
+            U256::from(20_000_000_000_000_000_000_000_000u128)
+        );
+        // The allocation of Base mainnet only contains predeploys without balance
+        #[cfg(feature = "optimism")]
+        assert_eq!(BASE_MAINNET.genesis_total_allocated(), U256::ZERO);
+
+        let genesis = Genesis::default().extend_accounts([
+            (Address::with_last_byte(1), GenesisAccount::default().with_balance(U256::MAX)),
+            (Address::with_last_byte(2), GenesisAccount::default().with_balance(U256::MAX)),
+            (Address::with_last_byte(3), GenesisAccount::default()),
+        ]);
+        let spec = ChainSpecBuilder::mainnet().genesis(genesis).build();
+        assert_eq!(spec.genesis_total_allocated(), U256::MAX);
+    }
+
+    #[test]
+    fn verify_mainnet_genesis_hash() {
+        MAINNET.verify_genesis_hash().unwrap();
+        SEPOLIA.verify_genesis_hash().unwrap();
+        HOLESKY.verify_genesis_hash().unwrap();
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn verify_optimism_genesis_hash() {
+        OP_SEPOLIA.verify_genesis_hash().unwrap();
+        BASE_MAINNET.verify_genesis_hash().unwrap();
+        BASE_SEPOLIA.verify_genesis_hash().unwrap();
+
+        // the OP mainnet genesis has an empty alloc, the state at the bedrock block is imported
+        let err = OP_MAINNET.verify_genesis_hash().unwrap_err();
+        assert_eq!(err.expected, OP_MAINNET.genesis_hash());
+    }
+
+    #[test]
+    fn verify_genesis_hash_mismatch() {
+        let mut spec =
+            ChainSpec::builder().chain(Chain::mainnet()).genesis(Genesis::default()).build();
+        assert_eq!(spec.verify_genesis_hash(), Ok(()));
+
+        let computed = spec.genesis_header().hash_slow();
+        spec.genesis_hash = Some(B256::ZERO);
+        assert_eq!(
+            spec.verify_genesis_hash(),
+            Err(GenesisHashMismatch { expected: B256::ZERO, computed })
+        );
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn base_fee_params_from_op_hardforks() {
+        let london = BaseFeeParams::new(50, 6);
+        let canyon = BaseFeeParams::new(250, 6);
+        let fork_params = [(Hardfork::Canyon, canyon), (Hardfork::London, london)];
+
+        let hardforks = ChainSpecBuilder::mainnet().canyon_activated().build().hardforks;
+        assert_eq!(
+            BaseFeeParamsKind::from_op_hardforks(&hardforks, &fork_params),
+            BaseFeeParamsKind::Variable(
+                vec![(Hardfork::London, london), (Hardfork::Canyon, canyon)].into()
+            )
+        );
+
+        let hardforks = ChainSpecBuilder::mainnet().bedrock_activated().build().hardforks;
+        assert_eq!(
+            BaseFeeParamsKind::from_op_hardforks(&hardforks, &fork_params),
+            BaseFeeParamsKind::Variable(vec![(Hardfork::London, london)].into())
+        );
+
+        assert_eq!(
+            BASE_MAINNET.base_fee_params,
+            BaseFeeParamsKind::Variable(
+                vec![
+                    (Hardfork::London, BaseFeeParams::new(50, 6)),
+                    (Hardfork::Canyon, BaseFeeParams::new(250, 6))
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn optimism_paris_block_and_final_difficulty() {
+        for spec in [&*OP_MAINNET, &*OP_SEPOLIA, &*BASE_MAINNET, &*BASE_SEPOLIA] {
+            assert_eq!(spec.paris_block_and_final_difficulty, Some((0, U256::ZERO)));
+        }
+
+        let spec = ChainSpecBuilder::default()
+            .chain(Chain::from_id(1337))
+            .genesis(Genesis::default())
+            .op_hardforks(BASE_MAINNET_HARDFORKS)
+            .build();
+        assert_eq!(spec.paris_block_and_final_difficulty, Some((0, U256::ZERO)));
+
+        // no terminal total difficulty or merge netsplit block in the genesis
+        let spec = ChainSpec::from_genesis_json(r#"{"config":{"chainId":1337,"bedrockBlock":0}}"#)
+            .unwrap();
+        assert_eq!(spec.paris_block_and_final_difficulty, Some((0, U256::ZERO)));
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn op_chain_spec_builder() {
+        let spec = ChainSpecBuilder::default()
+            .chain(Chain::from_id(1337))
+            .genesis(Genesis::default())
+            .genesis_hash(B256::with_last_byte(1))
+            .op_hardforks([
+                (Hardfork::London, ForkCondition::Block(0)),
+                (Hardfork::Bedrock, ForkCondition::Block(0)),
+            ])
+            .op_base_fee_params(&crate::constants::optimism::OP_FORK_BASE_FEE_PARAMS)
+            .op_defaults()
+            .build();
+
+        assert_eq!(spec.genesis_hash(), B256::with_last_byte(1));
+        assert_eq!(spec.paris_block_and_final_difficulty, Some((0, U256::ZERO)));
+        assert_eq!(spec.prune_delete_limit, 1700);
+        assert_eq!(
+            spec.base_fee_params,
+            BaseFeeParamsKind::Variable(vec![(Hardfork::London, BaseFeeParams::new(50, 6))].into())
+        );
+    }
+
+    #[test]
+    fn fee_schedules_sorted() {
+        for spec in [&*MAINNET, &*SEPOLIA, &*HOLESKY, &*GOERLI, &*DEV] {
+            spec.assert_fee_schedule_sorted();
+        }
+        #[cfg(feature = "optimism")]
+        for spec in [&*OP_MAINNET, &*OP_SEPOLIA, &*BASE_MAINNET, &*BASE_SEPOLIA] {
+            spec.assert_fee_schedule_sorted();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "base fee params of London are listed after Shanghai")]
+    fn fee_schedule_unsorted() {
+        let spec = ChainSpecBuilder::mainnet()
+            .base_fee_params(BaseFeeParamsKind::Variable(
+                vec![
+                    (Hardfork::Shanghai, BaseFeeParams::new(250, 6)),
+                    (Hardfork::London, BaseFeeParams::ethereum()),
+                ]
+                .into(),
+            ))
+            .build();
+        spec.assert_fee_schedule_sorted();
+    }
+
+    /// Returns the hardforks of the spec that are part of a genesis `config`.
+    fn genesis_config_hardforks(spec: &ChainSpec) -> Vec<(Hardfork, ForkCondition)> {
+        spec.forks_iter().filter(|(fork, _)| *fork != Hardfork::Frontier).collect()
+    }
+
+    #[test]
+    fn mainnet_to_genesis_json() {
+        let spec = ChainSpec::from_genesis_json(&MAINNET.to_genesis_json().to_string()).unwrap();
+        assert_eq!(spec.chain, MAINNET.chain);
+        assert_eq!(genesis_config_hardforks(&spec), genesis_config_hardforks(&MAINNET));
+        assert_eq!(spec.genesis_hash(), MAINNET.genesis_hash());
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn base_mainnet_to_genesis_json() {
+        let json = BASE_MAINNET.to_genesis_json();
+        assert_eq!(
+            json["config"]["canyonTime"],
+            BASE_MAINNET.fork(Hardfork::Canyon).as_timestamp().unwrap()
+        );
+        assert_eq!(
+            json["config"]["optimism"],
+            serde_json::json!({
+                "eip1559Elasticity": 6,
+                "eip1559Denominator": 50,
+                "eip1559DenominatorCanyon": 250,
+            })
+        );
+
+        let spec = ChainSpec::from_genesis_json(&json.to_string()).unwrap();
+        assert_eq!(spec.chain, BASE_MAINNET.chain);
+        assert_eq!(genesis_config_hardforks(&spec), genesis_config_hardforks(&BASE_MAINNET));
+        assert_eq!(spec.base_fee_params, BASE_MAINNET.base_fee_params);
+        assert_eq!(
+            spec.paris_block_and_final_difficulty,
+            BASE_MAINNET.paris_block_and_final_difficulty
+        );
+        assert_eq!(spec.prune_delete_limit, BASE_MAINNET.prune_delete_limit);
+        assert_eq!(spec.genesis_hash(), BASE_MAINNET.genesis_hash());
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn base_mainnet_op_genesis_config() {
+        let config = *BASE_MAINNET.op_genesis_config().unwrap();
+        assert_eq!(
+            config,
+            OpGenesisConfig {
+                eip1559_elasticity: Some(6),
+                eip1559_denominator: Some(50),
+                eip1559_denominator_canyon: None,
+                l1_fee_recipient: None,
+            }
+        );
+        assert_eq!(
+            serde_json::to_value(config).unwrap(),
+            BASE_MAINNET.genesis.config.extra_fields["optimism"]
+        );
+        assert_eq!(MAINNET.op_genesis_config(), None);
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn op_genesis_config_l1_fee_recipient() {
+        let json = r#"{
+            "config": {
+                "chainId": 901,
+                "bedrockBlock": 0,
+                "optimism": {
+                    "eip1559Elasticity": 6,
+                    "eip1559Denominator": 50,
+                    "l1FeeRecipient": "0x000000000000000000000000000000000000dead"
+                }
+            },
+            "alloc": {}
+        }"#;
+        let spec = ChainSpec::from_genesis_json(json).unwrap();
+        let config = spec.op_genesis_config().unwrap();
+        assert_eq!(
+            config.l1_fee_recipient,
+            Some(address!("000000000000000000000000000000000000dead"))
+        );
+        assert_eq!(config.base_fee_params(), Some(BaseFeeParams::new(50, 6).into()));
+
+        let round_trip = ChainSpec::from_genesis_json(&spec.to_genesis_json().to_string()).unwrap();
+        assert_eq!(round_trip.op_genesis_config(), Some(config));
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn base_mainnet_hash() {
+        use std::collections::{hash_map::DefaultHasher, HashSet};
+
+        let build = || {
+            ChainSpecBuilder::default()
+                .chain(Chain::base_mainnet())
+                .genesis_json(include_str!("../res/genesis/base.json"))
+                .genesis_hash(b256!(
+                    "f712aa9241cc24369b143cf6dce85f0902a9731e70d66818a3a5845b296c73dd"
+                ))
+                .op_hardforks(BASE_MAINNET_HARDFORKS)
+                .op_base_fee_params(&OP_FORK_BASE_FEE_PARAMS)
+                .op_rollup_genesis(BASE_MAINNET_ROLLUP_GENESIS)
+                .op_defaults()
+                .build()
+        };
+        let hash = |spec: &ChainSpec| {
+            let mut hasher = DefaultHasher::new();
+            spec.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let (first, second) = (build(), build());
+        assert_eq!(first, second);
+        assert_eq!(hash(&first), hash(&second));
+        assert_eq!(first, **BASE_MAINNET);
+
+        let specs = HashSet::from([first, second, OP_MAINNET.as_ref().clone()]);
+        assert_eq!(specs.len(), 2);
+        assert!(specs.contains(&**BASE_MAINNET));
+    }
+
+    #[cfg(feature = "optimism")]
+    #[test]
+    fn base_mainnet_hardforks_with_overrides() {
+        let fjord = ForkCondition::Timestamp(1800000000);
+        let hardforks = BASE_MAINNET.hardforks_with_overrides(&[(Hardfork::Fjord, fjord)]);
+        assert_eq!(hardforks.get(&Hardfork::Fjord), Some(&fjord));
+        assert_eq!(hardforks.len(), BASE_MAINNET.hardforks.len());
+        assert!(hardforks
+            .iter()
+            .filter(|(fork, _)| **fork != Hardfork::Fjord)
+            .all(|(fork, condition)| BASE_MAINNET.hardforks.get(fork) == Some(condition)));
+
+        let spec = ChainSpecBuilder::from(&*BASE_MAINNET).op_hardforks(hardforks).build();
+        assert!(!spec.is_fjord_active_at_timestamp(1800000000 - 1));
+        assert!(spec.is_fjord_active_at_timestamp(1800000000));
+        assert!(BASE_MAINNET.is_fjord_active_at_timestamp(1800000000 - 1));
+    }
 }
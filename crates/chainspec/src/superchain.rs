@@ -0,0 +1,257 @@
+use crate::{ChainSpec, ChainSpecBuilder, OpGenesisConfig, OpRollupGenesis};
+use alloy_chains::Chain;
+use alloy_genesis::Genesis;
+use alloy_primitives::U256;
+use reth_ethereum_forks::{ForkCondition, Hardfork};
+use std::fmt;
+
+/// Error returned by [`ChainSpec::from_superchain_config`].
+#[derive(Debug)]
+pub enum SuperchainConfigError {
+    /// The config is not a valid TOML document, or misses a required field.
+    Toml(toml::de::Error),
+    /// The `optimism` section of the config does not set both the EIP-1559 elasticity and
+    /// denominator.
+    MissingBaseFeeParams,
+}
+
+impl fmt::Display for SuperchainConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Toml(err) => write!(f, "failed to parse superchain config: {err}"),
+            Self::MissingBaseFeeParams => f.write_str(
+                "superchain config does not set the EIP-1559 elasticity and denominator",
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SuperchainConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Toml(err) => Some(err),
+            Self::MissingBaseFeeParams => None,
+        }
+    }
+}
+
+/// The chain config of a Superchain registry entry, e.g. `configs/mainnet/base.toml`.
+///
+/// Only the fields that are part of a [`ChainSpec`] are read, all others are ignored.
+#[derive(serde::Deserialize)]
+struct SuperchainConfig {
+    chain_id: u64,
+    #[serde(default)]
+    hardforks: SuperchainHardforks,
+    optimism: SuperchainOptimism,
+    genesis: OpRollupGenesis,
+}
+
+/// The `hardforks` section of a [`SuperchainConfig`].
+///
+/// Forks without changes to the execution layer, like Delta, are ignored.
+#[derive(Default, serde::Deserialize)]
+struct SuperchainHardforks {
+    regolith_time: Option<u64>,
+    canyon_time: Option<u64>,
+    ecotone_time: Option<u64>,
+    fjord_time: Option<u64>,
+    granite_time: Option<u64>,
+    holocene_time: Option<u64>,
+    isthmus_time: Option<u64>,
+}
+
+/// The `optimism` section of a [`SuperchainConfig`], the snake case equivalent of the
+/// [`OpGenesisConfig`].
+#[derive(serde::Deserialize)]
+struct SuperchainOptimism {
+    eip1559_elasticity: Option<u64>,
+    eip1559_denominator: Option<u64>,
+    eip1559_denominator_canyon: Option<u64>,
+}
+
+impl SuperchainConfig {
+    /// Returns the hardforks of the chain.
+    ///
+    /// The forks up to Berlin are active at genesis, while the forks from London up to Bedrock
+    /// activate at the L2 genesis block, which is the Bedrock block of chains that were migrated
+    /// to the OP stack. Regolith is active at genesis unless configured otherwise, and Shanghai,
+    /// Cancun and Prague activate with Canyon, Ecotone and Isthmus.
+    fn hardforks(&self) -> Vec<(Hardfork, ForkCondition)> {
+        let bedrock_block = self.genesis.l2.number;
+        let mut hardforks = [
+            Hardfork::Frontier,
+            Hardfork::Homestead,
+            Hardfork::Tangerine,
+            Hardfork::SpuriousDragon,
+            Hardfork::Byzantium,
+            Hardfork::Constantinople,
+            Hardfork::Petersburg,
+            Hardfork::Istanbul,
+            Hardfork::MuirGlacier,
+            Hardfork::Berlin,
+        ]
+        .into_iter()
+        .map(|fork| (fork, ForkCondition::Block(0)))
+        .chain(
+            [Hardfork::London, Hardfork::ArrowGlacier, Hardfork::GrayGlacier]
+                .into_iter()
+                .map(|fork| (fork, ForkCondition::Block(bedrock_block))),
+        )
+        .collect::<Vec<_>>();
+        hardforks.push((
+            Hardfork::Paris,
+            ForkCondition::TTD { fork_block: Some(bedrock_block), total_difficulty: U256::ZERO },
+        ));
+        hardforks.push((Hardfork::Bedrock, ForkCondition::Block(bedrock_block)));
+
+        let forks = &self.hardforks;
+        let time_hardforks = [
+            (Hardfork::Regolith, Some(forks.regolith_time.unwrap_or_default())),
+            (Hardfork::Shanghai, forks.canyon_time),
+            (Hardfork::Canyon, forks.canyon_time),
+            (Hardfork::Cancun, forks.ecotone_time),
+            (Hardfork::Ecotone, forks.ecotone_time),
+            (Hardfork::Fjord, forks.fjord_time),
+            (Hardfork::Granite, forks.granite_time),
+            (Hardfork::Holocene, forks.holocene_time),
+            (Hardfork::Prague, forks.isthmus_time),
+            (Hardfork::Isthmus, forks.isthmus_time),
+        ];
+        hardforks.extend(
+            time_hardforks
+                .into_iter()
+                .filter_map(|(fork, time)| time.map(|time| (fork, ForkCondition::Timestamp(time)))),
+        );
+        hardforks
+    }
+}
+
+impl ChainSpec {
+    /// Parses the chain config of a [Superchain registry] entry, e.g.
+    /// `configs/mainnet/base.toml`, into a chain spec.
+    ///
+    /// The spec is built from the chain id, the hardfork timestamps, the EIP-1559 params of the
+    /// `optimism` section and the rollup `genesis`, which becomes the
+    /// [`op_rollup_genesis`](Self::op_rollup_genesis) of the spec. The registry entry does not
+    /// contain the genesis state, so the genesis of the spec is empty and the hash of the L2
+    /// genesis block is pinned as the genesis hash instead.
+    ///
+    /// [Superchain registry]: https://github.com/ethereum-optimism/superchain-registry
+    pub fn from_superchain_config(toml: &str) -> Result<Self, SuperchainConfigError> {
+        let config: SuperchainConfig = toml::from_str(toml).map_err(SuperchainConfigError::Toml)?;
+        let SuperchainOptimism {
+            eip1559_elasticity,
+            eip1559_denominator,
+            eip1559_denominator_canyon,
+        } = config.optimism;
+        let op_genesis_config = OpGenesisConfig {
+            eip1559_elasticity,
+            eip1559_denominator,
+            eip1559_denominator_canyon,
+            l1_fee_recipient: None,
+        };
+        let base_fee_params = op_genesis_config
+            .base_fee_params()
+            .ok_or(SuperchainConfigError::MissingBaseFeeParams)?;
+
+        let mut genesis = Genesis {
+            timestamp: config.genesis.l2_time,
+            gas_limit: config.genesis.system_config.gas_limit.into(),
+            ..Default::default()
+        };
+        genesis.config.chain_id = config.chain_id;
+
+        Ok(ChainSpecBuilder::default()
+            .chain(Chain::from_id(config.chain_id))
+            .genesis(genesis)
+            .genesis_hash(config.genesis.l2.hash)
+            .op_hardforks(config.hardforks())
+            .base_fee_params(base_fee_params)
+            .op_rollup_genesis(config.genesis)
+            .op_genesis_config(op_genesis_config)
+            .op_defaults()
+            .build())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BASE_MAINNET;
+    use reth_ethereum_forks::Head;
+
+    const BASE_MAINNET_CONFIG: &str = r#"
+        name = "Base"
+        public_rpc = "https://mainnet.base.org"
+        chain_id = 8453
+        block_time = 2
+
+        [hardforks]
+          canyon_time = 1704992401
+          delta_time = 1708560000
+          ecotone_time = 1710374401
+          fjord_time = 1720627201
+          granite_time = 1726070401
+          holocene_time = 1736445601
+          isthmus_time = 1746806401
+
+        [optimism]
+          eip1559_elasticity = 6
+          eip1559_denominator = 50
+          eip1559_denominator_canyon = 250
+
+        [genesis]
+          l2_time = 1686789347
+          [genesis.l1]
+            hash = "0x5c13d307623a926cd31415036c8b7fa14572f9dac64528e857a470511fc30771"
+            number = 17481768
+          [genesis.l2]
+            hash = "0xf712aa9241cc24369b143cf6dce85f0902a9731e70d66818a3a5845b296c73dd"
+            number = 0
+          [genesis.system_config]
+            batcherAddress = "0x5050F69a9786F081509234F1a7F4684b5E5b76C9"
+            overhead = "0x00000000000000000000000000000000000000000000000000000000000000bc"
+            scalar = "0x00000000000000000000000000000000000000000000000000000000000a6fe0"
+            gasLimit = 30000000
+    "#;
+
+    #[test]
+    fn base_mainnet_superchain_config() {
+        let spec = ChainSpec::from_superchain_config(BASE_MAINNET_CONFIG).unwrap();
+        assert_eq!(spec.chain, BASE_MAINNET.chain);
+        assert_eq!(spec.genesis_hash(), BASE_MAINNET.genesis_hash());
+        assert_eq!(spec.hardforks, BASE_MAINNET.hardforks);
+        assert_eq!(spec.base_fee_params, BASE_MAINNET.base_fee_params);
+        assert_eq!(spec.op_rollup_genesis(), BASE_MAINNET.op_rollup_genesis());
+        assert_eq!(spec.prune_delete_limit, BASE_MAINNET.prune_delete_limit);
+
+        assert!(spec.is_fork_active_at_timestamp(Hardfork::Granite, 1726070401));
+        assert!(spec.is_prague_active_at_timestamp(1746806401));
+
+        for timestamp in [0, 1704992401, 1710374401, 1726070401, 1746806401] {
+            let head = Head { timestamp, ..Default::default() };
+            assert_eq!(spec.fork_id(&head), BASE_MAINNET.fork_id(&head), "{timestamp}");
+        }
+    }
+
+    #[test]
+    fn superchain_config_without_isthmus() {
+        let config = BASE_MAINNET_CONFIG.replace("isthmus_time = 1746806401", "");
+        let spec = ChainSpec::from_superchain_config(&config).unwrap();
+        assert_eq!(spec.fork(Hardfork::Granite), ForkCondition::Timestamp(1726070401));
+        assert_eq!(spec.fork(Hardfork::Isthmus), ForkCondition::Never);
+        assert_eq!(spec.fork(Hardfork::Prague), ForkCondition::Never);
+    }
+
+    #[test]
+    fn invalid_superchain_config() {
+        let config = BASE_MAINNET_CONFIG.replace("eip1559_denominator = 50", "");
+        let err = ChainSpec::from_superchain_config(&config).unwrap_err();
+        assert!(matches!(err, SuperchainConfigError::MissingBaseFeeParams), "{err:?}");
+
+        let config = BASE_MAINNET_CONFIG.replace("chain_id = 8453", "");
+        let err = ChainSpec::from_superchain_config(&config).unwrap_err();
+        assert!(err.to_string().contains("chain_id"), "{err}");
+    }
+}
@@ -0,0 +1,183 @@
+use crate::ChainSpec;
+use alloy_eips::BlockNumHash;
+use alloy_genesis::Genesis;
+use alloy_primitives::{Address, B256};
+
+/// The key of the [`OpRollupGenesis`] in the genesis `config`.
+pub(crate) const ROLLUP_GENESIS_KEY: &str = "rollupGenesis";
+
+/// The genesis of an OP stack rollup: the L1 block its derivation starts from, its L2 genesis
+/// block and its initial [`OpSystemConfig`].
+///
+/// This is the `genesis` section of the rollup config of the chain, and is read from the
+/// `rollupGenesis` field of the genesis `config`, see [`ChainSpec::op_rollup_genesis`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "RollupGenesisJson", into = "RollupGenesisJson")]
+pub struct OpRollupGenesis {
+    /// The L1 block the derivation of the rollup starts from.
+    pub l1: BlockNumHash,
+    /// The L2 genesis block.
+    pub l2: BlockNumHash,
+    /// The timestamp of the L2 genesis block.
+    pub l2_time: u64,
+    /// The system config at the L2 genesis block.
+    pub system_config: OpSystemConfig,
+}
+
+impl OpRollupGenesis {
+    /// Extracts the rollup genesis from the given genesis, if it is present and valid.
+    pub(crate) fn extract_from(genesis: &Genesis) -> Option<Self> {
+        genesis.config.extra_fields.get_deserialized::<Self>(ROLLUP_GENESIS_KEY)?.ok()
+    }
+}
+
+/// The config of an OP stack rollup that is set on L1 in its `SystemConfig` contract, as of the
+/// L2 genesis block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpSystemConfig {
+    /// The address of the batcher, the only sender of batches the rollup derives blocks from.
+    #[serde(rename = "batcherAddr", alias = "batcherAddress")]
+    pub batcher_address: Address,
+    /// The fixed L1 fee overhead of every transaction, only used before Ecotone.
+    pub overhead: B256,
+    /// The L1 fee scalar.
+    pub scalar: B256,
+    /// The gas limit of the L2 blocks.
+    pub gas_limit: u64,
+}
+
+/// The json representation of an [`OpRollupGenesis`], as in the rollup config.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct RollupGenesisJson {
+    l1: NumHashJson,
+    l2: NumHashJson,
+    l2_time: u64,
+    system_config: OpSystemConfig,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct NumHashJson {
+    hash: B256,
+    number: u64,
+}
+
+impl From<RollupGenesisJson> for OpRollupGenesis {
+    fn from(json: RollupGenesisJson) -> Self {
+        let num_hash = |json: NumHashJson| BlockNumHash::new(json.number, json.hash);
+        Self {
+            l1: num_hash(json.l1),
+            l2: num_hash(json.l2),
+            l2_time: json.l2_time,
+            system_config: json.system_config,
+        }
+    }
+}
+
+impl From<OpRollupGenesis> for RollupGenesisJson {
+    fn from(genesis: OpRollupGenesis) -> Self {
+        let num_hash =
+            |num_hash: BlockNumHash| NumHashJson { hash: num_hash.hash, number: num_hash.number };
+        Self {
+            l1: num_hash(genesis.l1),
+            l2: num_hash(genesis.l2),
+            l2_time: genesis.l2_time,
+            system_config: genesis.system_config,
+        }
+    }
+}
+
+impl ChainSpec {
+    /// Returns the genesis of the rollup, if the spec is an OP stack chain whose genesis `config`
+    /// has a `rollupGenesis` field, or one of the built-in chains that ship it.
+    ///
+    /// The rollup genesis is parsed once when the spec is built.
+    pub const fn op_rollup_genesis(&self) -> Option<&OpRollupGenesis> {
+        self.op_rollup_genesis.as_ref()
+    }
+
+    /// Returns the system config at the L2 genesis block, see [`Self::op_rollup_genesis`].
+    pub const fn op_system_config(&self) -> Option<&OpSystemConfig> {
+        match &self.op_rollup_genesis {
+            Some(genesis) => Some(&genesis.system_config),
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChainSpecBuilder, BASE_MAINNET, OP_MAINNET};
+    use alloy_primitives::{address, b256};
+
+    #[test]
+    fn base_mainnet_system_config() {
+        let genesis = BASE_MAINNET.op_rollup_genesis().unwrap();
+        assert_eq!(genesis.l1.number, 17481768);
+        assert_eq!(
+            genesis.l1.hash,
+            b256!("5c13d307623a926cd31415036c8b7fa14572f9dac64528e857a470511fc30771")
+        );
+        assert_eq!(genesis.l2, BlockNumHash::new(0, BASE_MAINNET.genesis_hash()));
+        assert_eq!(genesis.l2_time, BASE_MAINNET.genesis_timestamp());
+
+        let system_config = BASE_MAINNET.op_system_config().unwrap();
+        assert_eq!(
+            system_config.batcher_address,
+            address!("5050f69a9786f081509234f1a7f4684b5e5b76c9")
+        );
+        assert_eq!(system_config.overhead, B256::with_last_byte(0xbc));
+        assert_eq!(system_config.scalar, B256::left_padding_from(&[0x0a, 0x6f, 0xe0]));
+        assert_eq!(system_config.gas_limit, 30_000_000);
+
+        assert_eq!(OP_MAINNET.op_system_config(), None);
+    }
+
+    #[test]
+    fn parse_rollup_genesis() {
+        let rollup_genesis = r#"{
+            "l1": {
+                "hash": "0x5c13d307623a926cd31415036c8b7fa14572f9dac64528e857a470511fc30771",
+                "number": 17481768
+            },
+            "l2": {
+                "hash": "0xf712aa9241cc24369b143cf6dce85f0902a9731e70d66818a3a5845b296c73dd",
+                "number": 0
+            },
+            "l2_time": 1686789347,
+            "system_config": {
+                "batcherAddr": "0x5050f69a9786f081509234f1a7f4684b5e5b76c9",
+                "overhead": "0x00000000000000000000000000000000000000000000000000000000000000bc",
+                "scalar": "0x00000000000000000000000000000000000000000000000000000000000a6fe0",
+                "gasLimit": 30000000
+            }
+        }"#;
+        let mut genesis = Genesis::default();
+        genesis
+            .config
+            .extra_fields
+            .insert(ROLLUP_GENESIS_KEY.to_string(), serde_json::from_str(rollup_genesis).unwrap());
+
+        // the spec parsed from the genesis has the system config of the built-in spec
+        let spec = ChainSpec::from(genesis);
+        assert_eq!(spec.op_rollup_genesis(), BASE_MAINNET.op_rollup_genesis());
+
+        // and it is written back to the genesis json
+        let json = spec.to_genesis_json();
+        let expected: serde_json::Value = serde_json::from_str(rollup_genesis).unwrap();
+        assert_eq!(json["config"][ROLLUP_GENESIS_KEY], expected);
+
+        let invalid = serde_json::json!({ "l1": { "number": 1 } });
+        let mut genesis = Genesis::default();
+        genesis.config.extra_fields.insert(ROLLUP_GENESIS_KEY.to_string(), invalid);
+        assert_eq!(ChainSpec::from(genesis).op_rollup_genesis(), None);
+
+        let spec = ChainSpecBuilder::default()
+            .chain(crate::Chain::base_mainnet())
+            .genesis(Genesis::default())
+            .op_rollup_genesis(*BASE_MAINNET.op_rollup_genesis().unwrap())
+            .build();
+        assert_eq!(spec.op_system_config(), BASE_MAINNET.op_system_config());
+    }
+}
@@ -2,7 +2,7 @@ use crate::{ForkCondition, Hardfork};
 use alloy_primitives::U256;
 
 /// Optimism mainnet hardforks
-pub const OP_MAINNET_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
+pub const OP_MAINNET_HARDFORKS: [(Hardfork, ForkCondition); 25] = [
     (Hardfork::Frontier, ForkCondition::Block(0)),
     (Hardfork::Homestead, ForkCondition::Block(0)),
     (Hardfork::Tangerine, ForkCondition::Block(0)),
@@ -27,10 +27,14 @@ pub const OP_MAINNET_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
     (Hardfork::Cancun, ForkCondition::Timestamp(1710374401)),
     (Hardfork::Ecotone, ForkCondition::Timestamp(1710374401)),
     (Hardfork::Fjord, ForkCondition::Timestamp(1720627201)),
+    (Hardfork::Granite, ForkCondition::Timestamp(1726070401)),
+    (Hardfork::Holocene, ForkCondition::Timestamp(1736445601)),
+    (Hardfork::Prague, ForkCondition::Timestamp(1746806401)),
+    (Hardfork::Isthmus, ForkCondition::Timestamp(1746806401)),
 ];
 
 /// Optimism Sepolia hardforks
-pub const OP_SEPOLIA_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
+pub const OP_SEPOLIA_HARDFORKS: [(Hardfork, ForkCondition); 25] = [
     (Hardfork::Frontier, ForkCondition::Block(0)),
     (Hardfork::Homestead, ForkCondition::Block(0)),
     (Hardfork::Tangerine, ForkCondition::Block(0)),
@@ -52,10 +56,14 @@ pub const OP_SEPOLIA_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
     (Hardfork::Cancun, ForkCondition::Timestamp(1708534800)),
     (Hardfork::Ecotone, ForkCondition::Timestamp(1708534800)),
     (Hardfork::Fjord, ForkCondition::Timestamp(1716998400)),
+    (Hardfork::Granite, ForkCondition::Timestamp(1723478400)),
+    (Hardfork::Holocene, ForkCondition::Timestamp(1732633200)),
+    (Hardfork::Prague, ForkCondition::Timestamp(1744905600)),
+    (Hardfork::Isthmus, ForkCondition::Timestamp(1744905600)),
 ];
 
 /// Base Sepolia hardforks
-pub const BASE_SEPOLIA_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
+pub const BASE_SEPOLIA_HARDFORKS: [(Hardfork, ForkCondition); 25] = [
     (Hardfork::Frontier, ForkCondition::Block(0)),
     (Hardfork::Homestead, ForkCondition::Block(0)),
     (Hardfork::Tangerine, ForkCondition::Block(0)),
@@ -77,10 +85,14 @@ pub const BASE_SEPOLIA_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
     (Hardfork::Cancun, ForkCondition::Timestamp(1708534800)),
     (Hardfork::Ecotone, ForkCondition::Timestamp(1708534800)),
     (Hardfork::Fjord, ForkCondition::Timestamp(1716998400)),
+    (Hardfork::Granite, ForkCondition::Timestamp(1723478400)),
+    (Hardfork::Holocene, ForkCondition::Timestamp(1732633200)),
+    (Hardfork::Prague, ForkCondition::Timestamp(1744905600)),
+    (Hardfork::Isthmus, ForkCondition::Timestamp(1744905600)),
 ];
 
 /// Base Mainnet hardforks
-pub const BASE_MAINNET_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
+pub const BASE_MAINNET_HARDFORKS: [(Hardfork, ForkCondition); 25] = [
     (Hardfork::Frontier, ForkCondition::Block(0)),
     (Hardfork::Homestead, ForkCondition::Block(0)),
     (Hardfork::Tangerine, ForkCondition::Block(0)),
@@ -102,4 +114,8 @@ pub const BASE_MAINNET_HARDFORKS: [(Hardfork, ForkCondition); 21] = [
     (Hardfork::Cancun, ForkCondition::Timestamp(1710374401)),
     (Hardfork::Ecotone, ForkCondition::Timestamp(1710374401)),
     (Hardfork::Fjord, ForkCondition::Timestamp(1720627201)),
+    (Hardfork::Granite, ForkCondition::Timestamp(1726070401)),
+    (Hardfork::Holocene, ForkCondition::Timestamp(1736445601)),
+    (Hardfork::Prague, ForkCondition::Timestamp(1746806401)),
+    (Hardfork::Isthmus, ForkCondition::Timestamp(1746806401)),
 ];
@@ -78,11 +78,23 @@ pub enum Hardfork {
     // ArbOS20Atlas,
 
     // Upcoming
-    /// Prague: <https://github.com/ethereum/execution-specs/blob/master/network-upgrades/mainnet-upgrades/prague.md>
-    Prague,
     /// Fjord: <https://github.com/ethereum-optimism/specs/blob/main/specs/protocol/superchain-upgrades.md#fjord>
     #[cfg(feature = "optimism")]
     Fjord,
+    /// Granite: <https://github.com/ethereum-optimism/specs/blob/main/specs/protocol/superchain-upgrades.md#granite>
+    #[cfg(feature = "optimism")]
+    Granite,
+    /// Holocene: <https://github.com/ethereum-optimism/specs/blob/main/specs/protocol/superchain-upgrades.md#holocene>
+    #[cfg(feature = "optimism")]
+    Holocene,
+    /// Prague: <https://github.com/ethereum/execution-specs/blob/master/network-upgrades/mainnet-upgrades/prague.md>
+    ///
+    /// Declared after the OP stack forks before Isthmus, which activates it on OP stack chains, so
+    /// that forks are ordered by activation.
+    Prague,
+    /// Isthmus: <https://github.com/ethereum-optimism/specs/blob/main/specs/protocol/superchain-upgrades.md#isthmus>
+    #[cfg(feature = "optimism")]
+    Isthmus,
 }
 
 impl Hardfork {
@@ -105,6 +117,22 @@ impl Hardfork {
         matches!(self.consensus_type(), ConsensusType::ProofOfWork)
     }
 
+    /// Checks if the hardfork is specific to the OP stack.
+    #[cfg(feature = "optimism")]
+    pub const fn is_optimism(&self) -> bool {
+        matches!(
+            self,
+            Self::Bedrock |
+                Self::Regolith |
+                Self::Canyon |
+                Self::Ecotone |
+                Self::Fjord |
+                Self::Granite |
+                Self::Holocene |
+                Self::Isthmus
+        )
+    }
+
     /// Retrieves the activation block for the specified hardfork on the given chain.
     pub fn activation_block(&self, chain: Chain) -> Option<u64> {
         if chain == Chain::mainnet() {
@@ -259,6 +287,9 @@ impl Hardfork {
             Self::Shanghai | Self::Canyon => Some(2106456),
             Self::Cancun | Self::Ecotone => Some(6383256),
             Self::Fjord => Some(10615056),
+            Self::Granite => Some(13855056),
+            Self::Holocene => Some(18432456),
+            Self::Prague | Self::Isthmus => Some(24568656),
             _ => None,
         }
     }
@@ -287,6 +318,10 @@ impl Hardfork {
             Self::Regolith => Some(0),
             Self::Shanghai | Self::Canyon => Some(9101527),
             Self::Cancun | Self::Ecotone => Some(11188936),
+            Self::Fjord => Some(16918927),
+            Self::Granite => Some(19640527),
+            Self::Holocene => Some(24828127),
+            Self::Prague | Self::Isthmus => Some(30008527),
             _ => None,
         }
     }
@@ -494,6 +529,9 @@ impl Hardfork {
             Self::Shanghai | Self::Canyon => Some(1699981200),
             Self::Cancun | Self::Ecotone => Some(1708534800),
             Self::Fjord => Some(1716998400),
+            Self::Granite => Some(1723478400),
+            Self::Holocene => Some(1732633200),
+            Self::Prague | Self::Isthmus => Some(1744905600),
             _ => None,
         }
     }
@@ -523,6 +561,9 @@ impl Hardfork {
             Self::Shanghai | Self::Canyon => Some(1704992401),
             Self::Cancun | Self::Ecotone => Some(1710374401),
             Self::Fjord => Some(1720627201),
+            Self::Granite => Some(1726070401),
+            Self::Holocene => Some(1736445601),
+            Self::Prague | Self::Isthmus => Some(1746806401),
             _ => None,
         }
     }
@@ -560,6 +601,12 @@ impl FromStr for Hardfork {
             "ecotone" => Self::Ecotone,
             #[cfg(feature = "optimism")]
             "fjord" => Self::Fjord,
+            #[cfg(feature = "optimism")]
+            "granite" => Self::Granite,
+            #[cfg(feature = "optimism")]
+            "holocene" => Self::Holocene,
+            #[cfg(feature = "optimism")]
+            "isthmus" => Self::Isthmus,
             "prague" => Self::Prague,
             // "arbos11" => Hardfork::ArbOS11,
             // "arbos20atlas" => Hardfork::ArbOS20Atlas,
@@ -630,13 +677,17 @@ mod tests {
     #[test]
     #[cfg(feature = "optimism")]
     fn check_op_hardfork_from_str() {
-        let hardfork_str = ["beDrOck", "rEgOlITH", "cAnYoN", "eCoToNe", "FJorD"];
+        let hardfork_str =
+            ["beDrOck", "rEgOlITH", "cAnYoN", "eCoToNe", "FJorD", "GrAnItE", "HoLoCeNe", "isTHMus"];
         let expected_hardforks = [
             Hardfork::Bedrock,
             Hardfork::Regolith,
             Hardfork::Canyon,
             Hardfork::Ecotone,
             Hardfork::Fjord,
+            Hardfork::Granite,
+            Hardfork::Holocene,
+            Hardfork::Isthmus,
         ];
 
         let hardforks: Vec<Hardfork> =
@@ -678,6 +729,9 @@ mod tests {
             Hardfork::Canyon,
             Hardfork::Ecotone,
             Hardfork::Fjord,
+            Hardfork::Granite,
+            Hardfork::Holocene,
+            Hardfork::Isthmus,
         ];
 
         for hardfork in &pow_hardforks {
@@ -1,7 +1,9 @@
 //! clap [Args](clap::Args) for benchmark configuration
 
+use crate::args::utils::chain_value_parser;
 use clap::Args;
-use std::path::PathBuf;
+use reth_chainspec::{ChainSpec, Hardfork};
+use std::{path::PathBuf, sync::Arc};
 
 /// Parameters for benchmark configuration
 #[derive(Debug, Args, PartialEq, Eq, Default, Clone)]
@@ -15,6 +17,77 @@ pub struct BenchmarkArgs {
     #[arg(long, verbatim_doc_comment)]
     pub to: Option<u64>,
 
+    /// The number of blocks at the start of the benchmark whose results are discarded.
+    ///
+    /// Warmup blocks are still sent to the engine, so the node state advances, only their
+    /// latencies and gas are left out of the results, e.g. to exclude the effects of cold caches.
+    #[arg(long, value_name = "BLOCKS", default_value_t = 0, verbatim_doc_comment)]
+    pub warmup: u64,
+
+    /// The chain of the benchmarked blocks.
+    ///
+    /// If set, the engine API version of every block is selected from the fork schedule of the
+    /// chain instead of the fields of the block, and the blocks are checked against it before
+    /// they are sent, e.g. blocks from before the merge fail with a clear error instead of being
+    /// rejected by the engine. Possible values are either a built-in chain or the path to a chain
+    /// specification file.
+    #[arg(
+        long,
+        value_name = "CHAIN_OR_PATH",
+        value_parser = chain_value_parser,
+        verbatim_doc_comment
+    )]
+    pub chain: Option<Arc<ChainSpec>>,
+
+    /// Activate a hardfork at a timestamp other than the one of `--chain` when selecting the
+    /// engine API version of a block, as `<FORK>=<TIMESTAMP>`, e.g. `prague=1740000000`.
+    ///
+    /// This must match the fork schedule the node was actually started with, e.g. on a devnet
+    /// with overridden fork timestamps: if the fork timing does not match, the node rejects the
+    /// blocks with an unsupported fork error, because every engine API version is only accepted
+    /// for the blocks of its own hardfork. Can be repeated for several hardforks.
+    #[arg(
+        long = "fork-override",
+        value_name = "FORK=TIMESTAMP",
+        value_parser = parse_fork_override,
+        requires = "chain",
+        verbatim_doc_comment
+    )]
+    pub fork_overrides: Vec<(Hardfork, u64)>,
+
+    /// Check the structure of every block against the chain of `--chain` before it is sent.
+    ///
+    /// This is a cheap local check of the fields that depend on the hardforks active at the
+    /// block, like the withdrawals and the blob gas fields, and of the block hash, so malformed
+    /// blocks fail without a round trip to the engine. The blocks are not executed.
+    #[arg(long, requires = "chain", verbatim_doc_comment)]
+    pub validate_payloads: bool,
+
+    /// Check that the engine accepts every block with the hash of the source block.
+    ///
+    /// The benchmark stops at the first block for which the latest valid hash returned by
+    /// `engine_newPayload` is not the hash of the block, which turns it into a correctness check
+    /// against the source data. The `fcu-only` benchmark checks the latest valid hash returned by
+    /// `engine_forkchoiceUpdated` against the new head instead.
+    #[arg(long, verbatim_doc_comment)]
+    pub verify_hashes: bool,
+
+    /// Re-send blocks with `engine_newPayloadV3` if the engine does not support
+    /// `engine_newPayloadV4`.
+    ///
+    /// This allows benchmarking a node without Prague support with the blocks before Prague. A
+    /// block that contains requests still fails, because it cannot be sent with
+    /// `engine_newPayloadV3`.
+    #[arg(long, verbatim_doc_comment)]
+    pub engine_version_fallback: bool,
+
+    /// Report the gas used by each type of transaction over the benchmarked blocks.
+    ///
+    /// The transactions are split into calls, contract creations, blob and deposit transactions.
+    /// This fetches the receipts of every block from the RPC in addition to the block.
+    #[arg(long, verbatim_doc_comment)]
+    pub tx_gas_breakdown: bool,
+
     /// Path to a JWT secret to use for the authenticated engine-API RPC server.
     ///
     /// This will perform JWT authentication for all requests to the given engine RPC url.
@@ -38,6 +111,15 @@ pub struct BenchmarkArgs {
     pub output: Option<PathBuf>,
 }
 
+/// Parses a hardfork activation timestamp override of the form `<FORK>=<TIMESTAMP>`.
+fn parse_fork_override(value: &str) -> eyre::Result<(Hardfork, u64)> {
+    let (fork, timestamp) = value
+        .split_once('=')
+        .ok_or_else(|| eyre::eyre!("expected <FORK>=<TIMESTAMP>, got {value}"))?;
+    let fork = fork.parse().map_err(|err: String| eyre::eyre!(err))?;
+    Ok((fork, timestamp.parse()?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,4 +141,38 @@ mod tests {
         let args = CommandParser::<BenchmarkArgs>::parse_from(["reth-bench"]).args;
         assert_eq!(args, default_args);
     }
+
+    #[test]
+    fn test_parse_benchmark_args_chain() {
+        let args =
+            CommandParser::<BenchmarkArgs>::parse_from(["reth-bench", "--chain", "dev"]).args;
+        assert_eq!(args.chain, Some(reth_chainspec::DEV.clone()));
+    }
+
+    #[test]
+    fn test_parse_benchmark_args_fork_overrides() {
+        let args = CommandParser::<BenchmarkArgs>::parse_from([
+            "reth-bench",
+            "--chain",
+            "dev",
+            "--fork-override",
+            "prague=20",
+            "--fork-override",
+            "Cancun=10",
+        ])
+        .args;
+        assert_eq!(args.fork_overrides, [(Hardfork::Prague, 20), (Hardfork::Cancun, 10)]);
+
+        for value in ["prague", "prague=soon", "unknown=20"] {
+            let args = ["reth-bench", "--chain", "dev", "--fork-override", value];
+            assert!(CommandParser::<BenchmarkArgs>::try_parse_from(args).is_err(), "{value}");
+        }
+    }
+
+    #[test]
+    fn test_parse_benchmark_args_warmup() {
+        let args =
+            CommandParser::<BenchmarkArgs>::parse_from(["reth-bench", "--warmup", "10"]).args;
+        assert_eq!(args.warmup, 10);
+    }
 }
@@ -1,6 +1,5 @@
 //! Clap parser utilities
 
-use alloy_genesis::Genesis;
 use reth_chainspec::ChainSpec;
 use reth_fs_util as fs;
 use reth_primitives::{BlockHashOrNumber, B256};
@@ -75,10 +74,8 @@ pub fn chain_value_parser(s: &str) -> eyre::Result<Arc<ChainSpec>, eyre::Error>
                 }
             };
 
-            // both serialized Genesis and ChainSpec structs supported
-            let genesis: Genesis = serde_json::from_str(&raw)?;
-
-            Arc::new(genesis.into())
+            // a serialized Genesis, including its `config` block
+            Arc::new(ChainSpec::from_genesis_json(&raw)?)
         }
     })
 }
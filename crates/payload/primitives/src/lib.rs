@@ -20,7 +20,8 @@ pub use traits::{BuiltPayload, PayloadAttributes, PayloadBuilderAttributes};
 mod payload;
 pub use payload::PayloadOrAttributes;
 
-use reth_chainspec::ChainSpec;
+use reth_chainspec::{ChainSpec, Hardfork};
+use std::collections::BTreeMap;
 
 /// The types that are used by the engine API.
 pub trait PayloadTypes: Send + Sync + Unpin + core::fmt::Debug + Clone {
@@ -336,6 +337,46 @@ pub enum EngineApiMessageVersion {
     V4,
 }
 
+impl EngineApiMessageVersion {
+    /// Returns the version of the engine API messages for a payload with the given timestamp,
+    /// which is the version added in the latest hardfork that is active at the timestamp.
+    ///
+    /// Hardforks that the chain skipped are not taken into account, e.g. a chain that activates
+    /// Cancun without Shanghai uses [`Self::V3`] after Cancun.
+    pub fn for_timestamp(chain_spec: &ChainSpec, timestamp: u64) -> Self {
+        Self::for_timestamp_with_overrides(chain_spec, &BTreeMap::new(), timestamp)
+    }
+
+    /// Returns the version of the engine API messages for a payload with the given timestamp like
+    /// [`Self::for_timestamp`], but activates the hardforks in `overrides` at the given timestamps
+    /// instead of the ones of the chain spec.
+    ///
+    /// This allows matching the actual configuration of a node that was started with a different
+    /// fork schedule than its chain spec, e.g. a devnet with an overridden Cancun time. If the
+    /// fork timing does not match the node, the node rejects the payloads with an unsupported
+    /// fork error, because every engine API version is only accepted for the payloads of its own
+    /// hardfork.
+    pub fn for_timestamp_with_overrides(
+        chain_spec: &ChainSpec,
+        overrides: &BTreeMap<Hardfork, u64>,
+        timestamp: u64,
+    ) -> Self {
+        let is_active = |fork| match overrides.get(&fork) {
+            Some(activation) => timestamp >= *activation,
+            None => chain_spec.is_fork_active_at_timestamp(fork, timestamp),
+        };
+        if is_active(Hardfork::Prague) {
+            Self::V4
+        } else if is_active(Hardfork::Cancun) {
+            Self::V3
+        } else if is_active(Hardfork::Shanghai) {
+            Self::V2
+        } else {
+            Self::V1
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -344,4 +385,58 @@ mod tests {
     fn version_ord() {
         assert!(EngineApiMessageVersion::V4 > EngineApiMessageVersion::V3);
     }
+
+    #[test]
+    fn version_for_timestamp() {
+        use reth_chainspec::{ChainSpecBuilder, ForkCondition, Hardfork};
+
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(10))
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(20))
+            .with_fork(Hardfork::Prague, ForkCondition::Timestamp(30))
+            .build();
+        let version = |timestamp| EngineApiMessageVersion::for_timestamp(&chain_spec, timestamp);
+        assert_eq!(version(9), EngineApiMessageVersion::V1);
+        assert_eq!(version(10), EngineApiMessageVersion::V2);
+        assert_eq!(version(19), EngineApiMessageVersion::V2);
+        assert_eq!(version(20), EngineApiMessageVersion::V3);
+        assert_eq!(version(30), EngineApiMessageVersion::V4);
+
+        // a chain that skips Shanghai goes straight from V1 to V3
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .without_fork(Hardfork::Shanghai)
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(20))
+            .build();
+        assert_eq!(
+            EngineApiMessageVersion::for_timestamp(&chain_spec, 19),
+            EngineApiMessageVersion::V1
+        );
+        assert_eq!(
+            EngineApiMessageVersion::for_timestamp(&chain_spec, 20),
+            EngineApiMessageVersion::V3
+        );
+    }
+
+    #[test]
+    fn version_for_timestamp_with_overrides() {
+        use reth_chainspec::{ChainSpecBuilder, ForkCondition, Hardfork};
+
+        let chain_spec = ChainSpecBuilder::mainnet()
+            .with_fork(Hardfork::Shanghai, ForkCondition::Timestamp(10))
+            .with_fork(Hardfork::Cancun, ForkCondition::Timestamp(20))
+            .build();
+        // the node activates Cancun later and Prague, which the chain spec does not schedule
+        let overrides = BTreeMap::from([(Hardfork::Cancun, 25), (Hardfork::Prague, 30)]);
+        let version = |timestamp| {
+            EngineApiMessageVersion::for_timestamp_with_overrides(
+                &chain_spec,
+                &overrides,
+                timestamp,
+            )
+        };
+        assert_eq!(version(10), EngineApiMessageVersion::V2);
+        assert_eq!(version(20), EngineApiMessageVersion::V2);
+        assert_eq!(version(25), EngineApiMessageVersion::V3);
+        assert_eq!(version(30), EngineApiMessageVersion::V4);
+    }
 }
@@ -11,7 +11,10 @@ pub fn revm_spec_by_timestamp_after_merge(
 ) -> revm_primitives::SpecId {
     #[cfg(feature = "optimism")]
     if chain_spec.is_optimism() {
-        return if chain_spec.fork(Hardfork::Fjord).active_at_timestamp(timestamp) {
+        // Isthmus activates the changes of L1's Prague hardfork
+        return if chain_spec.fork(Hardfork::Prague).active_at_timestamp(timestamp) {
+            revm_primitives::PRAGUE
+        } else if chain_spec.fork(Hardfork::Fjord).active_at_timestamp(timestamp) {
             revm_primitives::FJORD
         } else if chain_spec.fork(Hardfork::Ecotone).active_at_timestamp(timestamp) {
             revm_primitives::ECOTONE
@@ -39,7 +42,9 @@ pub fn revm_spec_by_timestamp_after_merge(
 pub fn revm_spec(chain_spec: &ChainSpec, block: Head) -> revm_primitives::SpecId {
     #[cfg(feature = "optimism")]
     if chain_spec.is_optimism() {
-        if chain_spec.fork(Hardfork::Fjord).active_at_head(&block) {
+        if chain_spec.fork(Hardfork::Prague).active_at_head(&block) {
+            return revm_primitives::PRAGUE
+        } else if chain_spec.fork(Hardfork::Fjord).active_at_head(&block) {
             return revm_primitives::FJORD
         } else if chain_spec.fork(Hardfork::Ecotone).active_at_head(&block) {
             return revm_primitives::ECOTONE
@@ -119,6 +124,10 @@ mod tests {
                 let cs = ChainSpecBuilder::mainnet().chain(reth_chainspec::Chain::from_id(10));
                 f(cs).build()
             }
+            assert_eq!(
+                revm_spec_by_timestamp_after_merge(&op_cs(|cs| cs.isthmus_activated()), 0),
+                revm_primitives::PRAGUE
+            );
             assert_eq!(
                 revm_spec_by_timestamp_after_merge(&op_cs(|cs| cs.fjord_activated()), 0),
                 revm_primitives::FJORD
@@ -205,6 +214,10 @@ mod tests {
                 let cs = ChainSpecBuilder::mainnet().chain(reth_chainspec::Chain::from_id(10));
                 f(cs).build()
             }
+            assert_eq!(
+                revm_spec(&op_cs(|cs| cs.isthmus_activated()), Head::default()),
+                revm_primitives::PRAGUE
+            );
             assert_eq!(
                 revm_spec(&op_cs(|cs| cs.fjord_activated()), Head::default()),
                 revm_primitives::FJORD
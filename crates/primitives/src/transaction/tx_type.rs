@@ -1,6 +1,7 @@
 use crate::{U64, U8};
 use alloy_rlp::{Decodable, Encodable};
 use bytes::Buf;
+use reth_chainspec::{ChainSpec, Hardfork};
 use reth_codecs::{derive_arbitrary, Compact};
 use serde::{Deserialize, Serialize};
 
@@ -60,6 +61,21 @@ impl TxType {
             Self::Deposit => false,
         }
     }
+
+    /// Check if transactions of this type are charged the base fee in the block with the given
+    /// number.
+    ///
+    /// No transaction is charged the base fee before London. Optimism deposit transactions are
+    /// never charged the base fee, their gas is paid for on L1.
+    pub fn is_base_fee_charged(&self, chain_spec: &ChainSpec, block_number: u64) -> bool {
+        match self {
+            Self::Legacy | Self::Eip2930 | Self::Eip1559 | Self::Eip4844 => {
+                chain_spec.is_fork_active_at_block(Hardfork::London, block_number)
+            }
+            #[cfg(feature = "optimism")]
+            Self::Deposit => false,
+        }
+    }
 }
 
 impl From<TxType> for u8 {
@@ -273,6 +289,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_is_base_fee_charged() {
+        use reth_chainspec::MAINNET;
+
+        let london_block = 12_965_000;
+        for tx_type in [TxType::Legacy, TxType::Eip2930, TxType::Eip1559, TxType::Eip4844] {
+            assert!(!tx_type.is_base_fee_charged(&MAINNET, london_block - 1));
+            assert!(tx_type.is_base_fee_charged(&MAINNET, london_block));
+        }
+
+        #[cfg(feature = "optimism")]
+        assert!(!TxType::Deposit.is_base_fee_charged(&reth_chainspec::BASE_MAINNET, london_block));
+    }
+
     #[test]
     fn decode_tx_type() {
         // Test for Legacy transaction
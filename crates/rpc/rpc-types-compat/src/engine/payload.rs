@@ -114,10 +114,13 @@ pub fn try_payload_v4_to_block(payload: ExecutionPayloadV4) -> Result<Block, Pay
     Ok(block)
 }
 
-/// Converts [`SealedBlock`] to [`ExecutionPayload`]
+/// Converts [`SealedBlock`] to [`ExecutionPayload`], and returns the parent beacon block root if
+/// the payload is a V3 or V4 payload.
 pub fn block_to_payload(value: SealedBlock) -> (ExecutionPayload, Option<B256>) {
     if value.header.requests_root.is_some() {
-        (ExecutionPayload::V4(block_to_payload_v4(value)), None)
+        // block with requests root: V4
+        let parent_beacon_block_root = value.header.parent_beacon_block_root;
+        (ExecutionPayload::V4(block_to_payload_v4(value)), parent_beacon_block_root)
     } else if value.header.parent_beacon_block_root.is_some() {
         // block with parent beacon block root: V3
         let (payload, beacon_block_root) = block_to_payload_v3(value);